@@ -0,0 +1,136 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use crate::utils::path_utils;
+
+/// Known cloud-ignore attributes and the sync clients that honour them
+///
+/// Names are given without the `user.` namespace Linux requires.
+const CLOUD_IGNORE_ATTRIBUTES: &[(&str, &str)] = &[
+    ("com.dropbox.ignored", "Dropbox"),
+    ("com.apple.fileprovider.ignore#P", "File Provider"),
+];
+
+/// A path carrying more than one cloud-ignore attribute
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkerConflict {
+    pub path: PathBuf,
+    /// The cloud-ignore attributes present, as stored on disk
+    pub attributes: Vec<String>,
+}
+
+/// Sync client a cloud-ignore attribute belongs to, or `None` if it isn't one
+///
+/// Besides the known attributes, any other attribute with "ignore" in its name is
+/// reported as belonging to an unrecognized client.
+pub fn cloud_ignore_client(attr: &str) -> Option<&'static str> {
+    let name = attr.strip_prefix("user.").unwrap_or(attr);
+    CLOUD_IGNORE_ATTRIBUTES
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, client)| *client)
+        .or_else(|| {
+            name.to_ascii_lowercase()
+                .contains("ignore")
+                .then_some("unrecognized client")
+        })
+}
+
+/// List the cloud-ignore attributes present on a path, without following symlinks
+///
+/// Always empty on platforms without extended attributes.
+pub fn cloud_ignore_attributes(path: &Path) -> Vec<String> {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        let Ok(names) = xattr::list(path) else {
+            return Vec::new();
+        };
+        let mut attributes: Vec<String> = names
+            .filter_map(|name| name.into_string().ok())
+            .filter(|name| cloud_ignore_client(name).is_some())
+            .collect();
+        attributes.sort();
+        attributes
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = path;
+        Vec::new()
+    }
+}
+
+/// Find files and directories under `root` carrying conflicting cloud-ignore attributes
+pub fn find_conflicts(root: &Path) -> Vec<MarkerConflict> {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        use ignore::WalkBuilder;
+
+        let walker = WalkBuilder::new(root)
+            .standard_filters(false)
+            .hidden(false)
+            .filter_entry(|entry| !path_utils::is_internal_dir_name(entry.file_name()))
+            .build();
+
+        let mut conflicts: Vec<MarkerConflict> = walker
+            .flatten()
+            .filter_map(|entry| {
+                let attributes = cloud_ignore_attributes(entry.path());
+                (attributes.len() > 1).then(|| MarkerConflict {
+                    path: entry.path().to_path_buf(),
+                    attributes,
+                })
+            })
+            .collect();
+        conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+        conflicts
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = root;
+        Vec::new()
+    }
+}
+
+/// Main entry point for `--detect-conflicts`
+///
+/// Returns whether any conflicts were found. With `silent`, nothing is printed.
+pub fn show_conflicts(silent: bool) -> Result<bool> {
+    let current_path = path_utils::current_dir()?;
+    let conflicts = find_conflicts(&current_path);
+    if silent {
+        return Ok(!conflicts.is_empty());
+    }
+
+    if conflicts.is_empty() {
+        println!(
+            "{} No files carry conflicting cloud-ignore attributes",
+            "✓".green()
+        );
+        return Ok(false);
+    }
+
+    println!(
+        "{} {} path(s) carry multiple cloud-ignore attributes:",
+        "⚠".yellow(),
+        conflicts.len()
+    );
+    for conflict in &conflicts {
+        let relative = conflict
+            .path
+            .strip_prefix(&current_path)
+            .unwrap_or(&conflict.path);
+        println!("  {} {}", "✗".red(), relative.display());
+        for attr in &conflict.attributes {
+            println!(
+                "      {} ({})",
+                attr,
+                cloud_ignore_client(attr).unwrap_or_default().dimmed()
+            );
+        }
+    }
+
+    Ok(true)
+}
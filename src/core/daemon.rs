@@ -1,20 +1,41 @@
-use crate::utils::json_utils;
+use crate::core::state_location::{self, GlobalConfig};
+use crate::utils::{json_utils, path_utils};
 use anyhow::{Context, Result};
+use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// How often a running daemon refreshes its heartbeat
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A heartbeat older than this means the daemon is alive but stalled
+const HEARTBEAT_STALE_AFTER_SECS: i64 = 60;
+
+/// Size the daemon log is kept under unless `--log-max-size` says otherwise
+pub const DEFAULT_LOG_MAX_SIZE: u64 = 1 << 20;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DaemonStatus {
     pub pid: u32,
     pub repo_path: PathBuf,
     pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Last time the daemon's event loop reported progress
+    #[serde(default)]
+    pub last_heartbeat: Option<chrono::DateTime<chrono::Utc>>,
+    /// Plain-text pidfile written for process supervisors (`--pidfile`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pidfile: Option<PathBuf>,
+    /// Log file the daemon's output goes to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<PathBuf>,
 }
 
 impl DaemonStatus {
     pub fn status_file_path(repo_path: &Path) -> PathBuf {
-        repo_path.join(".dbx-ignore").join("daemon.json")
+        state_location::state_dir(repo_path).join("daemon.json")
     }
 
     pub fn read(repo_path: &Path) -> Result<Option<Self>> {
@@ -64,6 +85,32 @@ impl DaemonStatus {
         Ok(())
     }
 
+    /// Rewrite the status file with a fresh heartbeat timestamp
+    ///
+    /// Does nothing if no status file exists (e.g. a foreground watcher).
+    pub fn refresh_heartbeat(repo_path: &Path) -> Result<()> {
+        let status_file = Self::status_file_path(repo_path);
+        if !status_file.exists() {
+            return Ok(());
+        }
+
+        let mut status = json_utils::read_json::<DaemonStatus>(&status_file)?;
+        status.last_heartbeat = Some(chrono::Utc::now());
+        status.write(repo_path)
+    }
+
+    /// Check if the daemon process is alive but its heartbeat has gone stale
+    ///
+    /// Status files written before heartbeats existed are treated as healthy.
+    pub fn is_stalled(&self) -> bool {
+        match self.last_heartbeat {
+            Some(heartbeat) => {
+                (chrono::Utc::now() - heartbeat).num_seconds() > HEARTBEAT_STALE_AFTER_SECS
+            }
+            None => false,
+        }
+    }
+
     pub fn remove(repo_path: &Path) -> Result<()> {
         let status_file = Self::status_file_path(repo_path);
         if status_file.exists() {
@@ -73,6 +120,253 @@ impl DaemonStatus {
     }
 }
 
+/// A daemon recorded in the user-wide registry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegisteredDaemon {
+    pub pid: u32,
+    pub repo_path: PathBuf,
+}
+
+/// Daemons started from any repository, kept in `daemons.json` in the user's dbx-ignore
+/// config directory so `--unwatch-all` can find them
+///
+/// Entries are added when a daemon is spawned and pruned when it is stopped; daemons
+/// that exit on their own leave entries behind until the next `--unwatch-all`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DaemonRegistry {
+    pub daemons: Vec<RegisteredDaemon>,
+}
+
+impl DaemonRegistry {
+    /// Get the registry file path, if a config directory is known
+    pub fn file_path() -> Option<PathBuf> {
+        GlobalConfig::config_dir().map(|dir| dir.join("daemons.json"))
+    }
+
+    /// Load the registry; a missing or unreadable file yields an empty one
+    pub fn load() -> Self {
+        Self::file_path()
+            .map(|path| json_utils::read_json_or_default(&path))
+            .unwrap_or_default()
+    }
+
+    /// Save the registry, creating the config directory if needed
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = Self::file_path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        json_utils::write_json_atomic(&path, self).context("Failed to write daemon registry")
+    }
+
+    /// Record a daemon, replacing any earlier entry for its repository
+    pub fn register(pid: u32, repo_path: &Path) -> Result<()> {
+        let repo_path = fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+        let mut registry = Self::load();
+        registry
+            .daemons
+            .retain(|daemon| daemon.repo_path != repo_path);
+        registry.daemons.push(RegisteredDaemon { pid, repo_path });
+        registry.save()
+    }
+
+    /// Drop the entry for `pid`, if there is one
+    pub fn unregister(pid: u32) -> Result<()> {
+        let mut registry = Self::load();
+        let before = registry.daemons.len();
+        registry.daemons.retain(|daemon| daemon.pid != pid);
+        if registry.daemons.len() == before {
+            return Ok(());
+        }
+        registry.save()
+    }
+}
+
+/// Main entry point for `--unwatch-all`
+///
+/// Stops every registered daemon that is still watching its repository. An entry whose
+/// process has exited, or whose PID now belongs to another process (the repository's
+/// status file names a different PID or none), is only pruned from the registry.
+pub fn unwatch_all(silent: bool) -> Result<()> {
+    let registry = DaemonRegistry::load();
+    let mut stopped = 0;
+    let mut stale = 0;
+
+    for daemon in &registry.daemons {
+        match DaemonStatus::read(&daemon.repo_path) {
+            Ok(Some(status)) if status.pid == daemon.pid => {
+                stop_daemon(status.pid)?;
+                DaemonStatus::remove(&daemon.repo_path)?;
+                set_paused(&daemon.repo_path, false)?;
+                if let Some(ref pidfile) = status.pidfile {
+                    remove_pidfile(pidfile, status.pid)?;
+                }
+                stopped += 1;
+                if !silent {
+                    println!(
+                        "{} Stopped daemon watcher for {} (PID: {})",
+                        "✓".green(),
+                        daemon.repo_path.display(),
+                        status.pid
+                    );
+                }
+            }
+            _ => stale += 1,
+        }
+    }
+
+    // Everything listed has been stopped or was already gone
+    DaemonRegistry::default().save()?;
+
+    if !silent {
+        if stopped == 0 {
+            println!("{} No active daemons found", "⚠".yellow());
+        }
+        if stale > 0 {
+            println!(
+                "{} Removed {} stale registry entry(ies) for daemons no longer running",
+                "✓".green(),
+                stale
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Control file whose presence tells a running watcher to hold off scanning
+pub fn pause_file_path(repo_path: &Path) -> PathBuf {
+    state_location::state_dir(repo_path).join("daemon.paused")
+}
+
+/// Check whether the watcher for a repository has been paused
+pub fn is_paused(repo_path: &Path) -> bool {
+    pause_file_path(repo_path).exists()
+}
+
+/// Pause or resume the watcher for a repository
+///
+/// The watcher checks the control file before each scan, so this takes effect without
+/// signalling the process. Changes seen while paused are scanned once it resumes.
+pub fn set_paused(repo_path: &Path, paused: bool) -> Result<()> {
+    let pause_file = pause_file_path(repo_path);
+    if paused {
+        if let Some(parent) = pause_file.parent() {
+            fs::create_dir_all(parent).context("Failed to create state directory")?;
+        }
+        fs::write(
+            &pause_file,
+            format!("{}\n", chrono::Utc::now().to_rfc3339()),
+        )
+        .context("Failed to write pause file")
+    } else if pause_file.exists() {
+        fs::remove_file(&pause_file).context("Failed to remove pause file")
+    } else {
+        Ok(())
+    }
+}
+
+/// Main entry point for `--pause` and `--resume`
+///
+/// Fails when no daemon is watching the current directory.
+pub fn pause_watcher(paused: bool, silent: bool) -> Result<()> {
+    let repo_path = path_utils::current_dir()?;
+    let Some(status) = DaemonStatus::read(&repo_path)? else {
+        anyhow::bail!("No active daemon found for this repository");
+    };
+
+    set_paused(&repo_path, paused)?;
+    if !silent {
+        if paused {
+            println!(
+                "{} Paused daemon watcher (PID: {}); run 'dbx-ignore --resume' to continue",
+                "⏸".yellow(),
+                status.pid
+            );
+        } else {
+            println!(
+                "{} Resumed daemon watcher (PID: {})",
+                "✓".green(),
+                status.pid
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Log file a spawned daemon writes its output to
+pub fn log_file_path(repo_path: &Path) -> PathBuf {
+    state_location::state_dir(repo_path).join("daemon.log")
+}
+
+/// Keep a log file under `max_size` bytes by dropping its oldest lines
+///
+/// Once the file grows past `max_size`, only the newest lines filling at most half of it
+/// are kept, so trimming doesn't happen on every write. The file is rewritten in place,
+/// which keeps the daemon's append-mode output pointing at it. Returns whether it was
+/// trimmed.
+pub fn trim_log(path: &Path, max_size: u64) -> Result<bool> {
+    use std::io::{Seek, Write};
+
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(false);
+    };
+    if metadata.len() <= max_size {
+        return Ok(false);
+    }
+
+    let contents = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let keep = usize::try_from(max_size / 2).unwrap_or(usize::MAX);
+    let cut = contents.len().saturating_sub(keep);
+    // Start at the first whole line inside the kept part
+    let start = contents[cut..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(contents.len(), |newline| cut + newline + 1);
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.set_len(0)
+        .and_then(|()| file.rewind())
+        .and_then(|()| file.write_all(&contents[start..]))
+        .with_context(|| format!("Failed to trim {}", path.display()))?;
+    Ok(true)
+}
+
+/// Open the daemon log for appending, creating the state directory if needed
+fn open_log_file(repo_path: &Path) -> Result<fs::File> {
+    let log_file = log_file_path(repo_path);
+    if let Some(parent) = log_file.parent() {
+        fs::create_dir_all(parent).context("Failed to create state directory")?;
+    }
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_file)
+        .with_context(|| format!("Failed to open daemon log: {}", log_file.display()))
+}
+
+/// Write `pid` to a plain-text pidfile, as process supervisors expect
+pub fn write_pidfile(path: &Path, pid: u32) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create pidfile directory")?;
+    }
+    fs::write(path, format!("{}\n", pid))
+        .with_context(|| format!("Failed to write pidfile: {}", path.display()))
+}
+
+/// Remove a pidfile, unless it has since been taken over by another process
+pub fn remove_pidfile(path: &Path, pid: u32) -> Result<()> {
+    match fs::read_to_string(path) {
+        Ok(contents) if contents.trim() == pid.to_string() => fs::remove_file(path)
+            .with_context(|| format!("Failed to remove pidfile: {}", path.display())),
+        _ => Ok(()),
+    }
+}
+
 /// Check if a process with the given PID is running
 #[cfg(unix)]
 fn is_process_running(pid: u32) -> bool {
@@ -102,9 +396,15 @@ fn is_process_running(pid: u32) -> bool {
 }
 
 /// Spawn a daemon process in the background
-pub fn spawn_daemon(repo_path: &Path) -> Result<u32> {
+///
+/// `daemon_args` are extra command-line options forwarded to the daemon process.
+pub fn spawn_daemon(repo_path: &Path, daemon_args: &[String]) -> Result<u32> {
     let exe_path = std::env::current_exe().context("Failed to get current executable path")?;
 
+    // Both output streams go to the daemon log
+    let log = open_log_file(repo_path)?;
+    let log_stderr = log.try_clone().context("Failed to open daemon log")?;
+
     #[cfg(unix)]
     {
         use std::os::unix::process::CommandExt;
@@ -112,14 +412,16 @@ pub fn spawn_daemon(repo_path: &Path) -> Result<u32> {
         let child = Command::new(&exe_path)
             .arg("--watch")
             .arg("--daemon-mode") // Special flag to indicate we're running as daemon
+            .args(daemon_args)
             .current_dir(repo_path)
             .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
+            .stdout(log)
+            .stderr(log_stderr)
             .process_group(0) // Create new process group
             .spawn()
             .context("Failed to spawn daemon process")?;
 
+        register_spawned(child.id(), repo_path);
         Ok(child.id())
     }
 
@@ -130,19 +432,32 @@ pub fn spawn_daemon(repo_path: &Path) -> Result<u32> {
         let child = Command::new(&exe_path)
             .arg("--watch")
             .arg("--daemon-mode")
+            .args(daemon_args)
             .current_dir(repo_path)
             .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
+            .stdout(log)
+            .stderr(log_stderr)
             .creation_flags(0x00000008 | 0x00000200) // DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP
             .spawn()
             .context("Failed to spawn daemon process")?;
 
+        register_spawned(child.id(), repo_path);
         Ok(child.id())
     }
 }
 
-/// Stop a running daemon
+/// Record a spawned daemon in the registry; the daemon runs either way
+fn register_spawned(pid: u32, repo_path: &Path) {
+    if let Err(e) = DaemonRegistry::register(pid, repo_path) {
+        eprintln!(
+            "{} Warning: Could not record the daemon for --unwatch-all: {:#}",
+            "⚠".yellow(),
+            e
+        );
+    }
+}
+
+/// Stop a running daemon and drop it from the registry
 pub fn stop_daemon(pid: u32) -> Result<()> {
     #[cfg(unix)]
     {
@@ -161,5 +476,7 @@ pub fn stop_daemon(pid: u32) -> Result<()> {
             .context("Failed to terminate daemon process")?;
     }
 
+    // A registry that can't be updated is pruned by the next --unwatch-all
+    let _ = DaemonRegistry::unregister(pid);
     Ok(())
 }
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::platforms::CurrentPlatform;
+use crate::traits::PlatformHandler;
+use crate::utils::{path_utils, platform_utils};
+
+/// Main entry point for `--doctor`
+///
+/// Prints what the tool detected about this system and how it will mark files here.
+pub fn show_doctor() -> Result<()> {
+    let current_path = path_utils::current_dir()?;
+
+    println!("{}", "dbx-ignore doctor".bold());
+    println!();
+
+    let platform = CurrentPlatform::platform_name();
+    if CurrentPlatform::is_supported() {
+        println!("{} Platform: {}", "✓".green(), platform);
+    } else {
+        println!("{} Platform: {} (not supported)", "✗".red(), platform);
+    }
+    println!(
+        "{} Ignore mechanism: {}",
+        "✓".green(),
+        CurrentPlatform::ignore_mechanism()
+    );
+
+    let attributes: Vec<&str> = CurrentPlatform::get_target_attributes()
+        .iter()
+        .copied()
+        .filter(|attr| CurrentPlatform::is_expected_attribute(attr))
+        .collect();
+    if attributes.is_empty() {
+        println!("{} Attribute written: none", "✗".red());
+    } else {
+        println!(
+            "{} Attribute written: {}",
+            "✓".green(),
+            attributes.join(", ")
+        );
+    }
+
+    let roots = platform_utils::detect_dropbox_roots();
+    if roots.is_empty() {
+        println!("{} Dropbox folders: none detected", "⚠".yellow());
+    } else {
+        println!("{} Dropbox folders:", "✓".green());
+        for root in &roots {
+            println!("    {}", root.display());
+        }
+    }
+
+    println!("  Current directory: {}", current_path.display());
+    match platform_utils::dropbox_root_of(&current_path) {
+        Some(folder) => println!(
+            "{} Inside Dropbox folder: {}",
+            "✓".green(),
+            folder.display()
+        ),
+        None => println!(
+            "{} Not inside a detected Dropbox folder; markers only take effect in synced folders",
+            "⚠".yellow()
+        ),
+    }
+    show_ignored_ancestor(&current_path);
+
+    Ok(())
+}
+
+/// Mention a marked ancestor, which already keeps everything here out of sync
+fn show_ignored_ancestor(current_path: &Path) {
+    if let Some(ignored) = platform_utils::find_ignored_ancestor(current_path) {
+        println!(
+            "{} {} is marked as ignored, so everything below it already is",
+            "⚠".yellow(),
+            ignored.display()
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use crate::core::repo_config::RepoConfig;
+use crate::core::tracked_files::TrackedFiles;
+use crate::utils::ignore_reason::GitignoreExplainer;
+use crate::utils::{git_utils, path_utils, platform_utils};
+
+/// Differences between tracked intent (`tracked_files.json`) and the markers on disk
+#[derive(Debug, Default)]
+pub struct DriftReport {
+    /// Tracked paths whose marker fell off
+    pub missing_markers: Vec<PathBuf>,
+    /// Marked paths that aren't tracked (marked outside the tool)
+    pub untracked_markers: Vec<PathBuf>,
+    /// Tracked paths that are still marked
+    pub consistent: Vec<PathBuf>,
+    /// Tracked paths that no longer exist (not counted as drift)
+    pub stale: Vec<PathBuf>,
+}
+
+impl DriftReport {
+    /// Compare tracked files against on-disk markers under `root`
+    pub fn gather(root: &Path) -> Result<Self> {
+        TrackedFiles::load(root)?.reconcile(root)
+    }
+
+    /// Re-apply the markers that fell off tracked files, written with `marker_value`
+    ///
+    /// Repaired paths move to `consistent`; returns the paths that still couldn't be
+    /// marked, with the error.
+    pub fn repair(&mut self, marker_value: &[u8]) -> Vec<(PathBuf, String)> {
+        let mut failures = Vec::new();
+        for path in std::mem::take(&mut self.missing_markers) {
+            match platform_utils::add_ignore_attributes(&path, true, marker_value) {
+                Ok(_) => self.consistent.push(path),
+                Err(e) => {
+                    failures.push((path.clone(), e.to_string()));
+                    self.missing_markers.push(path);
+                }
+            }
+        }
+        self.consistent.sort();
+        failures
+    }
+
+    /// Whether intent and reality disagree
+    pub fn has_drift(&self) -> bool {
+        !self.missing_markers.is_empty() || !self.untracked_markers.is_empty()
+    }
+
+    pub fn display(&self, root: &Path, verbose: bool) {
+        let relative = |path: &PathBuf| {
+            path.strip_prefix(root)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+
+        println!(
+            "{} {} tracked and marked, {} tracked but not marked, {} marked but not tracked",
+            "Drift:".blue().bold(),
+            self.consistent.len().to_string().green(),
+            self.missing_markers.len().to_string().red(),
+            self.untracked_markers.len().to_string().yellow()
+        );
+
+        if !self.missing_markers.is_empty() {
+            println!("{}", "Tracked but not marked (marker fell off):".red());
+            for path in &self.missing_markers {
+                println!("  {} {}", "✗".red(), relative(path));
+            }
+        }
+
+        if !self.untracked_markers.is_empty() {
+            println!(
+                "{}",
+                "Marked but not tracked (applied outside dbx-ignore):".yellow()
+            );
+            for path in &self.untracked_markers {
+                println!("  {} {}", "?".yellow(), relative(path));
+            }
+        }
+
+        if verbose {
+            if !self.consistent.is_empty() {
+                println!("{}", "Tracked and marked:".green());
+                for path in &self.consistent {
+                    println!("  {} {}", "✓".green(), relative(path));
+                }
+            }
+            if !self.stale.is_empty() {
+                println!("{}", "Tracked but no longer present:".dimmed());
+                for path in &self.stale {
+                    println!("  - {}", relative(path));
+                }
+            }
+        }
+
+        if !self.has_drift() {
+            println!("{}", "No drift: markers match tracked intent.".green());
+        }
+    }
+}
+
+/// Main entry point for the drift report; returns whether drift was found
+///
+/// With `silent`, nothing is printed and only the result is reported.
+pub fn show_drift(verbose: bool, silent: bool) -> Result<bool> {
+    show_verify(verbose, false, silent)
+}
+
+/// Main entry point for `--verify`; returns whether drift remains
+///
+/// With `repair`, markers that fell off tracked files are re-applied after the report.
+/// Markers applied outside the tool are only reported, never removed. With `silent`,
+/// nothing is printed.
+pub fn show_verify(verbose: bool, repair: bool, silent: bool) -> Result<bool> {
+    let current_path = path_utils::current_dir()?;
+    let mut report = TrackedFiles::load(&current_path)?.reconcile(&current_path)?;
+    if !silent {
+        report.display(&current_path, verbose);
+    }
+
+    if repair && !report.missing_markers.is_empty() {
+        let missing = report.missing_markers.len();
+        let marker_value = RepoConfig::load(&current_path)?.marker_value();
+        let failures = report.repair(&marker_value);
+        if silent {
+            return Ok(report.has_drift());
+        }
+        println!(
+            "{} Re-applied markers to {} tracked file(s)",
+            "✓".green(),
+            missing - failures.len()
+        );
+        for (path, error) in &failures {
+            eprintln!(
+                "  {} {}: {}",
+                "✗".red(),
+                path.strip_prefix(&current_path).unwrap_or(path).display(),
+                error
+            );
+        }
+    }
+
+    Ok(report.has_drift())
+}
+
+/// Git-ignored files under `root` that carry no marker
+///
+/// Files inside a marked directory count as marked. Only reads: neither markers nor
+/// the tracked state are changed.
+pub fn unmarked_gitignored_files(root: &Path) -> Result<Vec<PathBuf>> {
+    Ok(git_utils::get_git_ignored_files_in_path(root)?
+        .into_iter()
+        .filter(|path| platform_utils::find_ignored_ancestor(path).is_none())
+        .collect())
+}
+
+/// Unmarked files listed by `--check` before the rest is summarized (unless verbose)
+const CHECK_LIST_LIMIT: usize = 20;
+
+/// Main entry point for `--check`; returns whether any git-ignored file is unmarked
+///
+/// With `silent`, nothing is printed and only the result is reported.
+pub fn show_check(verbose: bool, silent: bool) -> Result<bool> {
+    let current_path = path_utils::current_dir()?;
+    let unmarked = unmarked_gitignored_files(&current_path)?;
+    if silent {
+        return Ok(!unmarked.is_empty());
+    }
+
+    if unmarked.is_empty() {
+        println!("{} Every git-ignored file is marked", "✓".green());
+        return Ok(false);
+    }
+
+    println!(
+        "{} {} git-ignored file(s) are not marked:",
+        "✗".red(),
+        unmarked.len()
+    );
+    let shown = if verbose {
+        unmarked.len()
+    } else {
+        CHECK_LIST_LIMIT
+    };
+    for path in unmarked.iter().take(shown) {
+        println!(
+            "  {}",
+            path.strip_prefix(&current_path).unwrap_or(path).display()
+        );
+    }
+    if unmarked.len() > shown {
+        println!(
+            "  ... and {} more (use --verbose to list all)",
+            unmarked.len() - shown
+        );
+    }
+    Ok(true)
+}
+
+/// Marked paths brought under the tool's management by `--import-markers`
+#[derive(Debug, Default)]
+pub struct MarkerImport {
+    /// Untracked marked paths added to the tracked files
+    pub imported: Vec<PathBuf>,
+    /// Untracked marked paths left alone because git doesn't ignore them
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Track the marked paths under `root` that were marked outside the tool
+///
+/// With `gitignored_only`, only paths git ignores are imported, so stray markers on
+/// source files aren't adopted. Nothing is saved with `dry_run`.
+pub fn import_markers(root: &Path, gitignored_only: bool, dry_run: bool) -> Result<MarkerImport> {
+    let mut tracked = TrackedFiles::load(root)?;
+    let report = tracked.reconcile(root)?;
+
+    let mut import = MarkerImport::default();
+    if gitignored_only {
+        git_utils::repo_root(root)?;
+        let mut explainer = GitignoreExplainer::new(root);
+        for path in report.untracked_markers {
+            if explainer.explain(&path).is_some() {
+                import.imported.push(path);
+            } else {
+                import.skipped.push(path);
+            }
+        }
+    } else {
+        import.imported = report.untracked_markers;
+    }
+
+    if !dry_run && !import.imported.is_empty() {
+        tracked.add_files(&import.imported);
+        tracked.save(root)?;
+    }
+    Ok(import)
+}
+
+/// Main entry point for `--import-markers`; with `silent`, nothing is printed
+pub fn show_import_markers(
+    gitignored_only: bool,
+    dry_run: bool,
+    verbose: bool,
+    silent: bool,
+) -> Result<()> {
+    let current_path = path_utils::current_dir()?;
+    let import = import_markers(&current_path, gitignored_only, dry_run)?;
+    if silent {
+        return Ok(());
+    }
+    let relative = |path: &PathBuf| {
+        path.strip_prefix(&current_path)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+
+    if import.imported.is_empty() && import.skipped.is_empty() {
+        println!("{} Every marked path is already tracked", "✓".green());
+        return Ok(());
+    }
+
+    let verb = if dry_run { "Would import" } else { "Imported" };
+    println!(
+        "{} {} {} marked path(s) into tracked files",
+        "✓".green(),
+        verb,
+        import.imported.len()
+    );
+    for path in &import.imported {
+        println!("  {} {}", "+".green(), relative(path));
+    }
+
+    if !import.skipped.is_empty() {
+        println!(
+            "{} {} marked path(s) aren't ignored by git and stay untracked",
+            "⚠".yellow(),
+            import.skipped.len()
+        );
+        if verbose {
+            for path in &import.skipped {
+                println!("  {} {}", "?".yellow(), relative(path));
+            }
+        }
+    }
+    Ok(())
+}
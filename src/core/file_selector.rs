@@ -0,0 +1,566 @@
+use anyhow::Result;
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::utils::pattern_matcher::{self, PatternMatcher};
+use crate::utils::platform_utils::AttributeCondition;
+use crate::utils::predicate::Predicate;
+use crate::utils::{dockerignore, git_utils, path_utils};
+
+/// Where a selection gets its candidate paths from
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// Paths as given on the command line: literal paths, glob patterns, `.` (the
+    /// visible entries of the current directory) and `.gitignore` files (what they ignore)
+    Paths {
+        paths: Vec<PathBuf>,
+        /// Never expand glob patterns (e.g. paths from `--files0-from`)
+        literal: bool,
+        /// Apply only a given `.gitignore`'s own rules, not inherited ones
+        local_only: bool,
+        /// Refuse paths that resolve outside this directory
+        confine_to: Option<PathBuf>,
+    },
+    /// Files git ignores under `root`
+    GitIgnored {
+        root: PathBuf,
+        recurse_submodules: bool,
+        max_depth: Option<usize>,
+    },
+    /// Files flagged skip-worktree or assume-unchanged in the git index of `root`
+    SkipWorktree(PathBuf),
+    /// Untracked and ignored entries git status lists under `root`
+    GitStatus(PathBuf),
+    /// Files a `.dockerignore` leaves out of the Docker build context
+    DockerIgnore {
+        file: PathBuf,
+        max_depth: Option<usize>,
+    },
+    /// Files under `root` matching gitignore-style patterns
+    Patterns {
+        root: PathBuf,
+        patterns: Vec<String>,
+        max_depth: Option<usize>,
+    },
+    /// Every file under `root`
+    Walk {
+        root: PathBuf,
+        max_depth: Option<usize>,
+    },
+}
+
+impl Source {
+    fn collect(&self, parallel_walk_threshold: usize) -> Result<Vec<PathBuf>> {
+        match self {
+            Source::Paths {
+                paths,
+                literal,
+                local_only,
+                confine_to,
+            } => get_files_from_paths(
+                paths,
+                *literal,
+                *local_only,
+                confine_to.as_deref(),
+                parallel_walk_threshold,
+            ),
+            Source::GitIgnored {
+                root,
+                recurse_submodules,
+                max_depth,
+            } => git_utils::get_git_ignored_files_scoped(root, *recurse_submodules, *max_depth),
+            Source::SkipWorktree(root) => git_utils::get_skip_worktree_files_in_path(root),
+            Source::GitStatus(root) => git_utils::get_untracked_and_ignored_in_path(root),
+            Source::DockerIgnore { file, max_depth } => {
+                dockerignore::get_files_from_dockerignore(file, *max_depth, parallel_walk_threshold)
+            }
+            Source::Patterns {
+                root,
+                patterns,
+                max_depth,
+            } => pattern_matcher::PatternMatcher::new(root, patterns, false)?
+                .with_max_depth(*max_depth)
+                .with_parallel_walk_threshold(parallel_walk_threshold)
+                .find_matching_files(root),
+            Source::Walk { root, max_depth } => Ok(path_utils::walk_files(
+                root,
+                *max_depth,
+                parallel_walk_threshold,
+            )),
+        }
+    }
+}
+
+/// Kind of filesystem entry, for [`Filter::Kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+}
+
+/// A condition every selected path must meet
+pub enum Filter {
+    /// Last modified before the cutoff (paths without a readable mtime are dropped)
+    ModifiedBefore(SystemTime),
+    /// Last modified at or after the cutoff (paths without a readable mtime are dropped)
+    ModifiedSince(SystemTime),
+    /// At least this many bytes, counting everything inside a directory
+    MinSize(u64),
+    /// At most this many bytes, counting everything inside a directory
+    MaxSize(u64),
+    /// Only files, or only directories
+    Kind(EntryKind),
+    /// Matching a `--where` expression
+    Where(Predicate),
+    /// Carrying an attribute, for `--where-xattr`
+    Attribute(AttributeCondition),
+    /// Not inside any of these paths (relative ones are resolved against the selector's base)
+    Exclude(Vec<PathBuf>),
+    /// Inside at least one of these paths (relative ones are resolved against the selector's base)
+    Allow(Vec<PathBuf>),
+    /// Any other condition, named for [`Selection::dropped_by`]
+    Custom {
+        name: &'static str,
+        keep: Box<dyn Fn(&Path) -> bool + Send + Sync>,
+    },
+}
+
+impl Filter {
+    /// Name the filter's drop count is reported under
+    pub fn name(&self) -> &'static str {
+        match self {
+            Filter::ModifiedBefore(_) => "modified-before",
+            Filter::ModifiedSince(_) => "modified-since",
+            Filter::MinSize(_) => "min-size",
+            Filter::MaxSize(_) => "max-size",
+            Filter::Kind(_) => "kind",
+            Filter::Where(_) => "where",
+            Filter::Attribute(_) => "where-xattr",
+            Filter::Exclude(_) => "exclude",
+            Filter::Allow(_) => "allow",
+            Filter::Custom { name, .. } => name,
+        }
+    }
+
+    fn keeps(&self, path: &Path, base: &Path) -> bool {
+        let size = || path_utils::total_size(path);
+        match self {
+            Filter::ModifiedBefore(cutoff) => is_modified_before(path, *cutoff),
+            Filter::ModifiedSince(cutoff) => modified_time(path).is_some_and(|m| m >= *cutoff),
+            Filter::MinSize(min) => size().is_some_and(|size| size >= *min),
+            Filter::MaxSize(max) => size().is_some_and(|size| size <= *max),
+            Filter::Kind(EntryKind::File) => path.is_file(),
+            Filter::Kind(EntryKind::Directory) => path.is_dir(),
+            Filter::Where(predicate) => predicate.matches(path),
+            Filter::Attribute(condition) => condition.matches(path),
+            Filter::Exclude(excluded) => {
+                let path = base.join(path);
+                !excluded.iter().any(|dir| path.starts_with(base.join(dir)))
+            }
+            Filter::Allow(allowed) => {
+                let path = base.join(path);
+                allowed.iter().any(|dir| path.starts_with(base.join(dir)))
+            }
+            Filter::Custom { keep, .. } => keep(path),
+        }
+    }
+}
+
+/// Paths picked by a [`FileSelector`]
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// Selected paths, in source order without duplicates
+    pub files: Vec<PathBuf>,
+    /// How many paths each filter dropped, by filter name, in the order filters ran
+    pub dropped: Vec<(&'static str, usize)>,
+    /// Paths left out by the exclude patterns, including those split off selected directories
+    pub excluded: Vec<PathBuf>,
+}
+
+impl Selection {
+    /// Total number of paths dropped by filters with this name
+    pub fn dropped_by(&self, name: &str) -> usize {
+        self.dropped
+            .iter()
+            .filter(|(filter, _)| *filter == name)
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+/// Builds the set of paths a run operates on
+///
+/// Paths from every source are combined, then each filter narrows the set in turn.
+///
+/// ```no_run
+/// use dbx_ignore::file_selector::{EntryKind, FileSelector, Filter, Source};
+///
+/// let selection = FileSelector::new(".")
+///     .source(Source::Walk { root: ".".into(), max_depth: None })
+///     .filter(Filter::Kind(EntryKind::File))
+///     .filter(Filter::MinSize(100 * 1024 * 1024))
+///     .select()?;
+/// # anyhow::Ok(())
+/// ```
+pub struct FileSelector {
+    base: PathBuf,
+    sources: Vec<Source>,
+    filters: Vec<Filter>,
+    exclude: Option<PatternMatcher>,
+    parallel_walk_threshold: usize,
+}
+
+impl FileSelector {
+    /// Create an empty selector; relative `Exclude`/`Allow` paths are resolved against `base`
+    pub fn new(base: impl Into<PathBuf>) -> Self {
+        Self {
+            base: base.into(),
+            sources: Vec::new(),
+            filters: Vec::new(),
+            exclude: None,
+            parallel_walk_threshold: path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
+        }
+    }
+
+    /// Set the number of top-level entries at which sources walk a tree in parallel
+    /// (0: always)
+    pub fn parallel_walk_threshold(mut self, threshold: usize) -> Self {
+        self.parallel_walk_threshold = threshold;
+        self
+    }
+
+    /// Add paths from another source
+    pub fn source(mut self, source: Source) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Add a filter, applied after the ones already added
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Leave out paths matching gitignore-style patterns, relative to the base
+    ///
+    /// Applied before the filters. A selected directory holding an excluded path is
+    /// replaced by its remaining contents, so marking it can't cover the excluded path.
+    pub fn exclude(mut self, patterns: &[String]) -> Result<Self> {
+        self.exclude = Some(PatternMatcher::new(&self.base, patterns, false)?);
+        Ok(self)
+    }
+
+    /// Collect paths from every source and apply the filters
+    pub fn select(&self) -> Result<Selection> {
+        let mut seen = HashSet::new();
+        let mut files = Vec::new();
+        for source in &self.sources {
+            for path in source.collect(self.parallel_walk_threshold)? {
+                if seen.insert(path.clone()) {
+                    files.push(path);
+                }
+            }
+        }
+
+        let mut excluded = Vec::new();
+        if let Some(ref matcher) = self.exclude {
+            let mut kept = Vec::with_capacity(files.len());
+            for path in files {
+                split_excluded(path, matcher, &mut kept, &mut excluded)?;
+            }
+            files = kept;
+        }
+
+        let mut dropped = Vec::with_capacity(self.filters.len());
+        for filter in &self.filters {
+            let before = files.len();
+            files.retain(|path| filter.keeps(path, &self.base));
+            dropped.push((filter.name(), before - files.len()));
+        }
+
+        Ok(Selection {
+            files,
+            dropped,
+            excluded,
+        })
+    }
+}
+
+/// Sort a selected path into `kept` or `excluded`, splitting directories that hold both
+fn split_excluded(
+    path: PathBuf,
+    matcher: &PatternMatcher,
+    kept: &mut Vec<PathBuf>,
+    excluded: &mut Vec<PathBuf>,
+) -> Result<()> {
+    if matcher.covers(&path) {
+        excluded.push(path);
+    } else if path.is_dir() && holds_excluded(&path, matcher) {
+        for entry in std::fs::read_dir(&path)? {
+            split_excluded(entry?.path(), matcher, kept, excluded)?;
+        }
+    } else {
+        kept.push(path);
+    }
+    Ok(())
+}
+
+/// Check if anything below a directory matches the exclude patterns
+fn holds_excluded(dir: &Path, matcher: &PatternMatcher) -> bool {
+    ignore::WalkBuilder::new(dir)
+        .standard_filters(false)
+        .hidden(false)
+        .build()
+        .flatten()
+        .any(|entry| entry.depth() > 0 && matcher.covers(entry.path()))
+}
+
+/// Check if a path string contains glob pattern characters
+pub fn is_glob_pattern(path_str: &str) -> bool {
+    path_str.contains('*')
+        || path_str.contains('?')
+        || path_str.contains('[')
+        || (path_str.contains('{') && path_str.contains('}'))
+}
+
+/// Compile a glob given on the command line
+///
+/// Globs follow shell rules rather than gitignore ones: `*` and `?` stay within one path
+/// component, so `*.log` only matches at the top level, while `**` spans any number of
+/// components and `{a,b}` matches either alternative. Watch-mode patterns match
+/// `*.log` at any depth instead.
+pub fn compile_glob(pattern: &str) -> Result<GlobMatcher> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map(|glob| glob.compile_matcher())
+        .map_err(|e| anyhow::anyhow!("Invalid pattern '{}': {}", pattern, e))
+}
+
+/// Split a glob into the directory to walk and how many levels below it can match
+///
+/// The directory is the glob's leading run of literal components; the depth is unlimited
+/// once `**` appears.
+fn glob_walk_root(pattern: &str) -> (PathBuf, Option<usize>) {
+    let components: Vec<Component> = Path::new(pattern).components().collect();
+    let literal = components
+        .iter()
+        .take(components.len().saturating_sub(1))
+        .take_while(|component| !is_glob_pattern(&component.as_os_str().to_string_lossy()))
+        .count();
+    let rest = &components[literal..];
+
+    let root: PathBuf = components[..literal].iter().collect();
+    let depth = if rest.iter().any(|component| component.as_os_str() == "**") {
+        None
+    } else {
+        Some(rest.len())
+    };
+    (root, depth)
+}
+
+/// Check if a path was last modified before the given time
+///
+/// Paths whose modification time can't be read are never selected.
+fn is_modified_before(path: &Path, cutoff: SystemTime) -> bool {
+    modified_time(path).is_some_and(|modified| modified < cutoff)
+}
+
+/// Last modification time of a path, following symlinks
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Classification of path types for special handling
+enum PathType {
+    CurrentDirectory,
+    GitIgnoreFile,
+    Regular,
+}
+
+/// Classify a path for special handling
+fn classify_path(path: &Path) -> PathType {
+    // Check if it's the current directory
+    if path.to_str() == Some(".") || path.file_name().and_then(|n| n.to_str()) == Some(".") {
+        PathType::CurrentDirectory
+    }
+    // Check if it's a .gitignore file
+    else if path.file_name().and_then(|n| n.to_str()) == Some(".gitignore") {
+        PathType::GitIgnoreFile
+    } else {
+        PathType::Regular
+    }
+}
+
+/// Check if a path is a hidden file (starts with .)
+fn is_hidden_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|name| name.starts_with('.'))
+        .unwrap_or(false)
+}
+
+/// Process a glob pattern and add matching files to items
+/// Returns true if any matches were found
+fn process_glob_pattern(pattern: &str, items: &mut Vec<PathBuf>) -> Result<bool> {
+    let initial_count = items.len();
+    let matcher = compile_glob(pattern)?;
+    let (root, max_depth) = glob_walk_root(pattern);
+    let relative = root.as_os_str().is_empty();
+    let walk_root = if relative { Path::new(".") } else { &root };
+    if !walk_root.is_dir() {
+        return Ok(false);
+    }
+
+    let walker = ignore::WalkBuilder::new(walk_root)
+        .standard_filters(false)
+        .hidden(false)
+        .max_depth(max_depth)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| !path_utils::is_internal_dir_name(entry.file_name()))
+        .build();
+
+    for entry in walker {
+        let entry = entry.map_err(|e| anyhow::anyhow!("Glob error: {}", e))?;
+        if entry.depth() == 0 {
+            continue;
+        }
+        // Relative globs match, and report, paths without the leading `./`
+        let path = if relative {
+            entry.path().strip_prefix(".").unwrap_or(entry.path())
+        } else {
+            entry.path()
+        };
+        if matcher.is_match(path) && path.exists() {
+            items.push(path.to_path_buf());
+        }
+    }
+    Ok(items.len() > initial_count)
+}
+
+/// Resolve `..` and symlinks in a path's directory, keeping the final component as is
+///
+/// Markers go on the entry itself, so a symlink is located where it sits, not where it
+/// points. Paths that can't be resolved are returned unchanged.
+fn resolve_location(path: &Path) -> PathBuf {
+    let resolved = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            parent.canonicalize().map(|parent| parent.join(name))
+        }
+        _ => path.canonicalize(),
+    };
+    resolved.unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Fail if any of `items` resolves to a location outside `root`
+fn ensure_within(root: &Path, items: &[PathBuf]) -> Result<()> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let outside: Vec<String> = items
+        .iter()
+        .filter(|item| !resolve_location(item).starts_with(&root))
+        .map(|item| item.display().to_string())
+        .collect();
+
+    if outside.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "Refusing to process {} path(s) outside {}: {} (use --allow-outside to process them anyway)",
+        outside.len(),
+        root.display(),
+        outside.join(", ")
+    ))
+}
+
+fn get_files_from_paths(
+    paths: &[PathBuf],
+    literal: bool,
+    local_only: bool,
+    confine_to: Option<&Path>,
+    parallel_walk_threshold: usize,
+) -> Result<Vec<PathBuf>> {
+    let mut items = Vec::new();
+    let mut regular_paths = Vec::new();
+    let mut empty_patterns = Vec::new();
+
+    // Process each path, categorizing as pattern or regular path
+    for path in paths {
+        let path_str = path.to_string_lossy();
+
+        if !literal && is_glob_pattern(&path_str) {
+            // Handle glob patterns
+            let start = items.len();
+            match process_glob_pattern(&path_str, &mut items) {
+                Ok(found_matches) => {
+                    if !found_matches {
+                        empty_patterns.push(path_str.to_string());
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+            if let Some(root) = confine_to {
+                ensure_within(root, &items[start..])?;
+            }
+        } else {
+            regular_paths.push(path.clone());
+        }
+    }
+
+    // Report error if any patterns matched nothing
+    if !empty_patterns.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No files found matching patterns: {}",
+            empty_patterns.join(", ")
+        ));
+    }
+
+    // Process regular paths
+    for path in regular_paths {
+        if !path.exists() {
+            return Err(anyhow::anyhow!("Path not found: {}", path.display()));
+        }
+
+        let start = items.len();
+        match classify_path(&path) {
+            PathType::CurrentDirectory => {
+                // Expand current directory contents, skipping hidden files
+                for entry in std::fs::read_dir(path)? {
+                    let entry_path = entry?.path();
+                    if !is_hidden_file(&entry_path) {
+                        items.push(entry_path);
+                    }
+                }
+            }
+            PathType::GitIgnoreFile if local_only => {
+                // Only this file's own rules, without those inherited from the repository
+                items.extend(git_utils::get_files_matching_gitignore_only(
+                    &path,
+                    parallel_walk_threshold,
+                )?);
+            }
+            PathType::GitIgnoreFile => {
+                // Process .gitignore file and add the ignored files
+                let gitignore_files = git_utils::get_git_ignored_files_from_gitignore(&path)?;
+                items.extend(gitignore_files);
+            }
+            PathType::Regular => {
+                // Add the path directly
+                items.push(path);
+            }
+        }
+        if let Some(root) = confine_to {
+            ensure_within(root, &items[start..])?;
+        }
+    }
+
+    Ok(items)
+}
@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use crate::utils::ignore_reason::GitignoreExplainer;
+use crate::utils::{git_utils, path_utils};
+
+/// How replacing the root `.gitignore` would change which files git mode marks
+#[derive(Debug, Default)]
+pub struct GitignoreDiff {
+    /// Files the proposed rules ignore that the current ones don't, with the deciding pattern
+    pub to_mark: Vec<(PathBuf, String)>,
+    /// Files the current rules ignore that the proposed ones don't
+    pub to_unmark: Vec<PathBuf>,
+}
+
+impl GitignoreDiff {
+    /// Compare the files ignored under `root` today with those `proposed` would ignore
+    ///
+    /// `proposed` stands in for the `.gitignore` at the repository root. Nested
+    /// `.gitignore` files, `.git/info/exclude` and the global excludes apply to both sides.
+    pub fn compute(root: &Path, proposed: &Path) -> Result<Self> {
+        if !proposed.is_file() {
+            anyhow::bail!("Proposed .gitignore not found: {}", proposed.display());
+        }
+        // Only to fail early outside a repository; the explainers find the root from
+        // `root` itself, so its paths compare equal to the walked ones
+        git_utils::repo_root(root)?;
+
+        let mut current = GitignoreExplainer::new(root);
+        let mut future = GitignoreExplainer::new(root)
+            .with_root_gitignore(proposed)
+            .with_context(|| format!("Failed to read {}", proposed.display()))?;
+
+        let mut diff = GitignoreDiff::default();
+        for file in all_files(root) {
+            let before = current.explain(&file);
+            let after = future.explain(&file);
+            match (before, after) {
+                (None, Some(reason)) => {
+                    diff.to_mark.push((file, reason.describe(root)));
+                }
+                (Some(_), None) => diff.to_unmark.push(file),
+                _ => {}
+            }
+        }
+
+        diff.to_mark.sort();
+        diff.to_unmark.sort();
+        Ok(diff)
+    }
+
+    /// Whether the proposed rules change anything
+    pub fn is_empty(&self) -> bool {
+        self.to_mark.is_empty() && self.to_unmark.is_empty()
+    }
+
+    pub fn display(&self, root: &Path) {
+        let relative = |path: &PathBuf| {
+            path.strip_prefix(root)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+
+        if self.is_empty() {
+            println!(
+                "{} The proposed .gitignore doesn't change which files are marked",
+                "✓".green()
+            );
+            return;
+        }
+
+        println!(
+            "{} {} file(s) would be marked, {} would be unmarked",
+            "Gitignore diff:".blue().bold(),
+            self.to_mark.len().to_string().green(),
+            self.to_unmark.len().to_string().yellow()
+        );
+
+        if !self.to_mark.is_empty() {
+            println!("{}", "Newly ignored (would be marked):".green());
+            for (path, reason) in &self.to_mark {
+                println!(
+                    "  {} {} {}",
+                    "+".green(),
+                    relative(path),
+                    format!("— {}", reason).dimmed()
+                );
+            }
+        }
+
+        if !self.to_unmark.is_empty() {
+            println!("{}", "No longer ignored (would be unmarked):".yellow());
+            for path in &self.to_unmark {
+                println!("  {} {}", "-".yellow(), relative(path));
+            }
+        }
+    }
+}
+
+/// Every file under `root`, outside `.git`, the state directory and nested repositories
+fn all_files(root: &Path) -> Vec<PathBuf> {
+    use ignore::WalkBuilder;
+
+    let walker = WalkBuilder::new(root)
+        .standard_filters(false)
+        .hidden(false)
+        .filter_entry(|entry| {
+            !path_utils::is_internal_dir_name(entry.file_name())
+                && !path_utils::is_nested_repo_root(entry)
+        })
+        .build();
+
+    walker
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Main entry point for `--diff-gitignore`
+///
+/// Nothing is marked or unmarked; the report only previews the change.
+pub fn show_gitignore_diff(proposed: &Path) -> Result<()> {
+    let current_path = path_utils::current_dir()?;
+    let diff = GitignoreDiff::compute(&current_path, proposed)?;
+    diff.display(&current_path);
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use crate::Action;
+use crate::core::state_location;
+use crate::utils::{json_utils, path_utils};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of history entries kept before the oldest are pruned
+const MAX_HISTORY_ENTRIES: usize = 100;
+
+/// A single recorded marking/reset operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationRecord {
+    /// When the operation finished
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// The action that was performed
+    pub action: Action,
+    /// Whether this was a dry run (no markers were changed)
+    pub dry_run: bool,
+    /// Number of files processed
+    pub file_count: usize,
+    /// Number of marker operations performed (or that would be performed)
+    pub operations: usize,
+    /// Files whose markers were changed (or would have been changed)
+    #[serde(default)]
+    pub files: Vec<PathBuf>,
+}
+
+impl OperationRecord {
+    pub fn new(
+        action: Action,
+        dry_run: bool,
+        file_count: usize,
+        operations: usize,
+        mut files: Vec<PathBuf>,
+    ) -> Self {
+        files.sort();
+        Self {
+            timestamp: chrono::Utc::now(),
+            action,
+            dry_run,
+            file_count,
+            operations,
+            files,
+        }
+    }
+
+    /// Get the history directory path
+    pub fn history_dir(repo_path: &Path) -> PathBuf {
+        state_location::state_dir(repo_path).join("history")
+    }
+
+    /// Save this record as a new history entry, pruning the oldest entries
+    pub fn save(&self, repo_path: &Path) -> Result<()> {
+        let history_dir = Self::history_dir(repo_path);
+        let file_name = format!("{}.json", self.timestamp.format("%Y%m%dT%H%M%S%.9fZ"));
+
+        json_utils::write_json_atomic(&history_dir.join(file_name), self)
+            .context("Failed to write history entry")?;
+
+        prune_history(&history_dir)
+    }
+
+    /// Load all history entries, oldest first
+    pub fn load_all(repo_path: &Path) -> Result<Vec<Self>> {
+        let history_dir = Self::history_dir(repo_path);
+        if !history_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut records = Vec::new();
+        for entry in history_entry_paths(&history_dir)? {
+            // Skip corrupted entries rather than failing the whole log
+            if let Ok(record) = json_utils::read_json::<OperationRecord>(&entry) {
+                records.push(record);
+            }
+        }
+
+        records.sort_by_key(|r| r.timestamp);
+        Ok(records)
+    }
+
+    /// The most recent operation that changed markers, which `--undo` reverts
+    pub fn last_undoable(repo_path: &Path) -> Result<Option<Self>> {
+        Ok(Self::load_all(repo_path)?.into_iter().rev().find(|record| {
+            !record.dry_run && matches!(record.action, Action::Ignore | Action::Reset)
+        }))
+    }
+}
+
+/// List the JSON entries in the history directory, sorted by name (oldest first)
+fn history_entry_paths(history_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(history_dir)
+        .context("Failed to read history directory")?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Remove the oldest entries so that at most MAX_HISTORY_ENTRIES remain
+fn prune_history(history_dir: &Path) -> Result<()> {
+    let paths = history_entry_paths(history_dir)?;
+    if paths.len() > MAX_HISTORY_ENTRIES {
+        for path in &paths[..paths.len() - MAX_HISTORY_ENTRIES] {
+            let _ = fs::remove_file(path);
+        }
+    }
+    Ok(())
+}
+
+/// Main entry point for the log command
+pub fn show_log(verbose: bool) -> Result<()> {
+    let current_path = path_utils::current_dir()?;
+    let records = OperationRecord::load_all(&current_path)?;
+
+    if records.is_empty() {
+        println!("{}", "No operations recorded yet.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{} {}",
+        "Operation Log for:".blue().bold(),
+        current_path.display()
+    );
+    println!("{}", "─".repeat(50));
+
+    for record in &records {
+        let dry_run_note = if record.dry_run {
+            format!(" {}", "(dry run)".yellow())
+        } else {
+            String::new()
+        };
+
+        println!(
+            "{}  {:<6}  {} files, {} operations{}",
+            record
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            record.action.to_string().green(),
+            record.file_count,
+            record.operations,
+            dry_run_note
+        );
+
+        if verbose {
+            for file in &record.files {
+                println!("    {}", file.display());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::core::watch::ScanSummary;
+use crate::utils::input_utils;
+
+/// How long a hook may run before it is killed
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often a running hook is polled for completion
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Result of running a hook command
+#[derive(Debug)]
+pub enum HookOutcome {
+    Completed(ExitStatus),
+    TimedOut,
+}
+
+/// User command run by the daemon after a scan that changed markers (`--on-change`)
+///
+/// The command runs through the shell in the repository root, with the scan's counts
+/// in `DBX_MARKED`, `DBX_UNMARKED` and `DBX_ERRORS`.
+#[derive(Debug, Clone)]
+pub struct OnChangeHook {
+    command: String,
+    working_dir: PathBuf,
+    timeout: Duration,
+}
+
+impl OnChangeHook {
+    pub fn new(command: String, working_dir: PathBuf) -> Self {
+        Self {
+            command,
+            working_dir,
+            timeout: DEFAULT_HOOK_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run the hook in the background if the scan changed any markers
+    ///
+    /// Returns the handle of the background thread, or `None` if nothing changed.
+    pub fn notify(&self, summary: &ScanSummary) -> Option<thread::JoinHandle<Result<HookOutcome>>> {
+        if !summary.has_changes() {
+            return None;
+        }
+
+        let hook = self.clone();
+        let summary = *summary;
+        Some(thread::spawn(move || {
+            let outcome = hook.run(&summary);
+            match &outcome {
+                Ok(HookOutcome::Completed(status)) if !status.success() => {
+                    eprintln!("{} On-change hook exited with {}", "⚠".yellow(), status);
+                }
+                Ok(HookOutcome::TimedOut) => {
+                    eprintln!(
+                        "{} On-change hook timed out after {}s and was killed",
+                        "⚠".yellow(),
+                        hook.timeout.as_secs()
+                    );
+                }
+                Err(e) => eprintln!("{} On-change hook failed: {}", "⚠".yellow(), e),
+                _ => {}
+            }
+            outcome
+        }))
+    }
+
+    /// Run the hook and wait for it, killing it if it exceeds the timeout
+    pub fn run(&self, summary: &ScanSummary) -> Result<HookOutcome> {
+        let mut child = shell_command(&self.command)
+            .current_dir(&self.working_dir)
+            .env("DBX_MARKED", summary.marked.to_string())
+            .env("DBX_UNMARKED", summary.unmarked.to_string())
+            .env("DBX_ERRORS", summary.errors.to_string())
+            .stdin(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to run hook: {}", self.command))?;
+
+        wait_with_timeout(&mut child, self.timeout)
+    }
+}
+
+/// User command run before file selection whose output lists paths to skip (`--pre-scan-hook`)
+///
+/// The command runs through the shell in the working directory and must print a
+/// NUL-separated list of paths on stdout; relative paths are resolved against the
+/// working directory.
+#[derive(Debug, Clone)]
+pub struct PreScanHook {
+    command: String,
+    working_dir: PathBuf,
+    timeout: Duration,
+}
+
+impl PreScanHook {
+    pub fn new(command: String, working_dir: PathBuf) -> Self {
+        Self {
+            command,
+            working_dir,
+            timeout: DEFAULT_HOOK_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run the hook and return the paths it wants excluded
+    ///
+    /// Fails if the hook can't be started, exits with an error, or times out.
+    pub fn run(&self) -> Result<Vec<PathBuf>> {
+        let mut child = shell_command(&self.command)
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run pre-scan hook: {}", self.command))?;
+
+        // Drain stdout while waiting so a chatty hook can't block on a full pipe
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let reader = thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).map(|_| output)
+        });
+
+        match wait_with_timeout(&mut child, self.timeout)? {
+            HookOutcome::Completed(status) if status.success() => {}
+            HookOutcome::Completed(status) => {
+                anyhow::bail!("Pre-scan hook exited with {}: {}", status, self.command)
+            }
+            HookOutcome::TimedOut => anyhow::bail!(
+                "Pre-scan hook timed out after {}s: {}",
+                self.timeout.as_secs(),
+                self.command
+            ),
+        }
+
+        let output = reader
+            .join()
+            .map_err(|_| anyhow::anyhow!("Failed to read pre-scan hook output"))?
+            .context("Failed to read pre-scan hook output")?;
+
+        Ok(input_utils::split_nul_paths(&output)?
+            .into_iter()
+            .map(|path| self.working_dir.join(path))
+            .collect())
+    }
+}
+
+/// Wait for a child process, killing it if it runs longer than `timeout`
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<HookOutcome> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(HookOutcome::Completed(status));
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(HookOutcome::TimedOut);
+        }
+        thread::sleep(HOOK_POLL_INTERVAL);
+    }
+}
+
+/// Build a command that runs `command` through the platform shell
+fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    }
+
+    #[cfg(not(windows))]
+    {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
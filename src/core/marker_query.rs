@@ -0,0 +1,154 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::core::tracked_files::TrackedFiles;
+use crate::utils::{path_utils, platform_utils};
+
+/// Marker state of one queried path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MarkerState {
+    pub path: PathBuf,
+    pub exists: bool,
+    pub marked: bool,
+}
+
+/// How `--is-marked` prints its results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueryFormat {
+    /// One line per path
+    #[default]
+    Text,
+    /// A JSON array of `{path, exists, marked}` objects
+    Json,
+    /// Only the marked paths, each terminated by a NUL byte
+    Print0,
+}
+
+/// Check the given paths for ignore markers, without walking into directories
+pub fn query(paths: &[PathBuf]) -> Vec<MarkerState> {
+    paths
+        .iter()
+        .map(|path| MarkerState {
+            path: path.clone(),
+            exists: path.symlink_metadata().is_ok(),
+            marked: platform_utils::has_any_ignore_attribute(path),
+        })
+        .collect()
+}
+
+/// Main entry point for `--is-marked`
+///
+/// Returns whether every path is marked, or with `any`, whether at least one is. With
+/// `silent`, nothing is printed.
+pub fn show_is_marked(
+    paths: &[PathBuf],
+    format: QueryFormat,
+    any: bool,
+    silent: bool,
+) -> Result<bool> {
+    if paths.is_empty() {
+        anyhow::bail!("--is-marked requires at least one path");
+    }
+
+    let states = query(paths);
+    let answer = if any {
+        states.iter().any(|state| state.marked)
+    } else {
+        states.iter().all(|state| state.marked)
+    };
+    if silent {
+        return Ok(answer);
+    }
+    let mut stdout = std::io::stdout().lock();
+    match format {
+        QueryFormat::Text => {
+            for state in &states {
+                let label = if !state.exists {
+                    "not found".red()
+                } else if state.marked {
+                    "marked".green()
+                } else {
+                    "not marked".yellow()
+                };
+                writeln!(stdout, "{}: {}", state.path.display(), label)?;
+            }
+        }
+        QueryFormat::Json => {
+            let json =
+                serde_json::to_string_pretty(&states).context("Failed to serialize to JSON")?;
+            writeln!(stdout, "{}", json)?;
+        }
+        QueryFormat::Print0 => {
+            for state in states.iter().filter(|state| state.marked) {
+                stdout.write_all(state.path.as_os_str().as_encoded_bytes())?;
+                stdout.write_all(b"\0")?;
+            }
+        }
+    }
+    stdout.flush()?;
+
+    Ok(answer)
+}
+
+/// Main entry point for `--list`
+///
+/// Prints every path under `root` that carries a marker on disk, one per line and
+/// relative to `root`. With `verbose`, paths tagged with `--tag` show their tag. With
+/// `null`, each path is terminated by a NUL byte instead, without tags.
+pub fn list_marked(
+    root: &Path,
+    recurse_submodules: bool,
+    max_depth: Option<usize>,
+    verbose: bool,
+    null: bool,
+) -> Result<()> {
+    let mut marked = platform_utils::find_marked_files(
+        root,
+        recurse_submodules,
+        max_depth,
+        path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
+    )?;
+    marked.sort();
+
+    let tags: HashMap<PathBuf, String> = if verbose && !null {
+        TrackedFiles::load(root)?
+            .tags
+            .into_iter()
+            .map(|(path, tag)| (root.join(path), tag))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    for path in &marked {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        // The root itself strips to an empty path
+        let shown = if relative.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            relative
+        };
+        if null {
+            stdout.write_all(shown.as_os_str().as_encoded_bytes())?;
+            stdout.write_all(b"\0")?;
+            continue;
+        }
+        match tags.get(path) {
+            Some(tag) => writeln!(
+                stdout,
+                "{} {}",
+                shown.display(),
+                format!("[{}]", tag).dimmed()
+            )?,
+            None => writeln!(stdout, "{}", shown.display())?,
+        }
+    }
+    stdout.flush()?;
+
+    Ok(())
+}
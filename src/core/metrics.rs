@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::core::watch::ScanSummary;
+use crate::utils::json_utils;
+
+/// Lifetime counters of a watch session, exported in the Prometheus text format
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatchMetrics {
+    /// Scans run to completion
+    pub scans: u64,
+    /// Markers added across all scans
+    pub marked: u64,
+    /// Markers removed across all scans
+    pub unmarked: u64,
+    /// Paths that couldn't be updated, plus scans that failed outright
+    pub errors: u64,
+    /// When the last scan finished (`None` until the first one does)
+    pub last_scan: Option<SystemTime>,
+}
+
+impl WatchMetrics {
+    /// Count a completed scan
+    pub fn record_scan(&mut self, summary: &ScanSummary) {
+        self.scans += 1;
+        self.marked += summary.marked as u64;
+        self.unmarked += summary.unmarked as u64;
+        self.errors += summary.errors as u64;
+        self.last_scan = Some(SystemTime::now());
+    }
+
+    /// Count a scan that failed before finishing
+    pub fn record_failure(&mut self) {
+        self.errors += 1;
+    }
+
+    /// Render the counters in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let last_scan = self
+            .last_scan
+            .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64());
+
+        let mut text = String::new();
+        for (name, kind, help, value) in [
+            (
+                "dbx_ignore_scans_total",
+                "counter",
+                "Scans completed by the watcher",
+                self.scans as f64,
+            ),
+            (
+                "dbx_ignore_files_marked_total",
+                "counter",
+                "Ignore markers added by the watcher",
+                self.marked as f64,
+            ),
+            (
+                "dbx_ignore_files_unmarked_total",
+                "counter",
+                "Ignore markers removed by the watcher",
+                self.unmarked as f64,
+            ),
+            (
+                "dbx_ignore_errors_total",
+                "counter",
+                "Paths the watcher failed to update, and scans that failed",
+                self.errors as f64,
+            ),
+            (
+                "dbx_ignore_last_scan_timestamp_seconds",
+                "gauge",
+                "Unix time the last scan finished (0 before the first scan)",
+                last_scan,
+            ),
+        ] {
+            let _ = writeln!(text, "# HELP {} {}", name, help);
+            let _ = writeln!(text, "# TYPE {} {}", name, kind);
+            let _ = writeln!(text, "{} {}", name, value);
+        }
+        text
+    }
+
+    /// Write the rendered counters to `path`, replacing it atomically
+    ///
+    /// Scrapers such as node_exporter's textfile collector never see a partial file.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        json_utils::write_atomic(path, self.render().as_bytes())
+            .with_context(|| format!("Failed to write metrics to {}", path.display()))
+    }
+}
@@ -1,4 +1,21 @@
+pub mod conflicts;
 pub mod daemon;
+pub mod doctor;
+pub mod drift;
+pub mod file_selector;
+pub mod gitignore_diff;
+pub mod history;
+pub mod hooks;
+pub mod marker_query;
+pub mod metrics;
+pub mod notifications;
+pub mod pattern_source;
+pub mod presets;
+pub mod repo_config;
+pub mod scan_cache;
+pub mod schema;
+pub mod selective_sync;
+pub mod state_location;
 pub mod status;
 pub mod tracked_files;
 pub mod watch;
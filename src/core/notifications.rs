@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Shortest time between two error notifications from the same watcher
+pub const MIN_NOTIFY_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Shows a desktop notification with the given title and body
+pub type Notifier = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Show a native desktop notification, without waiting for it
+///
+/// Uses `osascript` on macOS and `notify-send` on Linux; elsewhere nothing is shown.
+/// Failures are ignored: the error has already been logged by the caller.
+pub fn send_desktop_notification(title: &str, body: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(title)
+        );
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else if cfg!(target_os = "linux") {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name", "dbx-ignore", title, body]);
+        command
+    } else {
+        return;
+    };
+
+    if let Ok(mut child) = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        // Reap the process in the background so it doesn't linger as a zombie
+        std::thread::spawn(move || child.wait());
+    }
+}
+
+/// Quote a string for AppleScript
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Turns watcher errors into desktop notifications, at most one per interval
+///
+/// Errors arriving within [`MIN_NOTIFY_INTERVAL`] of the last notification are counted
+/// and mentioned in the next one instead.
+pub struct ErrorNotifier {
+    notifier: Notifier,
+    repo_root: PathBuf,
+    last_sent: Option<Instant>,
+    suppressed: usize,
+}
+
+impl ErrorNotifier {
+    pub fn new(notifier: Notifier, repo_root: PathBuf) -> Self {
+        Self {
+            notifier,
+            repo_root,
+            last_sent: None,
+            suppressed: 0,
+        }
+    }
+
+    /// Report `count` errors, described by `detail`
+    pub fn report(&mut self, count: usize, detail: &str) {
+        if count == 0 {
+            return;
+        }
+        if self
+            .last_sent
+            .is_some_and(|sent| sent.elapsed() < MIN_NOTIFY_INTERVAL)
+        {
+            self.suppressed += count;
+            return;
+        }
+
+        let mut body = format!("{}: {}", self.repo_root.display(), detail);
+        if self.suppressed > 0 {
+            body.push_str(&format!(
+                " ({} more error(s) since the last notification)",
+                self.suppressed
+            ));
+        }
+        (self.notifier)("dbx-ignore watcher error", &body);
+        self.last_sent = Some(Instant::now());
+        self.suppressed = 0;
+    }
+}
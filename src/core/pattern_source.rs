@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use ignore::gitignore::GitignoreBuilder;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// How often the daemon re-fetches a central pattern list by default
+pub const DEFAULT_PATTERN_REFRESH: Duration = Duration::from_secs(5 * 60);
+
+/// Maximum time allowed for fetching patterns from a URL
+const FETCH_TIMEOUT_SECS: u64 = 30;
+
+/// Where the daemon gets its central pattern list from (`--patterns-url`/`--patterns-file`)
+///
+/// The list uses `.gitignore` syntax: one pattern per line, `#` comments and blank
+/// lines are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternSource {
+    File(PathBuf),
+    Url(String),
+}
+
+impl PatternSource {
+    /// Fetch and validate the current pattern list
+    pub fn fetch(&self) -> Result<Vec<String>> {
+        let contents = match self {
+            PatternSource::File(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read patterns file: {}", path.display()))?,
+            PatternSource::Url(url) => fetch_url(url)?,
+        };
+
+        let patterns = parse_pattern_list(&contents);
+        validate_patterns(&patterns).with_context(|| format!("Rejected patterns from {}", self))?;
+        Ok(patterns)
+    }
+
+    /// Command-line options that recreate this source in a spawned daemon
+    pub fn to_args(&self) -> Vec<String> {
+        match self {
+            PatternSource::File(path) => {
+                vec!["--patterns-file".to_string(), path.display().to_string()]
+            }
+            PatternSource::Url(url) => vec!["--patterns-url".to_string(), url.clone()],
+        }
+    }
+}
+
+impl std::fmt::Display for PatternSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternSource::File(path) => write!(f, "{}", path.display()),
+            PatternSource::Url(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+/// Split a pattern list into patterns, skipping blank lines and comments
+pub fn parse_pattern_list(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Check a fetched pattern list before it replaces the active one
+///
+/// An empty list is rejected too: applying it would strip every marker, which is far
+/// more likely to be a broken fetch than an intended policy.
+pub fn validate_patterns(patterns: &[String]) -> Result<()> {
+    if patterns.is_empty() {
+        anyhow::bail!("pattern list is empty");
+    }
+
+    let mut builder = GitignoreBuilder::new(Path::new(""));
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .with_context(|| format!("Invalid pattern: {}", pattern))?;
+    }
+    builder.build().context("Invalid pattern list")?;
+
+    Ok(())
+}
+
+/// Download `url` with the system `curl`, which must be on `PATH`
+fn fetch_url(url: &str) -> Result<String> {
+    // `--` keeps a URL starting with `-` from being read as an option
+    let output = Command::new("curl")
+        .args([
+            "-fsSL",
+            "--max-time",
+            &FETCH_TIMEOUT_SECS.to_string(),
+            "--",
+            url,
+        ])
+        .output()
+        .context("Failed to run curl to fetch patterns")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to fetch patterns from {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    String::from_utf8(output.stdout).context("Fetched patterns are not valid UTF-8")
+}
@@ -0,0 +1,53 @@
+/// Ecosystem presets and the gitignore-style patterns they stand for
+///
+/// Directory patterns are written as `**/<dir>/**` so they select the files inside, at
+/// any depth, and keep matching new files there when the watch daemon follows them.
+pub const PRESETS: &[(&str, &[&str])] = &[
+    (
+        "node",
+        &[
+            "**/node_modules/**",
+            "**/dist/**",
+            "**/build/**",
+            "**/.next/**",
+            "**/.parcel-cache/**",
+        ],
+    ),
+    (
+        "python",
+        &[
+            "**/.venv/**",
+            "**/venv/**",
+            "**/__pycache__/**",
+            "**/.pytest_cache/**",
+            "**/.mypy_cache/**",
+            "**/.tox/**",
+            "**/dist/**",
+            "**/build/**",
+        ],
+    ),
+    ("rust", &["**/target/**"]),
+];
+
+/// Names of the available presets
+pub fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Patterns of the named presets, in order and without duplicates
+///
+/// Unknown names contribute nothing; the command line only accepts known ones.
+pub fn preset_patterns(names: &[String]) -> Vec<String> {
+    let mut patterns: Vec<String> = Vec::new();
+    for name in names {
+        let Some((_, preset)) = PRESETS.iter().find(|(known, _)| known == name) else {
+            continue;
+        };
+        for pattern in *preset {
+            if !patterns.iter().any(|known| known == pattern) {
+                patterns.push(pattern.to_string());
+            }
+        }
+    }
+    patterns
+}
@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::utils::path_utils::STATE_DIR_NAME;
+use crate::utils::platform_utils::{self, DEFAULT_MARKER_VALUE};
+use crate::{Action, Config};
+
+/// Per-repository settings stored in `.dbx-ignore/config.toml`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RepoConfig {
+    /// Action used when no action flag is given (`ignore` or `reset`)
+    pub default_action: Option<Action>,
+    /// Verbose output unless `--quiet` is given
+    pub verbose: Option<bool>,
+    /// Quiet output unless `--verbose` is given
+    pub quiet: Option<bool>,
+    /// Watch daemon debounce window, in milliseconds
+    pub debounce_ms: Option<u64>,
+    /// Skip `--git` runs when no ignore file or tracked state changed since the last one
+    pub scan_cache: Option<bool>,
+    /// Value written with ignore markers, unless `DBX_IGNORE_MARKER_VALUE` is set
+    pub marker_value: Option<String>,
+}
+
+impl RepoConfig {
+    /// Get the config file path
+    pub fn config_file_path(repo_path: &Path) -> PathBuf {
+        repo_path.join(STATE_DIR_NAME).join("config.toml")
+    }
+
+    /// Load the repo config; a missing file yields the defaults
+    pub fn load(repo_path: &Path) -> Result<Self> {
+        let config_file = Self::config_file_path(repo_path);
+        if !config_file.exists() {
+            return Ok(Self::default());
+        }
+        Self::from_file(&config_file)
+    }
+
+    /// Load and validate a config file
+    pub fn from_file(config_file: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(config_file)
+            .with_context(|| format!("Failed to read {}", config_file.display()))?;
+        let config: RepoConfig = toml::from_str(&contents)
+            .with_context(|| format!("Invalid config file: {}", config_file.display()))?;
+
+        if let Some(action) = config.default_action
+            && !matches!(action, Action::Ignore | Action::Reset)
+        {
+            anyhow::bail!(
+                "Invalid config file: {}: default_action must be \"ignore\" or \"reset\", got \"{}\"",
+                config_file.display(),
+                action
+            );
+        }
+        if config.marker_value.as_deref() == Some("") {
+            anyhow::bail!(
+                "Invalid config file: {}: marker_value must not be empty",
+                config_file.display()
+            );
+        }
+        if config.debounce_ms == Some(0) {
+            anyhow::bail!(
+                "Invalid config file: {}: debounce_ms must be greater than zero",
+                config_file.display()
+            );
+        }
+
+        Ok(config)
+    }
+
+    /// Value written with ignore markers: `DBX_IGNORE_MARKER_VALUE`, then the
+    /// configured `marker_value`, then the default
+    ///
+    /// Every path that writes markers resolves the value here, so repairs and the
+    /// watch daemon agree with ordinary runs.
+    pub fn marker_value(&self) -> Vec<u8> {
+        platform_utils::marker_value_from_env()
+            .or_else(|| self.marker_value.clone().map(String::into_bytes))
+            .unwrap_or_else(|| DEFAULT_MARKER_VALUE.to_vec())
+    }
+
+    /// Fill in the settings the command line left unset
+    ///
+    /// Flags always win: `--quiet` overrides a configured `verbose` and vice versa.
+    pub fn apply_to(&self, config: &mut Config) {
+        let cli_verbose = config.verbose;
+        let cli_quiet = config.quiet;
+        if self.verbose == Some(true) && !cli_quiet {
+            config.verbose = true;
+        }
+        if self.quiet == Some(true) && !cli_verbose {
+            config.quiet = true;
+            config.verbose = false;
+        }
+        if config.debounce.is_none() {
+            config.debounce = self.debounce_ms.map(Duration::from_millis);
+        }
+        if self.scan_cache == Some(true) {
+            config.scan_cache = true;
+        }
+        if config.marker_value.is_none() {
+            config.marker_value = Some(self.marker_value());
+        }
+    }
+}
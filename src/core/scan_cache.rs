@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::core::state_location;
+use crate::core::tracked_files::TrackedFiles;
+use crate::utils::{git_utils, json_utils};
+
+/// What the last `--git` run saw, so a run on an unchanged repository can be skipped
+///
+/// Only ignore rules, the marker value and the tracked state are compared: files created
+/// since the last run are not noticed, which is why the cache is opt-in.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanCache {
+    /// Modification time of each ignore file (`.gitignore` files and `.git/info/exclude`)
+    pub ignore_files: BTreeMap<PathBuf, SystemTime>,
+    /// Modification time of `tracked_files.json` after the run
+    pub tracked_files: Option<SystemTime>,
+    /// Number of tracked paths after the run
+    pub marked_count: usize,
+    /// Value the run wrote with its markers
+    #[serde(default)]
+    pub marker_value: Vec<u8>,
+}
+
+impl ScanCache {
+    /// Get the cache file path
+    pub fn cache_file_path(repo_path: &Path) -> PathBuf {
+        state_location::state_dir(repo_path).join("scan_cache.json")
+    }
+
+    /// Record the current ignore files and tracked state of the repository at `repo_path`,
+    /// for a run writing `marker_value`
+    pub fn capture(repo_path: &Path, marker_value: &[u8]) -> Result<Self> {
+        let root = git_utils::repo_root(repo_path)?;
+        let mut ignore_files = BTreeMap::new();
+        let exclude = root.join(".git").join("info").join("exclude");
+        let mut sources = git_utils::find_gitignore_files(&root)?;
+        if exclude.is_file() {
+            sources.push(exclude);
+        }
+        for path in sources {
+            let modified = std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            ignore_files.insert(path, modified);
+        }
+
+        let tracked_files = std::fs::metadata(TrackedFiles::state_file_path(repo_path))
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        let marked_count = TrackedFiles::load(repo_path)?.marked_files.len();
+
+        Ok(Self {
+            ignore_files,
+            tracked_files,
+            marked_count,
+            marker_value: marker_value.to_vec(),
+        })
+    }
+
+    /// Load the cache left by the last run, if there is a readable one
+    pub fn load(repo_path: &Path) -> Option<Self> {
+        json_utils::read_json(&Self::cache_file_path(repo_path)).ok()
+    }
+
+    /// Save the cache for the next run
+    pub fn save(&self, repo_path: &Path) -> Result<()> {
+        json_utils::write_json_atomic(&Self::cache_file_path(repo_path), self)
+            .context("Failed to write scan cache")
+    }
+
+    /// Whether nothing the cache covers has changed since it was captured, for a run
+    /// writing `marker_value`
+    pub fn is_current(&self, repo_path: &Path, marker_value: &[u8]) -> bool {
+        Self::capture(repo_path, marker_value).is_ok_and(|now| now == *self)
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::str::FromStr;
+
+/// Identifier of the `--status --json` schema, also carried in the output as `$schema`
+///
+/// Bump the version whenever a field is removed or changes meaning.
+pub const STATUS_SCHEMA_ID: &str = "https://github.com/thomastheyoung/dbx-ignore/schemas/status/v1";
+
+/// Identifier of the `--is-marked --json` schema
+pub const IS_MARKED_SCHEMA_ID: &str =
+    "https://github.com/thomastheyoung/dbx-ignore/schemas/is-marked/v1";
+
+/// A machine-readable output with a published JSON Schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaKind {
+    /// The object printed by `--status --json`
+    Status,
+    /// The array printed by `--is-marked --json`
+    IsMarked,
+}
+
+impl FromStr for SchemaKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "status" => Ok(SchemaKind::Status),
+            "is-marked" => Ok(SchemaKind::IsMarked),
+            _ => anyhow::bail!("Unknown schema '{}'. Valid schemas: status, is-marked", s),
+        }
+    }
+}
+
+impl SchemaKind {
+    /// The JSON Schema (draft 2020-12) describing this output
+    pub fn schema(self) -> Value {
+        let path_list = json!({ "type": "array", "items": { "type": "string" } });
+        match self {
+            SchemaKind::Status => json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$id": STATUS_SCHEMA_ID,
+                "title": "dbx-ignore --status --json",
+                "type": "object",
+                "required": [
+                    "$schema", "current_path", "has_gitignore", "recursive", "total_files",
+                    "ignored_files", "non_ignored_files", "inconsistent_files", "attributes",
+                    "ignored_ancestor", "daemon_status", "daemon_paused"
+                ],
+                "additionalProperties": false,
+                "properties": {
+                    "$schema": { "const": STATUS_SCHEMA_ID },
+                    "current_path": { "type": "string" },
+                    "has_gitignore": { "type": "boolean" },
+                    "recursive": { "type": "boolean" },
+                    "total_files": { "type": "integer", "minimum": 0 },
+                    "ignored_files": path_list,
+                    "non_ignored_files": path_list,
+                    "inconsistent_files": path_list,
+                    "attributes": {
+                        "type": "object",
+                        "additionalProperties": {
+                            "type": "object",
+                            "required": ["present", "unexpected"],
+                            "additionalProperties": false,
+                            "properties": {
+                                "present": { "type": "array", "items": { "type": "string" } },
+                                "unexpected": { "type": "array", "items": { "type": "string" } }
+                            }
+                        }
+                    },
+                    "ignored_ancestor": { "type": ["string", "null"] },
+                    "daemon_status": {
+                        "type": ["object", "null"],
+                        "required": ["pid", "repo_path", "started_at", "last_heartbeat"],
+                        "additionalProperties": false,
+                        "properties": {
+                            "pid": { "type": "integer", "minimum": 0 },
+                            "repo_path": { "type": "string" },
+                            "started_at": { "type": "string", "format": "date-time" },
+                            "last_heartbeat": { "type": ["string", "null"], "format": "date-time" },
+                            "pidfile": { "type": "string" }
+                        }
+                    },
+                    "daemon_paused": { "type": "boolean" }
+                }
+            }),
+            SchemaKind::IsMarked => json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$id": IS_MARKED_SCHEMA_ID,
+                "title": "dbx-ignore --is-marked --json",
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["path", "exists", "marked"],
+                    "additionalProperties": false,
+                    "properties": {
+                        "path": { "type": "string" },
+                        "exists": { "type": "boolean" },
+                        "marked": { "type": "boolean" }
+                    }
+                }
+            }),
+        }
+    }
+}
+
+/// Main entry point for `--schema`
+pub fn show_schema(kind: SchemaKind) -> Result<()> {
+    let json =
+        serde_json::to_string_pretty(&kind.schema()).context("Failed to serialize to JSON")?;
+    println!("{}", json);
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::utils::{path_utils, platform_utils};
+
+/// Folder-level view of file-level ignore markers, for Dropbox's selective sync
+#[derive(Debug, Default)]
+pub struct SelectiveSyncReport {
+    /// Top-most folders whose entire contents are ignored (candidates for selective sync exclusion)
+    pub fully_ignored: Vec<PathBuf>,
+    /// Folders with some, but not all, contents ignored (must stay synced with per-file markers)
+    pub partially_ignored: Vec<PathBuf>,
+}
+
+impl SelectiveSyncReport {
+    /// Aggregate marked paths into folder-level recommendations
+    ///
+    /// `all_files` are the files under `root`; `marked` holds the marked files and
+    /// directories. A file counts as ignored if it or any of its parent folders is marked.
+    /// Returned folders are relative to `root`, which itself is never reported.
+    pub fn from_marked(root: &Path, all_files: &[PathBuf], marked: &HashSet<PathBuf>) -> Self {
+        // Per folder: (total files, ignored files)
+        let mut folders: BTreeMap<PathBuf, (usize, usize)> = BTreeMap::new();
+
+        for file in all_files {
+            let ignored = file
+                .ancestors()
+                .take_while(|p| p.starts_with(root))
+                .any(|p| marked.contains(p));
+
+            for folder in file.ancestors().skip(1) {
+                if folder == root || !folder.starts_with(root) {
+                    break;
+                }
+                let counts = folders.entry(folder.to_path_buf()).or_default();
+                counts.0 += 1;
+                if ignored {
+                    counts.1 += 1;
+                }
+            }
+        }
+
+        // Marked folders with no files inside are still fully ignored
+        let file_set: HashSet<&PathBuf> = all_files.iter().collect();
+        for path in marked {
+            if path != root && path.starts_with(root) && !file_set.contains(path) {
+                folders.entry(path.clone()).or_insert((0, 0));
+            }
+        }
+
+        let mut fully: Vec<PathBuf> = Vec::new();
+        let mut partially = Vec::new();
+        for (folder, (total, ignored)) in &folders {
+            let fully_ignored = if *total == 0 {
+                marked.contains(folder)
+            } else {
+                ignored == total
+            };
+
+            if fully_ignored {
+                // Only keep the top-most folder of a fully ignored subtree
+                if !fully.iter().any(|parent| folder.starts_with(parent)) {
+                    fully.push(folder.clone());
+                }
+            } else if *ignored > 0 {
+                partially.push(folder.clone());
+            }
+        }
+
+        let relative = |p: &PathBuf| p.strip_prefix(root).unwrap_or(p).to_path_buf();
+        SelectiveSyncReport {
+            fully_ignored: fully.iter().map(relative).collect(),
+            partially_ignored: partially.iter().map(relative).collect(),
+        }
+    }
+
+    /// Walk the tree under `root` and build the report from on-disk markers
+    pub fn gather(root: &Path) -> Result<Self> {
+        use ignore::WalkBuilder;
+
+        let walker = WalkBuilder::new(root)
+            .standard_filters(false)
+            .hidden(false)
+            .filter_entry(|entry| !path_utils::is_internal_dir_name(entry.file_name()))
+            .build();
+
+        let mut all_files = Vec::new();
+        let mut marked = HashSet::new();
+        for entry in walker.flatten() {
+            let path = entry.path();
+            if path == root {
+                continue;
+            }
+            if platform_utils::has_any_ignore_attribute(path) {
+                marked.insert(path.to_path_buf());
+            }
+            if path.is_file() {
+                all_files.push(path.to_path_buf());
+            }
+        }
+
+        Ok(Self::from_marked(root, &all_files, &marked))
+    }
+
+    pub fn display(&self) {
+        println!(
+            "{}",
+            "Folders fully ignored (candidates for selective sync exclusion):"
+                .green()
+                .bold()
+        );
+        if self.fully_ignored.is_empty() {
+            println!("  (none)");
+        }
+        for folder in &self.fully_ignored {
+            println!("  {}/", folder.display());
+        }
+
+        println!();
+        println!(
+            "{}",
+            "Folders partially ignored (keep synced, per-file markers apply):"
+                .yellow()
+                .bold()
+        );
+        if self.partially_ignored.is_empty() {
+            println!("  (none)");
+        }
+        for folder in &self.partially_ignored {
+            println!("  {}/", folder.display());
+        }
+    }
+}
+
+/// Main entry point for the selective sync report
+pub fn show_selective_sync_report() -> Result<()> {
+    let current_path = path_utils::current_dir()?;
+    let report = SelectiveSyncReport::gather(&current_path)?;
+    report.display();
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::utils::path_utils::STATE_DIR_NAME;
+
+/// Where a repository's state (tracked files, daemon status, history) is kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateLocation {
+    /// In `.dbx-ignore/` inside the repository
+    #[default]
+    Repo,
+    /// Under the user's config directory, keyed by the repository's canonical path
+    Global,
+}
+
+/// User-wide settings stored in `$XDG_CONFIG_HOME/dbx-ignore/config.toml`
+/// (`~/.config/dbx-ignore/config.toml` when `XDG_CONFIG_HOME` isn't set)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GlobalConfig {
+    /// Where repository state is kept (`repo` or `global`)
+    pub state_location: Option<StateLocation>,
+}
+
+impl GlobalConfig {
+    /// Get the user's dbx-ignore config directory, if a home directory is known
+    pub fn config_dir() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .or_else(|| std::env::var_os("USERPROFILE"))
+                    .map(|home| PathBuf::from(home).join(".config"))
+            })?;
+        Some(base.join("dbx-ignore"))
+    }
+
+    /// Load the global config; a missing file yields the defaults
+    pub fn load() -> Result<Self> {
+        let Some(config_file) = Self::config_dir().map(|dir| dir.join("config.toml")) else {
+            return Ok(Self::default());
+        };
+        if !config_file.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&config_file)
+            .with_context(|| format!("Failed to read {}", config_file.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Invalid config file: {}", config_file.display()))
+    }
+
+    /// The global config, read once per process
+    ///
+    /// An unreadable file is reported and the defaults are used, so state keeps going
+    /// to the repository rather than nowhere.
+    pub fn get() -> &'static GlobalConfig {
+        static CONFIG: OnceLock<GlobalConfig> = OnceLock::new();
+        CONFIG.get_or_init(|| {
+            Self::load().unwrap_or_else(|e| {
+                eprintln!("{} Warning: {:#}", "⚠".yellow(), e);
+                Self::default()
+            })
+        })
+    }
+}
+
+/// Whether repository state is kept under the user's config directory
+pub fn uses_global_state() -> bool {
+    GlobalConfig::get().state_location == Some(StateLocation::Global)
+        && GlobalConfig::config_dir().is_some()
+}
+
+/// Directory holding the state of the repository at `repo_path`
+pub fn state_dir(repo_path: &Path) -> PathBuf {
+    match GlobalConfig::config_dir() {
+        Some(config_dir) if uses_global_state() => global_state_dir(&config_dir, repo_path),
+        _ => repo_path.join(STATE_DIR_NAME),
+    }
+}
+
+/// Directory under `config_dir` holding the global state of the repository at `repo_path`
+///
+/// The repository is identified by its canonical path, escaped into a single file name.
+pub fn global_state_dir(config_dir: &Path, repo_path: &Path) -> PathBuf {
+    let canonical = fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+    let key = canonical
+        .to_string_lossy()
+        .replace('%', "%25")
+        .replace('/', "%2F")
+        .replace('\\', "%5C")
+        .replace(':', "%3A");
+    config_dir.join("repos").join(key)
+}
+
+/// Copy the repository's local tracked files into the global state location
+///
+/// Only happens when state is kept globally, the repository still has a local
+/// `tracked_files.json` and there is no global one yet; if both exist, nothing is
+/// touched. The local copy is left in place. Returns whether a copy was made.
+pub fn migrate_local_state(repo_path: &Path) -> Result<bool> {
+    if !uses_global_state() {
+        return Ok(false);
+    }
+    let local = repo_path.join(STATE_DIR_NAME).join("tracked_files.json");
+    let global = state_dir(repo_path).join("tracked_files.json");
+    if !local.is_file() || global.exists() {
+        return Ok(false);
+    }
+
+    if let Some(parent) = global.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::copy(&local, &global).with_context(|| {
+        format!(
+            "Failed to migrate {} to {}",
+            local.display(),
+            global.display()
+        )
+    })?;
+    Ok(true)
+}
@@ -1,10 +1,44 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 
 use crate::core::daemon;
-use crate::utils::platform_utils;
+use crate::core::schema::STATUS_SCHEMA_ID;
+use crate::utils::path_utils;
+use crate::utils::platform_utils::{self, AttributeBreakdown};
+
+/// How `--status` prints its report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatusFormat {
+    /// Colored, human-readable report
+    #[default]
+    Text,
+    /// The full report as a JSON object
+    Json,
+    /// A single terse line, for shell prompts
+    Short,
+}
+
+/// JSON form of [`StatusInfo`], with paths relative to `current_path` where possible
+#[derive(Serialize)]
+struct StatusJson<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    current_path: &'a Path,
+    has_gitignore: bool,
+    recursive: bool,
+    total_files: usize,
+    ignored_files: Vec<&'a Path>,
+    non_ignored_files: Vec<&'a Path>,
+    inconsistent_files: Vec<&'a Path>,
+    attributes: BTreeMap<&'a Path, &'a AttributeBreakdown>,
+    ignored_ancestor: Option<&'a Path>,
+    daemon_status: Option<&'a daemon::DaemonStatus>,
+    daemon_paused: bool,
+}
 
 pub struct StatusInfo {
     pub has_gitignore: bool,
@@ -12,37 +46,105 @@ pub struct StatusInfo {
     pub ignored_files: Vec<PathBuf>,
     pub non_ignored_files: Vec<PathBuf>,
     pub daemon_status: Option<daemon::DaemonStatus>,
+    /// Whether the running daemon has been paused with `--pause`
+    pub daemon_paused: bool,
     pub current_path: PathBuf,
+    /// Which target attributes are present on each listed file
+    pub attributes: HashMap<PathBuf, AttributeBreakdown>,
+    /// Files carrying an attribute variant this system doesn't expect
+    pub inconsistent_files: Vec<PathBuf>,
+    /// The current directory or closest ancestor carrying an ignore marker
+    pub ignored_ancestor: Option<PathBuf>,
+    /// The detected Dropbox folder containing the current directory
+    pub dropbox_root: Option<PathBuf>,
+    /// Whether files in subdirectories were counted too
+    pub recursive: bool,
+}
+
+/// Check if a file name is hidden (starts with .)
+fn is_hidden(name: &OsStr) -> bool {
+    name.to_str().is_some_and(|name| name.starts_with('.'))
+}
+
+/// Non-hidden entries directly in `dir`
+fn list_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if !is_hidden(&entry.file_name()) {
+            entries.push(entry.path());
+        }
+    }
+    Ok(entries)
+}
+
+/// Non-hidden entries at any depth below `dir`
+///
+/// Hidden directories, `.git` and the state directory among them, are skipped. The
+/// contents of marked directories are left out too: the marker already covers them.
+fn walk_entries(dir: &Path) -> Vec<PathBuf> {
+    use ignore::WalkBuilder;
+
+    let walker = WalkBuilder::new(dir)
+        .standard_filters(false)
+        .hidden(false)
+        .filter_entry(|entry| {
+            let in_marked_dir = entry.depth() > 1
+                && entry
+                    .path()
+                    .parent()
+                    .is_some_and(platform_utils::has_any_ignore_attribute);
+            !is_hidden(entry.file_name()) && !in_marked_dir
+        })
+        .build();
+
+    walker
+        .flatten()
+        .filter(|entry| entry.depth() > 0)
+        .map(|entry| entry.into_path())
+        .collect()
 }
 
 impl StatusInfo {
+    /// Gather the status of the entries directly in the current directory
     pub fn gather() -> Result<Self> {
-        let current_path = std::env::current_dir().context("Failed to get current directory")?;
+        Self::collect(false)
+    }
+
+    /// Gather the status of every entry below the current directory, at any depth
+    pub fn gather_recursive() -> Result<Self> {
+        Self::collect(true)
+    }
+
+    fn collect(recursive: bool) -> Result<Self> {
+        let current_path = path_utils::current_dir()?;
 
         // Check for .gitignore
         let has_gitignore = current_path.join(".gitignore").exists();
 
+        let ignored_ancestor = platform_utils::find_ignored_ancestor(&current_path);
+        let dropbox_root = platform_utils::dropbox_root_of(&current_path);
+
         // Get daemon status
         let daemon_status = daemon::DaemonStatus::read(&current_path)?;
+        let daemon_paused = daemon_status.is_some() && daemon::is_paused(&current_path);
+
+        // Get all files in the current directory, or the whole tree below it
+        let entries = if recursive {
+            walk_entries(&current_path)
+        } else {
+            list_entries(&current_path)?
+        };
 
-        // Get all files in the current directory (non-recursive)
         let mut all_files = Vec::new();
         let mut file_status = HashMap::new();
+        let mut attributes = HashMap::new();
 
-        for entry in std::fs::read_dir(&current_path)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            // Skip hidden files (starting with .)
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if name.starts_with('.') {
-                    continue;
-                }
-            }
-
+        for path in entries {
             // Check if file has ignore markers
             let is_ignored = platform_utils::has_any_ignore_attribute(&path);
 
+            attributes.insert(path.clone(), platform_utils::attribute_breakdown(&path));
             all_files.push(path.clone());
             file_status.insert(path, is_ignored);
         }
@@ -63,16 +165,114 @@ impl StatusInfo {
             .cloned()
             .collect();
 
+        let inconsistent_files: Vec<PathBuf> = all_files
+            .iter()
+            .filter(|f| {
+                attributes
+                    .get(*f)
+                    .is_some_and(AttributeBreakdown::has_unexpected)
+            })
+            .cloned()
+            .collect();
+
         Ok(StatusInfo {
             has_gitignore,
             total_files: all_files.len(),
             ignored_files,
             non_ignored_files,
             daemon_status,
+            daemon_paused,
             current_path,
+            attributes,
+            inconsistent_files,
+            ignored_ancestor,
+            dropbox_root,
+            recursive,
         })
     }
 
+    /// Group files by the directory containing them, relative to the current path
+    fn by_directory<'a>(&self, files: &'a [PathBuf]) -> BTreeMap<&'a Path, Vec<&'a PathBuf>> {
+        let mut groups: BTreeMap<&Path, Vec<&PathBuf>> = BTreeMap::new();
+        for file in files {
+            let dir = file
+                .parent()
+                .and_then(|dir| dir.strip_prefix(&self.current_path).ok())
+                .unwrap_or(Path::new(""));
+            groups.entry(dir).or_default().push(file);
+        }
+        groups
+    }
+
+    /// Print a file listing, under a header per subdirectory in recursive mode
+    fn list_files(&self, files: &[PathBuf], line: impl Fn(&PathBuf, &str)) {
+        if !self.recursive {
+            for file in files {
+                if let Some(name) = file.file_name().and_then(|n| n.to_str()) {
+                    line(file, name);
+                }
+            }
+            return;
+        }
+
+        for (dir, files) in self.by_directory(files) {
+            let header = if dir.as_os_str().is_empty() {
+                "./".to_string()
+            } else {
+                format!("{}/", dir.display())
+            };
+            println!("  {}", header.dimmed());
+            for file in files {
+                if let Some(name) = file.file_name().and_then(|n| n.to_str()) {
+                    print!("  ");
+                    line(file, name);
+                }
+            }
+        }
+    }
+
+    /// Print the report as a single JSON object, for scripts (`--status --json`)
+    pub fn display_json(&self) -> Result<()> {
+        fn relative<'a>(path: &'a Path, base: &Path) -> &'a Path {
+            path.strip_prefix(base).unwrap_or(path)
+        }
+        let base = self.current_path.as_path();
+        let report = StatusJson {
+            schema: STATUS_SCHEMA_ID,
+            current_path: &self.current_path,
+            has_gitignore: self.has_gitignore,
+            recursive: self.recursive,
+            total_files: self.total_files,
+            ignored_files: self
+                .ignored_files
+                .iter()
+                .map(|f| relative(f, base))
+                .collect(),
+            non_ignored_files: self
+                .non_ignored_files
+                .iter()
+                .map(|f| relative(f, base))
+                .collect(),
+            inconsistent_files: self
+                .inconsistent_files
+                .iter()
+                .map(|f| relative(f, base))
+                .collect(),
+            attributes: self
+                .attributes
+                .iter()
+                .map(|(path, breakdown)| (relative(path, base), breakdown))
+                .collect(),
+            ignored_ancestor: self.ignored_ancestor.as_deref(),
+            daemon_status: self.daemon_status.as_ref(),
+            daemon_paused: self.daemon_paused,
+        };
+
+        let json = serde_json::to_string_pretty(&report).context("Failed to serialize to JSON")?;
+        println!("{}", json);
+        Ok(())
+    }
+
     pub fn display(&self, verbose: bool) -> Result<()> {
         println!(
             "{}",
@@ -89,6 +289,16 @@ impl StatusInfo {
         );
         println!();
 
+        if let Some(ref ignored) = self.ignored_ancestor {
+            println!(
+                "{} {} {}",
+                "⚠".yellow(),
+                ignored.display().to_string().yellow(),
+                "is marked as ignored; markers below it have no effect (reset it first)".yellow()
+            );
+            println!();
+        }
+
         // Gitignore status
         println!(
             "{} {}",
@@ -100,11 +310,25 @@ impl StatusInfo {
             }
         );
 
+        println!(
+            "{} {}",
+            "Dropbox folder:".yellow().bold(),
+            match self.dropbox_root {
+                Some(ref root) => format!("✓ {}", root.display()).green(),
+                None => "✗ Not inside a detected Dropbox folder".red(),
+            }
+        );
+
         // File counts
         println!(
-            "{} {} files total",
+            "{} {} files total{}",
             "Files:".yellow().bold(),
-            self.total_files
+            self.total_files,
+            if self.recursive {
+                " (including subdirectories)"
+            } else {
+                ""
+            }
         );
         println!(
             "   {} {} files have ignore markers",
@@ -116,13 +340,41 @@ impl StatusInfo {
             "✗".red(),
             self.non_ignored_files.len().to_string().red()
         );
+        if !self.inconsistent_files.is_empty() {
+            println!(
+                "   {} {} files carry an unexpected attribute variant",
+                "⚠".yellow(),
+                self.inconsistent_files.len().to_string().yellow()
+            );
+        }
 
         // Daemon status
         println!(
             "{} {}",
             "Daemon:".yellow().bold(),
             if let Some(ref status) = self.daemon_status {
-                format!("✓ Running (PID: {})", status.pid).green()
+                if status.is_stalled() {
+                    format!(
+                        "⚠ Running but unresponsive (PID: {}, no heartbeat since {})",
+                        status.pid,
+                        status
+                            .last_heartbeat
+                            .map(|h| h
+                                .with_timezone(&chrono::Local)
+                                .format("%H:%M:%S")
+                                .to_string())
+                            .unwrap_or_default()
+                    )
+                    .yellow()
+                } else if self.daemon_paused {
+                    format!(
+                        "⏸ Running but paused (PID: {}, --resume to continue)",
+                        status.pid
+                    )
+                    .yellow()
+                } else {
+                    format!("✓ Running (PID: {})", status.pid).green()
+                }
             } else {
                 "✗ Not running".red()
             }
@@ -137,11 +389,33 @@ impl StatusInfo {
             // Show ignored files first
             if !self.ignored_files.is_empty() {
                 println!("{}", "Ignored files:".green());
-                for file in &self.ignored_files {
-                    if let Some(name) = file.file_name().and_then(|n| n.to_str()) {
-                        println!("  {} {}", "✓".green(), name.green());
-                    }
-                }
+                self.list_files(&self.ignored_files, |file, name| {
+                    let present = self
+                        .attributes
+                        .get(file)
+                        .map(|a| a.present.join(", "))
+                        .unwrap_or_default();
+                    println!("  {} {} {}", "✓".green(), name.green(), present.dimmed());
+                });
+            }
+
+            // Highlight files marked with the wrong variant for the current Dropbox mode
+            if !self.inconsistent_files.is_empty() {
+                println!();
+                println!("{}", "Files with unexpected attributes:".yellow());
+                self.list_files(&self.inconsistent_files, |file, name| {
+                    let unexpected = self
+                        .attributes
+                        .get(file)
+                        .map(|a| a.unexpected.join(", "))
+                        .unwrap_or_default();
+                    println!(
+                        "  {} {} {}",
+                        "⚠".yellow(),
+                        name.yellow(),
+                        unexpected.dimmed()
+                    );
+                });
             }
 
             // Show non-ignored files
@@ -150,11 +424,9 @@ impl StatusInfo {
                     println!();
                 }
                 println!("{}", "Not ignored files:".red());
-                for file in &self.non_ignored_files {
-                    if let Some(name) = file.file_name().and_then(|n| n.to_str()) {
-                        println!("  {} {}", "✗".red(), name.red());
-                    }
-                }
+                self.list_files(&self.non_ignored_files, |_, name| {
+                    println!("  {} {}", "✗".red(), name.red());
+                });
             }
         }
 
@@ -168,8 +440,54 @@ impl StatusInfo {
     }
 }
 
+/// One-line status of `current_path` for shell prompts, e.g. `dbx: 42 ignored, daemon✓`
+///
+/// Only counts the marked entries directly in the directory and reads the daemon status
+/// file, skipping the per-attribute breakdown of the full report. The daemon is left
+/// out when it isn't running, and shown as `daemon⏸` while paused.
+pub fn short_line(current_path: &Path) -> Result<String> {
+    let mut ignored = 0;
+    for entry in std::fs::read_dir(current_path)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| name.starts_with('.'));
+        if !hidden && platform_utils::has_any_ignore_attribute(&path) {
+            ignored += 1;
+        }
+    }
+
+    let mut line = format!("dbx: {} ignored", ignored);
+    if let Some(status) = daemon::DaemonStatus::read(current_path)? {
+        let daemon = if status.is_stalled() {
+            "daemon⚠".yellow()
+        } else if daemon::is_paused(current_path) {
+            "daemon⏸".yellow()
+        } else {
+            "daemon✓".green()
+        };
+        line.push_str(&format!(", {}", daemon));
+    }
+    Ok(line)
+}
+
 /// Main entry point for the status command
-pub fn show_status(verbose: bool) -> Result<()> {
-    let status = StatusInfo::gather()?;
-    status.display(verbose)
+pub fn show_status(verbose: bool, recursive: bool, format: StatusFormat) -> Result<()> {
+    if format == StatusFormat::Short {
+        let current_path = path_utils::current_dir()?;
+        println!("{}", short_line(&current_path)?);
+        return Ok(());
+    }
+
+    let status = if recursive {
+        StatusInfo::gather_recursive()?
+    } else {
+        StatusInfo::gather()?
+    };
+    match format {
+        StatusFormat::Text => status.display(verbose),
+        StatusFormat::Json => status.display_json(),
+        StatusFormat::Short => unreachable!("handled above"),
+    }
 }
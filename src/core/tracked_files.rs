@@ -1,10 +1,33 @@
+use crate::core::drift::DriftReport;
+use crate::core::history::OperationRecord;
+use crate::core::state_location;
 use crate::utils::json_utils;
+use crate::utils::path_utils::{self, STATE_DIR_NAME};
+use crate::utils::platform_utils;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// How the marked set changed between two runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MarkedDelta {
+    /// Paths tracked now that weren't before
+    pub added: usize,
+    /// Paths no longer tracked
+    pub removed: usize,
+}
+
+impl std::fmt::Display for MarkedDelta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "+{} / -{} vs last run", self.added, self.removed)
+    }
+}
+
+/// Outcome of [`TrackedFiles::reconcile`]: tracked intent checked against on-disk markers
+pub type ReconcileReport = DriftReport;
+
 /// Stores information about files that have been marked with ignore attributes
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct TrackedFiles {
@@ -13,17 +36,33 @@ pub struct TrackedFiles {
     /// Patterns used to mark files (e.g., "*.log", "build/", "**/*.tmp")
     #[serde(default)]
     pub patterns: Vec<String>,
+    /// Tag each marked file was given with `--tag`, for resetting one category at a time
+    #[serde(default)]
+    pub tags: HashMap<PathBuf, String>,
     /// Timestamp of last update
     pub last_updated: chrono::DateTime<chrono::Utc>,
+    /// Canonical directory the state was loaded from, which paths are stored relative to
+    #[serde(skip)]
+    root: Option<PathBuf>,
 }
 
 impl TrackedFiles {
     /// Load tracked files from the state file
     pub fn load(repo_path: &Path) -> Result<Self> {
+        state_location::migrate_local_state(repo_path)?;
         let state_file = Self::state_file_path(repo_path);
+        Self::remove_orphaned_temp_files(repo_path);
 
+        let root = Some(
+            repo_path
+                .canonicalize()
+                .unwrap_or_else(|_| repo_path.to_path_buf()),
+        );
         if !state_file.exists() {
-            return Ok(Self::default());
+            return Ok(Self {
+                root,
+                ..Self::default()
+            });
         }
 
         // Use robust JSON reading with fallback to default
@@ -32,15 +71,53 @@ impl TrackedFiles {
                 // Validate and clean data
                 tracked.marked_files.retain(|p| !p.as_os_str().is_empty());
                 tracked.patterns.retain(|p| !p.is_empty());
+                tracked
+                    .tags
+                    .retain(|p, tag| !p.as_os_str().is_empty() && !tag.is_empty());
+
+                // Older states hold paths as they were given; merge their spellings
+                tracked.root = root;
+                tracked.marked_files = std::mem::take(&mut tracked.marked_files)
+                    .into_iter()
+                    .map(|path| tracked.canonicalize_relative(&path))
+                    .collect();
+                tracked.tags = std::mem::take(&mut tracked.tags)
+                    .into_iter()
+                    .map(|(path, tag)| (tracked.canonicalize_relative(&path), tag))
+                    .collect();
                 Ok(tracked)
             }
             Err(_) => {
                 // If corrupted, return default and the corrupted file will be overwritten
-                Ok(Self::default())
+                Ok(Self {
+                    root,
+                    ..Self::default()
+                })
             }
         }
     }
 
+    /// The form `path` is stored in: relative to the directory the state was loaded from
+    ///
+    /// State that wasn't loaded from disk has no such directory, so only `.` and `..`
+    /// are resolved.
+    fn canonicalize_relative(&self, path: &Path) -> PathBuf {
+        match self.root {
+            Some(ref root) => path_utils::canonicalize_relative(root, path),
+            None => path_utils::normalize_lexically(path),
+        }
+    }
+
+    /// Clean up temp files left in the state directory by writes that were killed midway
+    fn remove_orphaned_temp_files(repo_path: &Path) {
+        for dir in [
+            state_location::state_dir(repo_path),
+            OperationRecord::history_dir(repo_path),
+        ] {
+            json_utils::remove_stale_temp_files(&dir, json_utils::STALE_TEMP_FILE_AGE);
+        }
+    }
+
     /// Save tracked files to the state file
     pub fn save(&self, repo_path: &Path) -> Result<()> {
         let state_file = Self::state_file_path(repo_path);
@@ -55,43 +132,139 @@ impl TrackedFiles {
     /// Add files to the tracked set
     pub fn add_files(&mut self, files: &[PathBuf]) {
         for file in files {
-            self.marked_files.insert(file.clone());
+            self.marked_files.insert(self.canonicalize_relative(file));
         }
         self.last_updated = chrono::Utc::now();
     }
 
-    /// Add patterns to track
-    pub fn add_patterns(&mut self, patterns: &[String]) {
+    /// Add patterns to track, returning those that weren't tracked already
+    pub fn add_patterns(&mut self, patterns: &[String]) -> Vec<String> {
+        let mut added = Vec::new();
         for pattern in patterns {
             if !self.patterns.contains(pattern) {
                 self.patterns.push(pattern.clone());
+                added.push(pattern.clone());
             }
         }
         self.last_updated = chrono::Utc::now();
+        added
     }
 
-    /// Remove files from the tracked set
+    /// Tag files, replacing any tag they had before
+    pub fn tag_files(&mut self, files: &[PathBuf], tag: &str) {
+        for file in files {
+            self.tags
+                .insert(self.canonicalize_relative(file), tag.to_string());
+        }
+        self.last_updated = chrono::Utc::now();
+    }
+
+    /// Files carrying a tag, sorted
+    pub fn files_tagged(&self, tag: &str) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self
+            .tags
+            .iter()
+            .filter(|(_, file_tag)| *file_tag == tag)
+            .map(|(file, _)| file.clone())
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Tag of a file, if it has one
+    pub fn tag_of(&self, file: &Path) -> Option<&str> {
+        self.tags
+            .get(&self.canonicalize_relative(file))
+            .map(String::as_str)
+    }
+
+    /// Remove files from the tracked set, along with their tags
     pub fn remove_files(&mut self, files: &[PathBuf]) {
         for file in files {
-            self.marked_files.remove(file);
+            let file = self.canonicalize_relative(file);
+            self.marked_files.remove(&file);
+            self.tags.remove(&file);
         }
         self.last_updated = chrono::Utc::now();
     }
 
-    /// Remove patterns from tracking
-    pub fn remove_patterns(&mut self, patterns: &[String]) {
-        self.patterns.retain(|p| !patterns.contains(p));
+    /// Remove patterns from tracking, returning those that were tracked
+    pub fn remove_patterns(&mut self, patterns: &[String]) -> Vec<String> {
+        let (removed, kept) = std::mem::take(&mut self.patterns)
+            .into_iter()
+            .partition(|p| patterns.contains(p));
+        self.patterns = kept;
         self.last_updated = chrono::Utc::now();
+        removed
+    }
+
+    /// Compare the marked set with an earlier snapshot of it
+    ///
+    /// The state directory, which every run marks, isn't counted.
+    pub fn delta_since(&self, previous: &HashSet<PathBuf>) -> MarkedDelta {
+        let is_user_path = |path: &&PathBuf| path.file_name() != Some(STATE_DIR_NAME.as_ref());
+        MarkedDelta {
+            added: self
+                .marked_files
+                .difference(previous)
+                .filter(is_user_path)
+                .count(),
+            removed: previous
+                .difference(&self.marked_files)
+                .filter(is_user_path)
+                .count(),
+        }
     }
 
     /// Check if a file is being tracked
     pub fn is_tracked(&self, file: &Path) -> bool {
-        self.marked_files.contains(file)
+        self.marked_files
+            .contains(&self.canonicalize_relative(file))
+    }
+
+    /// Cross-check the tracked files against the markers actually on disk
+    ///
+    /// Reports tracked files that lost their marker (copied, restored from a backup)
+    /// and marked paths under `repo_path` that were never tracked.
+    pub fn reconcile(&self, repo_path: &Path) -> Result<ReconcileReport> {
+        let tracked_paths: HashSet<PathBuf> = self
+            .marked_files
+            .iter()
+            .map(|path| repo_path.join(path))
+            .collect();
+
+        let mut report = ReconcileReport::default();
+        for path in &tracked_paths {
+            if !path.exists() {
+                report.stale.push(path.clone());
+            } else if platform_utils::has_any_ignore_attribute(path) {
+                report.consistent.push(path.clone());
+            } else {
+                report.missing_markers.push(path.clone());
+            }
+        }
+
+        for path in platform_utils::find_marked_files(
+            repo_path,
+            false,
+            None,
+            path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
+        )? {
+            if path != repo_path && !tracked_paths.contains(&path) {
+                report.untracked_markers.push(path);
+            }
+        }
+
+        report.missing_markers.sort();
+        report.untracked_markers.sort();
+        report.consistent.sort();
+        report.stale.sort();
+        Ok(report)
     }
 
     /// Get the state file path
-    fn state_file_path(repo_path: &Path) -> PathBuf {
-        repo_path.join(".dbx-ignore").join("tracked_files.json")
+    pub(crate) fn state_file_path(repo_path: &Path) -> PathBuf {
+        state_location::state_dir(repo_path).join("tracked_files.json")
     }
 
     /// Remove the state file
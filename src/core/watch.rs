@@ -1,44 +1,358 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 use git2::Repository;
+use notify::event::{AccessKind, AccessMode, CreateKind, ModifyKind};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::{Mutex, mpsc};
 use tokio::time;
 
-use crate::core::tracked_files;
-use crate::utils::{git_utils, platform_utils};
+use crate::core::metrics::WatchMetrics;
+use crate::core::notifications::{self, ErrorNotifier, Notifier};
+use crate::core::pattern_source::{DEFAULT_PATTERN_REFRESH, PatternSource};
+use crate::core::{daemon, hooks, tracked_files};
+use crate::platforms::CurrentPlatform;
+use crate::traits::PlatformHandler;
+use crate::utils::ignore_reason::{GitignoreExplainer, IgnoreReason};
+use crate::utils::pattern_matcher::PatternMatcher;
+use crate::utils::rate_limit::{self, RateLimiter};
+use crate::utils::{git_utils, path_utils, platform_utils};
 
 // Constants for output limiting
 const MAX_FILES_TO_DISPLAY: usize = 10;
 const MAX_ERRORS_TO_DISPLAY: usize = 5;
 const DEFAULT_DEBOUNCE_MS: u64 = 500;
 
+/// Shortest debounce window the daemon accepts from `--debounce` or the repo config
+pub const MIN_DEBOUNCE: Duration = Duration::from_millis(50);
+
 #[derive(Debug, Clone)]
 enum WatchMode {
     TrackedFiles,
     GitIgnore,
     Patterns(Vec<String>),
+    /// Tracked files, git-ignored files and pattern matches together
+    Combined(Vec<String>),
+}
+
+impl WatchMode {
+    /// Patterns the mode follows, if it follows any
+    fn patterns(&self) -> Option<&[String]> {
+        match self {
+            WatchMode::Patterns(patterns) | WatchMode::Combined(patterns) => Some(patterns),
+            WatchMode::TrackedFiles | WatchMode::GitIgnore => None,
+        }
+    }
+
+    /// The same mode following a new pattern list
+    fn with_patterns(&self, patterns: Vec<String>) -> Self {
+        match self {
+            WatchMode::Combined(_) => WatchMode::Combined(patterns),
+            _ => WatchMode::Patterns(patterns),
+        }
+    }
 }
 
 pub struct WatchConfig {
     pub repo_path: PathBuf,
     pub debounce_duration: Duration,
+    /// Command to run after each scan that changed markers
+    pub on_change: Option<String>,
+    /// Descend into git submodules instead of stopping at their boundaries
+    pub recurse_submodules: bool,
+    /// Maximum marker operations per second during scans (unlimited if `None`)
+    pub rate_limit: Option<u32>,
+    /// Maximum directory depth scanned below the repository root (unlimited if `None`)
+    pub max_depth: Option<usize>,
+    /// Wait for writers to close files before marking them, instead of reacting to
+    /// every modification (Linux only; elsewhere the debounced modify events are used)
+    pub close_write: bool,
+    /// Central pattern list that replaces the tracked patterns, re-fetched periodically
+    pub pattern_source: Option<PatternSource>,
+    /// How often `pattern_source` is re-fetched
+    pub pattern_refresh: Duration,
+    /// Full re-scan on this interval even without filesystem events (off if `None`)
+    pub rescan_interval: Option<Duration>,
+    /// Run a final scan and stop once the watcher has run this long (unbounded if `None`)
+    pub max_runtime: Option<Duration>,
+    /// Postpone scans while `sync_detector` reports that Dropbox is syncing
+    pub defer_during_sync: bool,
+    /// Tells whether Dropbox is syncing the repository (the platform's detection by default)
+    pub sync_detector: SyncDetector,
+    /// File the lifetime scan counters are written to after every scan, in the
+    /// Prometheus text format (none if `None`)
+    pub metrics_file: Option<PathBuf>,
+    /// Show a desktop notification when a scan records errors (rate-limited)
+    pub notify_on_error: bool,
+    /// Shows the error notifications (native desktop notifications by default)
+    pub notifier: Notifier,
+    /// Log the marker changes each scan would make without applying them
+    pub observe: bool,
+    /// Reconcile tracked files, git-ignored files and tracked patterns together instead
+    /// of picking one of them (`--mode combined`)
+    pub combined: bool,
+    /// Leave files the repository's own ignore rules cover out of pattern matching
+    pub respect_gitignore: bool,
+    /// Value written with ignore markers
+    pub marker_value: Vec<u8>,
+    /// Top-level entries at which scans walk the tree in parallel (0: always)
+    pub parallel_walk_threshold: usize,
+    /// Log file the daemon's output goes to, trimmed to `log_max_size` (none if `None`)
+    pub log_file: Option<PathBuf>,
+    /// Size in bytes the log file is kept under
+    pub log_max_size: u64,
 }
 
+/// Reports whether the sync client is busy with the repository at the given root
+pub type SyncDetector = Arc<dyn Fn(&Path) -> bool + Send + Sync>;
+
 impl WatchConfig {
     pub fn new(repo_path: PathBuf) -> Self {
         Self {
             repo_path,
             debounce_duration: Duration::from_millis(DEFAULT_DEBOUNCE_MS),
+            on_change: None,
+            recurse_submodules: false,
+            rate_limit: None,
+            max_depth: None,
+            close_write: false,
+            pattern_source: None,
+            pattern_refresh: DEFAULT_PATTERN_REFRESH,
+            rescan_interval: None,
+            max_runtime: None,
+            defer_during_sync: false,
+            sync_detector: Arc::new(CurrentPlatform::is_sync_in_progress),
+            metrics_file: None,
+            notify_on_error: false,
+            notifier: Arc::new(notifications::send_desktop_notification),
+            observe: false,
+            combined: false,
+            respect_gitignore: false,
+            marker_value: platform_utils::DEFAULT_MARKER_VALUE.to_vec(),
+            parallel_walk_threshold: path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
+            log_file: None,
+            log_max_size: daemon::DEFAULT_LOG_MAX_SIZE,
+        }
+    }
+}
+
+/// Settings shared by every scan of a watch session
+struct ScanOptions {
+    recurse_submodules: bool,
+    max_depth: Option<usize>,
+    rate_limiter: Option<RateLimiter>,
+    /// Files written to but not closed yet, which scans leave unmarked (close-write mode)
+    being_written: std::sync::Mutex<HashSet<PathBuf>>,
+    /// The running binary and its directory, when inside the repository (never marked)
+    own_binary: Vec<PathBuf>,
+    /// Only report the marker changes scans would make
+    observe: bool,
+    /// Files the repository's own ignore rules cover never match the patterns
+    respect_gitignore: bool,
+    /// Value written with ignore markers
+    marker_value: Vec<u8>,
+    /// Top-level entries at which scans walk the tree in parallel
+    parallel_walk_threshold: usize,
+}
+
+impl ScanOptions {
+    /// Wait for the rate limiter, if any, before changing a marker
+    fn throttle(&self) {
+        rate_limit::throttle(self.rate_limiter.as_ref());
+    }
+
+    /// Add markers to `path`, or only count it in observe mode
+    fn add_marker(&self, path: &Path) -> Result<usize> {
+        if self.observe {
+            return Ok(1);
+        }
+        self.throttle();
+        platform_utils::add_ignore_attributes(path, false, &self.marker_value)
+    }
+
+    /// Remove the markers from `path`, or only count it in observe mode
+    fn remove_marker(&self, path: &Path) -> Result<usize> {
+        if self.observe {
+            return Ok(1);
+        }
+        self.throttle();
+        platform_utils::remove_ignore_attributes(path)
+    }
+
+    fn added_label(&self) -> &'static str {
+        if self.observe {
+            "Would add ignore marker to"
+        } else {
+            "Added ignore marker to"
+        }
+    }
+
+    fn removed_label(&self) -> &'static str {
+        if self.observe {
+            "Would remove ignore marker from"
+        } else {
+            "Removed ignore marker from"
+        }
+    }
+
+    fn summary_label(&self) -> &'static str {
+        if self.observe {
+            "Summary (observe only, nothing changed):"
+        } else {
+            "Summary:"
+        }
+    }
+
+    /// Follow files from their first write until the writer closes them
+    fn track_writes(&self, event: &Event) {
+        let mut being_written = self.being_written.lock().unwrap();
+        match event.kind {
+            EventKind::Create(CreateKind::File) | EventKind::Modify(ModifyKind::Data(_)) => {
+                being_written.extend(event.paths.iter().cloned());
+            }
+            // Renamed files were complete at their old name; removed ones are gone
+            EventKind::Access(AccessKind::Close(AccessMode::Write))
+            | EventKind::Modify(ModifyKind::Name(_))
+            | EventKind::Remove(_) => {
+                for path in &event.paths {
+                    being_written.remove(path);
+                }
+            }
+            _ => {}
         }
     }
+
+    /// Whether a writer still has `path` open
+    fn is_being_written(&self, path: &Path) -> bool {
+        self.being_written.lock().unwrap().contains(path)
+    }
+
+    /// Whether scans should leave `path` unmarked for now or for good
+    fn should_skip(&self, path: &Path) -> bool {
+        self.is_being_written(path) || path_utils::is_own_binary_path(path, &self.own_binary)
+    }
+}
+
+/// Marker changes made by a single scan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanSummary {
+    pub marked: usize,
+    pub unmarked: usize,
+    pub errors: usize,
+}
+
+impl ScanSummary {
+    /// Whether the scan added or removed any markers
+    pub fn has_changes(&self) -> bool {
+        self.marked > 0 || self.unmarked > 0
+    }
+}
+
+/// Where the watcher gets its filesystem events from
+///
+/// The daemon uses [`NotifyEventSource`]; tests can feed synthetic events instead.
+pub trait EventSource {
+    /// Start delivering events for `repo_root` to `events`
+    ///
+    /// Events must keep flowing for as long as the source is alive.
+    fn start(&mut self, repo_root: &Path, events: mpsc::UnboundedSender<Event>) -> Result<()>;
+}
+
+/// Event source backed by the platform's native file watcher
+#[derive(Default)]
+pub struct NotifyEventSource {
+    watcher: Option<RecommendedWatcher>,
+}
+
+impl EventSource for NotifyEventSource {
+    fn start(&mut self, repo_root: &Path, events: mpsc::UnboundedSender<Event>) -> Result<()> {
+        let mut watcher = RecommendedWatcher::new(
+            move |result: Result<Event, notify::Error>| {
+                if let Ok(event) = result {
+                    let _ = events.send(event);
+                }
+            },
+            Config::default(),
+        )?;
+
+        // Watch the repository root
+        watcher.watch(repo_root, RecursiveMode::Recursive)?;
+
+        // Also watch .gitignore files specifically
+        for gitignore_path in git_utils::find_gitignore_files(repo_root)? {
+            watcher.watch(&gitignore_path, RecursiveMode::NonRecursive)?;
+        }
+
+        self.watcher = Some(watcher);
+        Ok(())
+    }
 }
 
 pub async fn watch_repository(config: WatchConfig) -> Result<()> {
+    // Stop on Ctrl+C, and on SIGTERM/SIGHUP (e.g. from --unwatch) so the caller can clean up
+    // The handler runs on its own thread, outside the runtime, so it only flips a flag
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let shutdown_clone = shutdown.clone();
+
+    ctrlc::set_handler(move || {
+        shutdown_clone.store(true, Ordering::SeqCst);
+    })?;
+
+    run_watcher(config, NotifyEventSource::default(), shutdown, None).await
+}
+
+/// The watch loop: an initial scan, then a re-scan whenever relevant events settle
+///
+/// Runs until `shutdown` is set. Each scan's summary is sent to `scans`, if given.
+pub(crate) async fn run_watcher<S: EventSource>(
+    config: WatchConfig,
+    mut event_source: S,
+    shutdown: Arc<AtomicBool>,
+    scans: Option<mpsc::UnboundedSender<ScanSummary>>,
+) -> Result<()> {
+    let metrics = std::sync::Mutex::new(WatchMetrics::default());
+    let export_metrics = |metrics: &WatchMetrics| {
+        if let Some(ref path) = config.metrics_file
+            && let Err(e) = metrics.write(path)
+        {
+            eprintln!("{} {:#}", "Failed to export metrics:".red(), e);
+        }
+    };
+    let error_notifier = config.notify_on_error.then(|| {
+        std::sync::Mutex::new(ErrorNotifier::new(
+            config.notifier.clone(),
+            config.repo_path.clone(),
+        ))
+    });
+    let notify_errors = |count: usize, detail: &str| {
+        if let Some(ref notifier) = error_notifier {
+            notifier.lock().unwrap().report(count, detail);
+        }
+    };
+    let report_scan = |summary: &ScanSummary| {
+        let mut metrics = metrics.lock().unwrap();
+        metrics.record_scan(summary);
+        export_metrics(&metrics);
+        notify_errors(
+            summary.errors,
+            &format!("{} path(s) could not be updated", summary.errors),
+        );
+        if let Some(ref scans) = scans {
+            let _ = scans.send(*summary);
+        }
+    };
+    let report_failure = |e: anyhow::Error| {
+        eprintln!("{} {}", "Error during scan:".red(), e);
+        let mut metrics = metrics.lock().unwrap();
+        metrics.record_failure();
+        export_metrics(&metrics);
+        notify_errors(1, &format!("scan failed: {:#}", e));
+    };
+
     let repo = Repository::open(&config.repo_path).context("Failed to open git repository")?;
 
     let repo_root = repo
@@ -48,7 +362,16 @@ pub async fn watch_repository(config: WatchConfig) -> Result<()> {
 
     // Determine watch mode based on tracked files and patterns
     let tracked = tracked_files::TrackedFiles::load(&repo_root)?;
-    let watch_mode = if !tracked.patterns.is_empty() {
+    let mut watch_mode = if config.combined {
+        let patterns = match config.pattern_source {
+            Some(ref source) => source.fetch()?,
+            None => tracked.patterns.clone(),
+        };
+        WatchMode::Combined(patterns)
+    } else if let Some(ref source) = config.pattern_source {
+        // A central pattern list takes precedence over locally tracked state
+        WatchMode::Patterns(source.fetch()?)
+    } else if !tracked.patterns.is_empty() {
         WatchMode::Patterns(tracked.patterns.clone())
     } else if tracked.marked_files.is_empty() {
         WatchMode::GitIgnore
@@ -58,6 +381,7 @@ pub async fn watch_repository(config: WatchConfig) -> Result<()> {
 
     println!("{}", "Starting file watcher daemon...".green().bold());
     println!("Watching repository at: {}", repo_root.display());
+    println!("Debounce: {}ms", config.debounce_duration.as_millis());
     match &watch_mode {
         WatchMode::TrackedFiles => {
             println!(
@@ -74,98 +398,327 @@ pub async fn watch_repository(config: WatchConfig) -> Result<()> {
                 println!("  - {}", pattern);
             }
         }
+        WatchMode::Combined(patterns) => {
+            println!(
+                "Mode: Combined - keeping {} tracked files, git-ignored files and {} pattern(s) marked",
+                tracked.marked_files.len(),
+                patterns.len()
+            );
+            for pattern in patterns {
+                println!("  - {}", pattern);
+            }
+        }
+    }
+    if let Some(ref source) = config.pattern_source {
+        println!(
+            "Patterns from: {} (refreshed every {}s)",
+            source,
+            config.pattern_refresh.as_secs()
+        );
+    }
+    if config.observe {
+        println!(
+            "{} Observe mode: logging the marker changes scans would make, without applying them",
+            "⚠".yellow()
+        );
     }
     println!("Press Ctrl+C to stop\n");
 
-    // Initial scan
-    perform_scan(&repo_root, &watch_mode)?;
+    // Observing changes nothing, so there is nothing for the hook to react to
+    let on_change = config
+        .on_change
+        .as_ref()
+        .filter(|_| !config.observe)
+        .map(|command| hooks::OnChangeHook::new(command.clone(), repo_root.clone()));
 
-    // Set up channels for file system events
-    let (tx, mut rx) = mpsc::unbounded_channel();
+    let scan_options = Arc::new(ScanOptions {
+        recurse_submodules: config.recurse_submodules,
+        max_depth: config.max_depth,
+        rate_limiter: config.rate_limit.map(RateLimiter::new),
+        being_written: std::sync::Mutex::new(HashSet::new()),
+        own_binary: path_utils::own_binary_paths(&repo_root),
+        observe: config.observe,
+        respect_gitignore: config.respect_gitignore,
+        marker_value: config.marker_value.clone(),
+        parallel_walk_threshold: config.parallel_walk_threshold,
+    });
+    if let Some(exe) = scan_options.own_binary.first() {
+        println!(
+            "{} The running binary {} is inside the repository; it and its directory stay unmarked",
+            "⚠".yellow(),
+            exe.display()
+        );
+    }
+
+    // Only inotify reports when a writer closes a file
+    let close_write = config.close_write && cfg!(target_os = "linux");
+    if config.close_write && !close_write {
+        println!(
+            "{} Close-write events are not available on this platform, falling back to debounced modify events",
+            "⚠".yellow()
+        );
+    }
+
+    // Scans wait while the watcher is paused (`--pause`), or while Dropbox is syncing
+    let hold_reason = || {
+        if daemon::is_paused(&config.repo_path) {
+            Some("Watcher is paused")
+        } else if config.defer_during_sync && (config.sync_detector)(&repo_root) {
+            Some("Dropbox is syncing")
+        } else {
+            None
+        }
+    };
 
     // Track pending events for debouncing
     let pending_events = Arc::new(Mutex::new(HashSet::new()));
 
-    // Create file watcher
-    let mut watcher = RecommendedWatcher::new(
-        move |result: Result<Event, notify::Error>| {
-            if let Ok(event) = result {
-                let _ = tx.send(event);
-            }
-        },
-        Config::default(),
-    )?;
+    // Scrapers see zeroed counters until the first scan finishes
+    export_metrics(&metrics.lock().unwrap());
 
-    // Watch the repository root
-    watcher.watch(&repo_root, RecursiveMode::Recursive)?;
+    // Initial scan, left to the event loop while scans are held
+    let initial_hold = hold_reason();
+    let mut deferring = initial_hold.is_some();
+    let initial_summary = if let Some(reason) = initial_hold {
+        println!("{}", format!("{}, deferring scan...", reason).yellow());
+        pending_events.lock().await.insert(repo_root.clone());
+        None
+    } else {
+        let summary = perform_scan_blocking(
+            &repo_root,
+            &watch_mode,
+            std::slice::from_ref(&repo_root),
+            &scan_options,
+        )
+        .await?;
+        if let Some(ref hook) = on_change {
+            hook.notify(&summary);
+        }
+        Some(summary)
+    };
 
-    // Also watch .gitignore files specifically
-    let gitignore_paths = find_gitignore_files(&repo_root)?;
-    for gitignore_path in &gitignore_paths {
-        watcher.watch(gitignore_path, RecursiveMode::NonRecursive)?;
+    // Set up channels for file system events
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    event_source.start(&repo_root, tx)?;
+    if let Some(ref summary) = initial_summary {
+        report_scan(summary);
     }
 
-    // Set up Ctrl+C handler
-    let shutdown = Arc::new(Mutex::new(false));
-    let shutdown_clone = shutdown.clone();
+    let full_scan = std::slice::from_ref(&repo_root);
+    let scan = async |watch_mode: &WatchMode, scopes: &[PathBuf]| match perform_scan_blocking(
+        &repo_root,
+        watch_mode,
+        scopes,
+        &scan_options,
+    )
+    .await
+    {
+        Ok(summary) => {
+            if let Some(ref hook) = on_change {
+                hook.notify(&summary);
+            }
+            report_scan(&summary);
+        }
+        Err(e) => report_failure(e),
+    };
 
-    ctrlc::set_handler(move || {
-        let shutdown_clone = shutdown_clone.clone();
-        tokio::spawn(async move {
-            *shutdown_clone.lock().await = true;
-        });
-    })?;
+    let deadline = config
+        .max_runtime
+        .map(|runtime| time::Instant::now() + runtime);
 
     // Event processing loop
     let mut debounce_timer = time::interval(config.debounce_duration);
+    let mut last_heartbeat = Instant::now();
+    let mut pattern_refresh_timer = time::interval_at(
+        time::Instant::now() + config.pattern_refresh,
+        config.pattern_refresh,
+    );
+    // Without an interval the timer branch is disabled, so its period doesn't matter
+    let rescan_period = config.rescan_interval.unwrap_or(config.pattern_refresh);
+    let mut rescan_timer = time::interval_at(time::Instant::now() + rescan_period, rescan_period);
 
     loop {
         tokio::select! {
             Some(event) = rx.recv() => {
-                if should_trigger_rescan(&event, &watch_mode) {
-                    let mut events = pending_events.lock().await;
-                    events.insert(event.paths.first().cloned().unwrap_or_default());
+                // Ignore our own state writes (tracked files, daemon status, temp files)
+                let own_state = event
+                    .paths
+                    .iter()
+                    .all(|path| path_utils::is_in_state_dir(&repo_root, path));
+                if !own_state {
+                    if close_write {
+                        scan_options.track_writes(&event);
+                    }
+                    if should_trigger_rescan(&event, &watch_mode, close_write) {
+                        let mut events = pending_events.lock().await;
+                        if event.paths.is_empty() {
+                            events.insert(repo_root.clone());
+                        }
+                        events.extend(event.paths.iter().cloned());
+                    }
                 }
             }
             _ = debounce_timer.tick() => {
                 let mut events = pending_events.lock().await;
-                if !events.is_empty() {
-                    println!("\n{}", "Detected changes, re-scanning...".yellow());
-                    if let Err(e) = perform_scan(&repo_root, &watch_mode) {
-                        eprintln!("{} {}", "Error during scan:".red(), e);
+                let hold = if events.is_empty() { None } else { hold_reason() };
+                if let Some(reason) = hold {
+                    // Keep the events pending until the hold is lifted
+                    if !deferring {
+                        println!("\n{}", format!("{}, deferring re-scan...", reason).yellow());
+                        deferring = true;
                     }
+                } else if !events.is_empty() {
+                    deferring = false;
+                    let scopes = scan_scopes(&repo_root, &events, &watch_mode);
+                    if scopes == full_scan {
+                        println!("\n{}", "Detected changes, re-scanning...".yellow());
+                    } else {
+                        println!(
+                            "\n{}",
+                            format!(
+                                "Detected changes, re-scanning {} changed path(s)...",
+                                scopes.len()
+                            )
+                            .yellow()
+                        );
+                    }
+                    scan(&watch_mode, &scopes).await;
+                    events.clear();
+                }
+
+                // Let `DaemonStatus::read` tell a working daemon from a stuck one
+                if last_heartbeat.elapsed() >= daemon::HEARTBEAT_INTERVAL {
+                    if let Err(e) = daemon::DaemonStatus::refresh_heartbeat(&config.repo_path) {
+                        eprintln!("{} {}", "Failed to refresh heartbeat:".red(), e);
+                    }
+                    if let Some(ref log_file) = config.log_file
+                        && let Err(e) = daemon::trim_log(log_file, config.log_max_size)
+                    {
+                        eprintln!("{} {:#}", "Failed to trim daemon log:".red(), e);
+                    }
+                    last_heartbeat = Instant::now();
+                }
+            }
+            _ = pattern_refresh_timer.tick(), if config.pattern_source.is_some() => {
+                if let Some(ref source) = config.pattern_source
+                    && let Some(patterns) = refresh_patterns(source, &watch_mode).await
+                {
+                    watch_mode = watch_mode.with_patterns(patterns);
+                    if hold_reason().is_some() {
+                        // Left to the debounce timer, which waits for the hold to lift
+                        pending_events.lock().await.insert(repo_root.clone());
+                    } else {
+                        println!("\n{}", "Pattern list changed, re-scanning...".yellow());
+                        scan(&watch_mode, full_scan).await;
+                    }
+                }
+            }
+            _ = rescan_timer.tick(), if config.rescan_interval.is_some() => {
+                // Safety net for backends that drop events
+                let mut events = pending_events.lock().await;
+                if hold_reason().is_some() {
+                    // Left to the debounce timer, which waits for the hold to lift
+                    events.insert(repo_root.clone());
+                } else {
+                    println!("\n{}", "Periodic re-scan...".yellow());
+                    scan(&watch_mode, full_scan).await;
+                    // Anything pending is covered by this full scan
                     events.clear();
                 }
             }
         }
 
         // Check for shutdown
-        if *shutdown.lock().await {
+        if shutdown.load(Ordering::SeqCst) {
             println!("\n{}", "Shutting down watcher...".yellow());
             break;
         }
+        // The debounce timer keeps the loop turning, so the deadline is never checked late
+        if deadline.is_some_and(|deadline| time::Instant::now() >= deadline) {
+            if daemon::is_paused(&config.repo_path) {
+                // A paused watcher leaves markers alone to the end
+                println!(
+                    "\n{}",
+                    "Maximum runtime reached while paused, shutting down...".yellow()
+                );
+            } else {
+                println!(
+                    "\n{}",
+                    "Maximum runtime reached, running a final scan and shutting down...".yellow()
+                );
+                scan(&watch_mode, full_scan).await;
+            }
+            break;
+        }
     }
 
+    // Final summary, the last thing a daemon writes to its log
+    let metrics = metrics.lock().unwrap();
+    println!(
+        "{} {} scan(s): {} markers added, {} removed, {} errors",
+        "Watcher stopped after".bold(),
+        metrics.scans,
+        metrics.marked,
+        metrics.unmarked,
+        metrics.errors
+    );
+
     Ok(())
 }
 
-fn should_trigger_rescan(event: &Event, watch_mode: &WatchMode) -> bool {
+/// Re-fetch the central pattern list, returning it only if it changed
+///
+/// The fetch runs on a blocking thread, since a slow URL would otherwise stall the event
+/// loop for up to the download timeout. Fetch or validation failures keep the current
+/// patterns in place.
+async fn refresh_patterns(source: &PatternSource, watch_mode: &WatchMode) -> Option<Vec<String>> {
+    let fetch_source = source.clone();
+    let fetched = tokio::task::spawn_blocking(move || fetch_source.fetch())
+        .await
+        .unwrap_or_else(|e| Err(anyhow::anyhow!("Pattern fetch task failed: {}", e)));
+    match fetched {
+        Ok(patterns) if watch_mode.patterns() == Some(patterns.as_slice()) => None,
+        Ok(patterns) => Some(patterns),
+        Err(e) => {
+            eprintln!(
+                "{} {:#} (keeping current patterns)",
+                "Failed to refresh patterns:".red(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Decide whether an event warrants a re-scan
+///
+/// With `close_write`, new and modified files only count once their writer closes them.
+fn should_trigger_rescan(event: &Event, watch_mode: &WatchMode, close_write: bool) -> bool {
     match event.kind {
-        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
+        EventKind::Create(CreateKind::File) | EventKind::Modify(ModifyKind::Data(_))
+            if close_write =>
+        {
+            false
+        }
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) if !close_write => false,
+        EventKind::Create(_)
+        | EventKind::Modify(_)
+        | EventKind::Remove(_)
+        | EventKind::Access(AccessKind::Close(AccessMode::Write)) => {
             match watch_mode {
                 WatchMode::Patterns(_) => {
-                    // For pattern mode, trigger on any file creation/removal
-                    matches!(event.kind, EventKind::Create(_) | EventKind::Remove(_))
+                    // For pattern mode, trigger on any file creation/removal (or finished write)
+                    !matches!(event.kind, EventKind::Modify(_))
                 }
-                _ => {
-                    // For other modes, check if it's a .gitignore file or within .git
-                    event.paths.iter().any(|path| {
-                        path.file_name()
-                            .and_then(|name| name.to_str())
-                            .map(|name| name == ".gitignore")
-                            .unwrap_or(false)
-                            || path.components().any(|c| c.as_os_str() == ".git")
-                    })
+                WatchMode::GitIgnore | WatchMode::Combined(_) => {
+                    // New files may be ignored already; rule changes can affect any file
+                    !matches!(event.kind, EventKind::Modify(_))
+                        || event.paths.iter().any(|path| is_ignore_source(path))
+                }
+                WatchMode::TrackedFiles => {
+                    // For tracked files, check if it's a .gitignore file or within .git
+                    event.paths.iter().any(|path| is_ignore_source(path))
                 }
             }
         }
@@ -173,15 +726,99 @@ fn should_trigger_rescan(event: &Event, watch_mode: &WatchMode) -> bool {
     }
 }
 
-fn perform_scan(repo_root: &Path, watch_mode: &WatchMode) -> Result<()> {
+/// Whether `path` is a `.gitignore` file or lies within `.git`
+fn is_ignore_source(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == ".gitignore")
+        || path.components().any(|c| c.as_os_str() == ".git")
+}
+
+/// Paths a re-scan has to cover once the given paths changed
+///
+/// Pattern and gitignore modes only re-evaluate the changed paths themselves (walking
+/// into changed directories), as existing rules decide each path on its own. A change to
+/// an ignore source can affect the whole tree, so it falls back to the repository root,
+/// as do the other modes. Paths that no longer exist took their markers with them.
+fn scan_scopes(
+    repo_root: &Path,
+    changed: &HashSet<PathBuf>,
+    watch_mode: &WatchMode,
+) -> Vec<PathBuf> {
+    if !matches!(watch_mode, WatchMode::Patterns(_) | WatchMode::GitIgnore)
+        || changed
+            .iter()
+            .any(|path| is_ignore_source(path) || !path.starts_with(repo_root))
+    {
+        return vec![repo_root.to_path_buf()];
+    }
+
+    let mut scopes: Vec<PathBuf> = changed
+        .iter()
+        .filter(|path| path.exists())
+        .cloned()
+        .collect();
+
+    // Sorting puts each subtree right after its root, so nested scopes are dropped
+    scopes.sort();
+    scopes.dedup_by(|later, earlier| later.starts_with(earlier));
+    scopes
+}
+
+/// Depth limit for a walk from `scope`, matching `max_depth` below the repository root
+///
+/// `None` inside the outer `Option` means unlimited; `None` overall means `scope` is
+/// already deeper than the limit.
+fn scope_max_depth(
+    repo_root: &Path,
+    scope: &Path,
+    max_depth: Option<usize>,
+) -> Option<Option<usize>> {
+    let depth = scope
+        .strip_prefix(repo_root)
+        .map_or(0, |relative| relative.components().count());
+    match max_depth {
+        Some(max) => max.checked_sub(depth).map(Some),
+        None => Some(None),
+    }
+}
+
+/// Run [`perform_scan`] on a blocking thread
+///
+/// Scans walk the tree and, with a rate limit, sleep between marker changes; off the
+/// runtime thread, they can't hold up the event loop, timers or signal handling.
+async fn perform_scan_blocking(
+    repo_root: &Path,
+    watch_mode: &WatchMode,
+    scopes: &[PathBuf],
+    options: &Arc<ScanOptions>,
+) -> Result<ScanSummary> {
+    let repo_root = repo_root.to_path_buf();
+    let watch_mode = watch_mode.clone();
+    let scopes = scopes.to_vec();
+    let options = Arc::clone(options);
+    tokio::task::spawn_blocking(move || perform_scan(&repo_root, &watch_mode, &scopes, &options))
+        .await
+        .context("Scan task failed")?
+}
+
+/// Run a scan of the given paths
+///
+/// Only pattern and gitignore modes honour `scopes`; the other modes always scan the
+/// whole tree.
+fn perform_scan(
+    repo_root: &Path,
+    watch_mode: &WatchMode,
+    scopes: &[PathBuf],
+    options: &ScanOptions,
+) -> Result<ScanSummary> {
     match watch_mode {
-        WatchMode::TrackedFiles => perform_tracked_files_scan(repo_root),
-        WatchMode::GitIgnore => perform_gitignore_scan(repo_root),
-        WatchMode::Patterns(patterns) => perform_pattern_scan(repo_root, patterns),
+        WatchMode::TrackedFiles => perform_tracked_files_scan(repo_root, options),
+        WatchMode::GitIgnore => perform_gitignore_scan(repo_root, scopes, options),
+        WatchMode::Patterns(patterns) => perform_pattern_scan(repo_root, patterns, scopes, options),
+        WatchMode::Combined(patterns) => perform_combined_scan(repo_root, patterns, options),
     }
 }
 
-fn perform_tracked_files_scan(repo_root: &Path) -> Result<()> {
+fn perform_tracked_files_scan(repo_root: &Path, options: &ScanOptions) -> Result<ScanSummary> {
     // Load tracked files
     let mut tracked = tracked_files::TrackedFiles::load(repo_root)?;
 
@@ -190,38 +827,49 @@ fn perform_tracked_files_scan(repo_root: &Path) -> Result<()> {
             "{}",
             "No files are being tracked. Use 'dbx-ignore <files>' to mark files first.".yellow()
         );
-        return Ok(());
+        return Ok(ScanSummary::default());
     }
 
-    // Get current git-ignored files
-    let git_ignored = git_utils::get_git_ignored_files_in_path(repo_root)?;
-    let git_ignored_set: HashSet<_> = git_ignored.into_iter().collect();
+    // Get current git-ignored files, and the directories ignored as a whole
+    let git_ignored = git_utils::get_git_ignored_entries_scoped(
+        repo_root,
+        options.recurse_submodules,
+        options.max_depth,
+    )?;
+    let git_ignored_set: HashSet<_> = git_ignored.files.iter().collect();
 
     let mut updated = 0;
     let mut removed = 0;
     let mut errors = 0;
+    let mut summary = ScanSummary::default();
 
     // Check each tracked file
-    for tracked_file in tracked.marked_files.clone() {
+    for tracked_path in tracked.marked_files.clone() {
+        // Tracked paths are relative to the repository root
+        let tracked_file = repo_root.join(&tracked_path);
         if !tracked_file.exists() {
             // File no longer exists, remove from tracking
-            tracked.remove_files(&[tracked_file.clone()]);
+            tracked.remove_files(std::slice::from_ref(&tracked_file));
             removed += 1;
             continue;
         }
 
-        let should_be_ignored = git_ignored_set.contains(&tracked_file);
+        // A directory marked as a unit stays marked exactly while its rules ignore it
+        let should_be_ignored = git_ignored_set.contains(&tracked_file)
+            || (tracked_file.is_dir() && git_ignored.in_ignored_dir(&tracked_file));
         let has_marker = platform_utils::has_any_ignore_attribute(&tracked_file);
 
-        if should_be_ignored && !has_marker {
+        if should_be_ignored && !has_marker && !options.should_skip(&tracked_file) {
             // File should be ignored but isn't - add marker
-            match platform_utils::add_ignore_attributes(&tracked_file, false) {
+            match options.add_marker(&tracked_file) {
                 Ok(count) => {
                     if count > 0 {
                         updated += 1;
+                        summary.marked += 1;
                         println!(
-                            "  {} Added ignore marker to: {}",
+                            "  {} {}: {}",
                             "✓".green(),
+                            options.added_label(),
                             tracked_file.display()
                         );
                     }
@@ -238,13 +886,15 @@ fn perform_tracked_files_scan(repo_root: &Path) -> Result<()> {
             }
         } else if !should_be_ignored && has_marker {
             // File should not be ignored but has marker - remove it
-            match platform_utils::remove_ignore_attributes(&tracked_file) {
+            match options.remove_marker(&tracked_file) {
                 Ok(count) => {
                     if count > 0 {
                         updated += 1;
+                        summary.unmarked += 1;
                         println!(
-                            "  {} Removed ignore marker from: {}",
+                            "  {} {}: {}",
                             "✓".green(),
+                            options.removed_label(),
                             tracked_file.display()
                         );
                     }
@@ -263,12 +913,14 @@ fn perform_tracked_files_scan(repo_root: &Path) -> Result<()> {
     }
 
     // Save updated tracked files
-    tracked.save(repo_root)?;
+    if !options.observe {
+        tracked.save(repo_root)?;
+    }
 
     if updated > 0 || removed > 0 || errors > 0 {
         println!(
             "{} {} files updated, {} removed from tracking, {} errors",
-            "Summary:".green().bold(),
+            options.summary_label().green().bold(),
             updated,
             removed,
             errors
@@ -277,30 +929,61 @@ fn perform_tracked_files_scan(repo_root: &Path) -> Result<()> {
         println!("{}", "All tracked files are up to date.".green());
     }
 
-    Ok(())
+    summary.errors = errors;
+    Ok(summary)
 }
 
-fn perform_gitignore_scan(repo_root: &Path) -> Result<()> {
-    // Get all git-ignored files
-    let git_ignored = git_utils::get_git_ignored_files_in_path(repo_root)?;
+fn perform_gitignore_scan(
+    repo_root: &Path,
+    scopes: &[PathBuf],
+    options: &ScanOptions,
+) -> Result<ScanSummary> {
+    // Get the git-ignored files in scope, and the directories ignored as a whole
+    let git_ignored = git_utils::get_git_ignored_entries_among(
+        repo_root,
+        scopes,
+        options.recurse_submodules,
+        options.max_depth,
+    )?;
+
+    // Get the files with markers in scope
+    let marked_files = platform_utils::find_marked_files_among(
+        repo_root,
+        scopes,
+        options.recurse_submodules,
+        options.max_depth,
+        options.parallel_walk_threshold,
+    )?;
+
+    // Directories marked as a unit stay collapsed while their rules still ignore them
+    let collapsed_dirs = collapsed_dirs(repo_root, &marked_files, &git_ignored)?;
 
     let mut added = 0;
     let mut removed = 0;
     let mut errors = 0;
+    let mut explainer = GitignoreExplainer::new(repo_root);
 
-    // Process all git-ignored files
-    for file_path in &git_ignored {
-        if !platform_utils::has_any_ignore_attribute(file_path) {
+    // Process the collapsed directories, then the git-ignored files outside them
+    let to_mark = collapsed_dirs.iter().chain(
+        git_ignored
+            .files
+            .iter()
+            .filter(|file| !collapsed_dirs.iter().any(|dir| file.starts_with(dir))),
+    );
+    for file_path in to_mark {
+        if !platform_utils::has_any_ignore_attribute(file_path) && !options.should_skip(file_path) {
             // File should be ignored but isn't - add marker
-            match platform_utils::add_ignore_attributes(file_path, false) {
+            match options.add_marker(file_path) {
                 Ok(count) => {
                     if count > 0 {
                         added += 1;
                         if added <= MAX_FILES_TO_DISPLAY {
                             println!(
-                                "  {} Added ignore marker to: {}",
+                                "  {} {}: {}{}",
                                 "✓".green(),
-                                file_path.display()
+                                options.added_label(),
+                                file_path.display(),
+                                describe_reason(explainer.explain(file_path), repo_root)
                             );
                         }
                     }
@@ -320,25 +1003,24 @@ fn perform_gitignore_scan(repo_root: &Path) -> Result<()> {
         }
     }
 
-    // Check for files that have markers but are no longer git-ignored
-    let git_ignored_set: HashSet<_> = git_ignored.into_iter().collect();
-
-    // Get all files with markers in the repository
-    let marked_files = find_marked_files(repo_root)?;
+    // Check for files (and directories) that have markers but are no longer git-ignored
+    let git_ignored_set: HashSet<_> = git_ignored.files.iter().collect();
 
     for marked_file in marked_files {
         if !git_ignored_set.contains(&marked_file)
+            && !git_ignored.in_ignored_dir(&marked_file)
             && platform_utils::has_any_ignore_attribute(&marked_file)
         {
             // File has marker but is no longer git-ignored - remove it
-            match platform_utils::remove_ignore_attributes(&marked_file) {
+            match options.remove_marker(&marked_file) {
                 Ok(count) => {
                     if count > 0 {
                         removed += 1;
                         if removed <= MAX_FILES_TO_DISPLAY {
                             println!(
-                                "  {} Removed ignore marker from: {}",
+                                "  {} {}: {}",
                                 "✓".green(),
+                                options.removed_label(),
                                 marked_file.display()
                             );
                         }
@@ -372,7 +1054,7 @@ fn perform_gitignore_scan(repo_root: &Path) -> Result<()> {
     if added > 0 || removed > 0 || errors > 0 {
         println!(
             "{} {} markers added, {} removed, {} errors",
-            "Summary:".green().bold(),
+            options.summary_label().green().bold(),
             added,
             removed,
             errors
@@ -381,71 +1063,268 @@ fn perform_gitignore_scan(repo_root: &Path) -> Result<()> {
         println!("{}", "All git-ignored files are properly marked.".green());
     }
 
-    Ok(())
+    Ok(ScanSummary {
+        marked: added,
+        unmarked: removed,
+        errors,
+    })
 }
 
-fn find_marked_files(repo_root: &Path) -> Result<Vec<PathBuf>> {
-    use ignore::WalkBuilder;
+/// Keep the union of tracked paths, git-ignored files and pattern matches marked
+///
+/// Markers are only removed from paths in none of the three sets (nor inside a
+/// directory that is). Tracked paths that no longer exist are dropped from tracking.
+fn perform_combined_scan(
+    repo_root: &Path,
+    patterns: &[String],
+    options: &ScanOptions,
+) -> Result<ScanSummary> {
+    let mut tracked = tracked_files::TrackedFiles::load(repo_root)?;
+    let git_ignored = git_utils::get_git_ignored_entries_scoped(
+        repo_root,
+        options.recurse_submodules,
+        options.max_depth,
+    )?;
+    let marked_files = platform_utils::find_marked_files(
+        repo_root,
+        options.recurse_submodules,
+        options.max_depth,
+        options.parallel_walk_threshold,
+    )?;
+    let collapsed_dirs = collapsed_dirs(repo_root, &marked_files, &git_ignored)?;
 
-    let mut marked_files = Vec::new();
+    let mut gone = Vec::new();
+    let mut wanted: BTreeSet<PathBuf> = BTreeSet::new();
+    for path in &tracked.marked_files {
+        let full_path = repo_root.join(path);
+        if full_path.exists() {
+            wanted.insert(full_path);
+        } else {
+            gone.push(path.clone());
+        }
+    }
+    wanted.extend(collapsed_dirs.iter().cloned());
+    wanted.extend(
+        git_ignored
+            .files
+            .iter()
+            .filter(|file| !collapsed_dirs.iter().any(|dir| file.starts_with(dir)))
+            .cloned(),
+    );
+    if !patterns.is_empty() {
+        wanted.extend(
+            PatternMatcher::new(repo_root, patterns, options.respect_gitignore)?
+                .with_parallel_walk_threshold(options.parallel_walk_threshold)
+                .with_max_depth(options.max_depth)
+                .find_matching_files(repo_root)?,
+        );
+    }
+
+    let mut added = 0;
+    let mut removed = 0;
+    let mut errors = 0;
+
+    for path in &wanted {
+        if platform_utils::has_any_ignore_attribute(path) || options.should_skip(path) {
+            continue;
+        }
+        match options.add_marker(path) {
+            Ok(count) => {
+                if count > 0 {
+                    added += 1;
+                    if added <= MAX_FILES_TO_DISPLAY {
+                        println!(
+                            "  {} {}: {}",
+                            "✓".green(),
+                            options.added_label(),
+                            path.display()
+                        );
+                    }
+                }
+            }
+            Err(e) => {
+                errors += 1;
+                if errors <= MAX_ERRORS_TO_DISPLAY {
+                    eprintln!(
+                        "  {} Failed to add marker to {}: {}",
+                        "✗".red(),
+                        path.display(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    for marked_file in marked_files {
+        // Inside a wanted directory (or one git ignores as a whole) still counts as wanted
+        let covered = marked_file
+            .ancestors()
+            .take_while(|ancestor| *ancestor != repo_root)
+            .any(|ancestor| wanted.contains(ancestor))
+            || git_ignored.in_ignored_dir(&marked_file);
+        if covered || !platform_utils::has_any_ignore_attribute(&marked_file) {
+            continue;
+        }
+        match options.remove_marker(&marked_file) {
+            Ok(count) => {
+                if count > 0 {
+                    removed += 1;
+                    if removed <= MAX_FILES_TO_DISPLAY {
+                        println!(
+                            "  {} {}: {}",
+                            "✓".green(),
+                            options.removed_label(),
+                            marked_file.display()
+                        );
+                    }
+                }
+            }
+            Err(e) => {
+                errors += 1;
+                if errors <= MAX_ERRORS_TO_DISPLAY {
+                    eprintln!(
+                        "  {} Failed to remove marker from {}: {}",
+                        "✗".red(),
+                        marked_file.display(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    if !gone.is_empty() && !options.observe {
+        tracked.remove_files(&gone);
+        tracked.save(repo_root)?;
+    }
+
+    if added > MAX_FILES_TO_DISPLAY {
+        println!("  ... and {} more files", added - MAX_FILES_TO_DISPLAY);
+    }
+    if removed > MAX_FILES_TO_DISPLAY {
+        println!("  ... and {} more files", removed - MAX_FILES_TO_DISPLAY);
+    }
+    if errors > MAX_ERRORS_TO_DISPLAY {
+        eprintln!("  ... and {} more errors", errors - MAX_ERRORS_TO_DISPLAY);
+    }
+
+    if added > 0 || removed > 0 || errors > 0 || !gone.is_empty() {
+        println!(
+            "{} {} markers added, {} removed, {} removed from tracking, {} errors",
+            options.summary_label().green().bold(),
+            added,
+            removed,
+            gone.len(),
+            errors
+        );
+    } else {
+        println!(
+            "{}",
+            "All tracked, git-ignored and pattern-matched files are properly marked.".green()
+        );
+    }
 
-    let walker = WalkBuilder::new(repo_root)
-        .standard_filters(false)
-        .hidden(false)
-        .git_ignore(false)
-        .git_global(false)
-        .git_exclude(false)
-        .filter_entry(|entry| {
-            // Skip .git directory
-            entry
-                .file_name()
-                .to_str()
-                .map(|name| name != ".git")
-                .unwrap_or(true)
-        })
-        .build();
-
-    for entry in walker.flatten() {
-        let path = entry.path();
-        if platform_utils::has_any_ignore_attribute(path) {
-            marked_files.push(path.to_path_buf());
-        }
-    }
-
-    Ok(marked_files)
+    Ok(ScanSummary {
+        marked: added,
+        unmarked: removed,
+        errors,
+    })
 }
 
-fn perform_pattern_scan(repo_root: &Path, patterns: &[String]) -> Result<()> {
+/// Directories that should carry a marker as a whole rather than file by file
+///
+/// These are the ignored directories that are already marked, plus those recorded in the
+/// tracked state: a tracked directory whose `.gitignore` rules stop ignoring it drops out
+/// (its marker is then removed like any other) and is collapsed again once they ignore it
+/// anew. Directories inside another collapsed directory are left out.
+fn collapsed_dirs(
+    repo_root: &Path,
+    marked_files: &[PathBuf],
+    git_ignored: &git_utils::GitIgnoredEntries,
+) -> Result<Vec<PathBuf>> {
+    let tracked = tracked_files::TrackedFiles::load(repo_root)?;
+    let mut dirs: Vec<PathBuf> = marked_files
+        .iter()
+        .cloned()
+        .chain(tracked.marked_files.iter().map(|path| repo_root.join(path)))
+        .filter(|path| path.is_dir() && git_ignored.in_ignored_dir(path))
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+
+    let mut outermost: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        if !outermost.iter().any(|outer| dir.starts_with(outer)) {
+            outermost.push(dir);
+        }
+    }
+    Ok(outermost)
+}
+
+/// Format the rule that selected a file as a suffix for marking output
+fn describe_reason(reason: Option<IgnoreReason>, repo_root: &Path) -> String {
+    reason
+        .map(|reason| format!(" {}", format!("— {}", reason.describe(repo_root)).dimmed()))
+        .unwrap_or_default()
+}
+
+fn perform_pattern_scan(
+    repo_root: &Path,
+    patterns: &[String],
+    scopes: &[PathBuf],
+    options: &ScanOptions,
+) -> Result<ScanSummary> {
     let mut added = 0;
     let mut removed = 0;
     let mut errors = 0;
 
-    // Use our consistent pattern matcher
-    let files_to_mark =
-        match crate::utils::git_utils::find_files_matching_patterns(repo_root, patterns) {
-            Ok(files) => files.into_iter().collect::<HashSet<_>>(),
-            Err(e) => {
-                eprintln!(
-                    "  {} Failed to find files matching patterns: {}",
-                    "✗".red(),
-                    e
-                );
-                return Ok(());
+    // Use our consistent pattern matcher, built once for every scope
+    let scan =
+        PatternMatcher::new(repo_root, patterns, options.respect_gitignore).and_then(|matcher| {
+            let matcher = matcher.with_parallel_walk_threshold(options.parallel_walk_threshold);
+            let mut files = Vec::new();
+            for scope in scopes {
+                if let Some(max_depth) = scope_max_depth(repo_root, scope, options.max_depth) {
+                    files.extend(matcher.find_matching_files_to_depth(scope, max_depth)?);
+                }
             }
-        };
+            Ok((matcher, files))
+        });
+    let (matcher, files_to_mark) = match scan {
+        Ok((matcher, files)) => (matcher, files.into_iter().collect::<HashSet<_>>()),
+        Err(e) => {
+            eprintln!(
+                "  {} Failed to find files matching patterns: {}",
+                "✗".red(),
+                e
+            );
+            return Ok(ScanSummary {
+                errors: 1,
+                ..Default::default()
+            });
+        }
+    };
 
     // Mark files that match patterns but aren't marked
     for file_path in &files_to_mark {
-        if !platform_utils::has_any_ignore_attribute(file_path) {
-            match platform_utils::add_ignore_attributes(file_path, false) {
+        if !platform_utils::has_any_ignore_attribute(file_path) && !options.should_skip(file_path) {
+            match options.add_marker(file_path) {
                 Ok(count) => {
                     if count > 0 {
                         added += 1;
                         if added <= MAX_FILES_TO_DISPLAY {
                             println!(
-                                "  {} Added ignore marker to: {}",
+                                "  {} {}: {}{}",
                                 "✓".green(),
-                                file_path.display()
+                                options.added_label(),
+                                file_path.display(),
+                                describe_reason(
+                                    matcher
+                                        .matching_pattern(file_path)
+                                        .map(|p| IgnoreReason::Pattern(p.to_string())),
+                                    repo_root
+                                )
                             );
                         }
                     }
@@ -466,26 +1345,38 @@ fn perform_pattern_scan(repo_root: &Path, patterns: &[String]) -> Result<()> {
     }
 
     // Find all marked files and remove markers from those that don't match patterns
-    let marked_files = find_marked_files(repo_root)?;
+    let mut marked_files = Vec::new();
+    for scope in scopes {
+        if let Some(max_depth) = scope_max_depth(repo_root, scope, options.max_depth) {
+            marked_files.extend(platform_utils::find_marked_files(
+                scope,
+                options.recurse_submodules,
+                max_depth,
+                options.parallel_walk_threshold,
+            )?);
+        }
+    }
 
     for marked_file in marked_files {
         if !files_to_mark.contains(&marked_file)
             && platform_utils::has_any_ignore_attribute(&marked_file)
         {
-            // Check if file matches any pattern using the pattern matcher
+            // Check if file matches any pattern using the pattern matcher; markers on
+            // matching files the repository ignores are left alone, just not added
             let matches_pattern =
                 crate::utils::pattern_matcher::matches_patterns(repo_root, &marked_file, patterns)
                     .unwrap_or(false);
 
             if !matches_pattern {
-                match platform_utils::remove_ignore_attributes(&marked_file) {
+                match options.remove_marker(&marked_file) {
                     Ok(count) => {
                         if count > 0 {
                             removed += 1;
                             if removed <= MAX_FILES_TO_DISPLAY {
                                 println!(
-                                    "  {} Removed ignore marker from: {}",
+                                    "  {} {}: {}",
                                     "✓".green(),
+                                    options.removed_label(),
                                     marked_file.display()
                                 );
                             }
@@ -520,7 +1411,7 @@ fn perform_pattern_scan(repo_root: &Path, patterns: &[String]) -> Result<()> {
     if added > 0 || removed > 0 || errors > 0 {
         println!(
             "{} {} markers added, {} removed, {} errors",
-            "Summary:".green().bold(),
+            options.summary_label().green().bold(),
             added,
             removed,
             errors
@@ -532,41 +1423,9 @@ fn perform_pattern_scan(repo_root: &Path, patterns: &[String]) -> Result<()> {
         );
     }
 
-    Ok(())
-}
-
-fn find_gitignore_files(repo_root: &Path) -> Result<Vec<PathBuf>> {
-    use ignore::WalkBuilder;
-
-    let mut gitignore_files = Vec::new();
-
-    let walker = WalkBuilder::new(repo_root)
-        .standard_filters(false)
-        .hidden(false)
-        .git_ignore(false)
-        .git_global(false)
-        .git_exclude(false)
-        .filter_entry(|entry| {
-            // Skip .git directory
-            entry
-                .file_name()
-                .to_str()
-                .map(|name| name != ".git")
-                .unwrap_or(true)
-        })
-        .build();
-
-    for entry in walker.flatten() {
-        let path = entry.path();
-        if path
-            .file_name()
-            .and_then(|name| name.to_str())
-            .map(|name| name == ".gitignore")
-            .unwrap_or(false)
-        {
-            gitignore_files.push(path.to_path_buf());
-        }
-    }
-
-    Ok(gitignore_files)
+    Ok(ScanSummary {
+        marked: added,
+        unmarked: removed,
+        errors,
+    })
 }
@@ -1,30 +1,61 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
 
 pub mod core;
 pub mod platforms;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 pub mod traits;
 pub mod utils;
 
+use crate::core::file_selector::{Filter, Source};
 use crate::platforms::CurrentPlatform;
 use crate::traits::PlatformHandler;
+use crate::utils::ignore_reason::{GitignoreExplainer, IgnoreReason};
+use crate::utils::output_template::{FileOutcome, OutputTemplate};
 
 // Re-export the show_status function and modules
+pub use crate::core::conflicts;
+pub use crate::core::conflicts::show_conflicts;
+pub use crate::core::daemon::{pause_watcher, unwatch_all};
+pub use crate::core::doctor::show_doctor;
+pub use crate::core::drift;
+pub use crate::core::drift::{show_check, show_drift, show_import_markers, show_verify};
+pub use crate::core::file_selector;
+pub use crate::core::file_selector::{FileSelector, is_glob_pattern};
+pub use crate::core::gitignore_diff;
+pub use crate::core::gitignore_diff::show_gitignore_diff;
+pub use crate::core::history;
+pub use crate::core::history::show_log;
+pub use crate::core::marker_query;
+pub use crate::core::marker_query::show_is_marked;
+pub use crate::core::repo_config;
+pub use crate::core::schema;
+pub use crate::core::schema::show_schema;
+pub use crate::core::selective_sync;
+pub use crate::core::selective_sync::show_selective_sync_report;
+pub use crate::core::state_location;
 pub use crate::core::status;
 pub use crate::core::status::show_status;
 pub use crate::core::tracked_files;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Action {
+    #[default]
     Ignore,
     Reset,
     Watch,
     Unwatch,
+    List,
 }
 
 impl std::fmt::Display for Action {
@@ -34,6 +65,7 @@ impl std::fmt::Display for Action {
             Action::Reset => write!(f, "reset"),
             Action::Watch => write!(f, "watch"),
             Action::Unwatch => write!(f, "unwatch"),
+            Action::List => write!(f, "list"),
         }
     }
 }
@@ -47,27 +79,218 @@ impl std::str::FromStr for Action {
             "reset" => Ok(Action::Reset),
             "watch" => Ok(Action::Watch),
             "unwatch" => Ok(Action::Unwatch),
+            "list" => Ok(Action::List),
             _ => Err(anyhow::anyhow!(
-                "Invalid action: {}. Valid actions are: ignore, reset, watch, unwatch",
+                "Invalid action: {}. Valid actions are: ignore, reset, watch, unwatch, list",
                 s
             )),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Config {
     pub action: Action,
     pub dry_run: bool,
     pub verbose: bool,
     pub quiet: bool,
+    /// Per-path output line replacing the decorated output (`--format`)
+    pub format: Option<OutputTemplate>,
     pub files: Vec<PathBuf>,
     pub patterns: Vec<String>, // Original patterns provided by user
+    /// Ecosystem presets (`--preset`) whose patterns are added to the selection
+    pub presets: Vec<String>,
     pub git_mode: bool,
     pub daemon_mode: bool,
+    /// Select files flagged skip-worktree or assume-unchanged in the git index
+    pub skip_worktree: bool,
+    /// Select the untracked and ignored entries git status lists
+    pub git_status: bool,
+    /// Only process files the sync client currently syncs (marking only)
+    pub synced_only: bool,
+    /// Select the files this `.dockerignore` leaves out of the Docker build context
+    pub dockerignore: Option<PathBuf>,
+    /// Treat `files` as literal paths, never as glob patterns (e.g. from --files0-from)
+    pub literal_paths: bool,
+    /// Only process paths last modified longer ago than this
+    pub older_than: Option<Duration>,
+    /// Only process paths modified within this long, e.g. the artifacts of the last build
+    pub since: Option<Duration>,
+    /// Only process paths of at least this many bytes (directories by their total contents)
+    pub min_size: Option<u64>,
+    /// Only process paths matching this `--where` expression
+    pub where_predicate: Option<utils::predicate::Predicate>,
+    /// Only process paths carrying this attribute (`--where-xattr`)
+    pub where_xattr: Option<utils::platform_utils::AttributeCondition>,
+    /// Descend into git submodules (with their own ignore rules) in git mode
+    pub recurse_submodules: bool,
+    /// Limit git mode to the current directory instead of the whole repository
+    pub cwd_scope: bool,
+    /// Directory to run in instead of the current one (`--repo`)
+    pub repo: Option<PathBuf>,
+    /// Select with only the rules of `.gitignore` files given as arguments, not inherited ones
+    pub local_only: bool,
+    /// Allow paths that resolve outside the repository (or working directory)
+    pub allow_outside: bool,
+    /// Process the targets of symlinks instead of skipping the links
+    pub follow_symlinks: bool,
+    /// Maximum marker operations per second (unlimited if `None`), for shared volumes
+    pub rate_limit: Option<u32>,
+    /// Worker threads used to process paths (rayon's default if `None`; 1 is sequential)
+    pub jobs: Option<usize>,
+    /// Top-level entries at which directory walks go parallel (64 if `None`; 0 always)
+    pub parallel_walk_threshold: Option<usize>,
+    /// Value written with ignore markers (resolved from `DBX_IGNORE_MARKER_VALUE` and
+    /// `.dbx-ignore/config.toml` if `None`)
+    pub marker_value: Option<Vec<u8>>,
+    /// Lower the process scheduling priority for the duration of the run
+    pub nice: bool,
+    /// Maximum directory depth searched below the working directory (unlimited if `None`)
+    pub max_depth: Option<usize>,
+    /// Have the watch daemon mark files once writers close them rather than on every modify
+    pub close_write: bool,
+    /// Postpone the daemon's scans while Dropbox is syncing (`--watch` only)
+    pub defer_during_sync: bool,
+    /// Plain-text pidfile the watch daemon writes for process supervisors
+    pub pidfile: Option<PathBuf>,
+    /// Central pattern list the watch daemon follows instead of the tracked patterns
+    pub pattern_source: Option<core::pattern_source::PatternSource>,
+    /// How often the daemon re-fetches `pattern_source` (default if `None`)
+    pub pattern_refresh: Option<Duration>,
+    /// Have the daemon run a full scan on this interval regardless of events (off if `None`)
+    pub rescan_interval: Option<Duration>,
+    /// Have the daemon exit on its own after running this long (unbounded if `None`)
+    pub max_runtime: Option<Duration>,
+    /// Command printing NUL-separated paths to leave out of the run
+    pub pre_scan_hook: Option<String>,
+    /// Continue the run when the pre-scan hook fails instead of aborting
+    pub keep_going: bool,
+    /// Command the watch daemon runs after each scan that changed markers
+    pub on_change: Option<String>,
+    /// Prometheus text file the watch daemon keeps its scan counters in
+    pub metrics_file: Option<PathBuf>,
+    /// Size the watch daemon's log is capped at (1 MB if `None`)
+    pub log_max_size: Option<u64>,
+    /// Have the watch daemon show a desktop notification when a scan records errors
+    pub notify_on_error: bool,
+    /// Have the watch daemon only log the marker changes it would make
+    pub observe: bool,
+    /// Have the watch daemon reconcile tracked files, git-ignored files and patterns together
+    pub combined: bool,
+    /// Have the watch daemon leave out pattern matches the repository's `.gitignore` ignores
+    pub respect_gitignore: bool,
+    /// Suppress all output, including messages `quiet` still lets through (implies `quiet`)
+    pub silent: bool,
+    /// Tag recorded for the files marked, or the tag whose files a reset is limited to
+    pub tag: Option<String>,
+    /// Watch daemon debounce window (500ms if `None`, never below 50ms)
+    pub debounce: Option<Duration>,
+    /// Gitignore-style patterns for paths to leave out of the selection
+    pub exclude: Vec<String>,
+    /// When marking, remove existing markers from excluded paths
+    pub reset_excluded: bool,
+    /// Fail the run when any path couldn't be processed
+    pub strict: bool,
+    /// Restore each path's access and modification times after changing its markers
+    pub preserve_times: bool,
+    /// Print `--list` paths NUL-separated (stdin paths are split by the caller)
+    pub null: bool,
+    /// Skip a plain `--git` run when nothing changed since the last one (repo config `scan_cache`)
+    pub scan_cache: bool,
+    /// Run even when the scan cache says there is nothing to do
+    pub force: bool,
+    /// Revert the most recent marking or reset recorded in the history (`--undo`)
+    pub undo: bool,
+    /// Receives progress events in place of the progress bar, for embedding front-ends
+    pub progress_sink: Option<std::sync::mpsc::Sender<ProgressEvent>>,
 }
 
+/// Progress of a run, sent to [`Config::progress_sink`]
+///
+/// Paths are processed in parallel, so `FileDone` and `FileFailed` events arrive in no
+/// particular order. Sending never blocks; events are dropped once the receiver is gone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    /// Processing is about to start on `total` paths
+    Started { total: usize },
+    /// A path was processed, with `ops` markers added or removed (0 if it needed no change)
+    FileDone { path: PathBuf, ops: usize },
+    /// A path couldn't be processed
+    FileFailed { path: PathBuf, error: String },
+    /// Every path has been handled
+    Finished { processed: usize, ops: usize },
+}
+
+impl Config {
+    /// Whether a filter option is set, which scans the whole tree when no paths are given
+    pub fn has_filters(&self) -> bool {
+        self.older_than.is_some() || self.where_predicate.is_some() || self.where_xattr.is_some()
+    }
+
+    /// Whether the watch daemon should only log its marker changes (`--observe`, or
+    /// `--dry-run` alongside `--watch`)
+    pub fn watch_observes(&self) -> bool {
+        self.observe || self.dry_run
+    }
+
+    /// Top-level entries at which directory walks go parallel
+    fn parallel_walk_threshold(&self) -> usize {
+        self.parallel_walk_threshold
+            .unwrap_or(utils::path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD)
+    }
+
+    /// Value written with ignore markers, once the run has resolved it
+    fn marker_value(&self) -> &[u8] {
+        self.marker_value
+            .as_deref()
+            .unwrap_or(utils::platform_utils::DEFAULT_MARKER_VALUE)
+    }
+}
+
+/// What a run did, for programs embedding the crate
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunSummary {
+    /// Paths processed without error
+    pub files_processed: usize,
+    /// Markers added or removed (or that would be, in a dry run)
+    pub operations_performed: usize,
+    /// Paths that failed, with the error message
+    pub errors: Vec<(PathBuf, String)>,
+    /// Processed paths that needed no change (already marked, or nothing to remove)
+    pub skipped: usize,
+}
+
+impl RunSummary {
+    /// The counts as one `key=value` line, as `--count-only` prints them
+    pub fn count_line(&self) -> String {
+        format!(
+            "processed={} operations={} errors={}",
+            self.files_processed,
+            self.operations_performed,
+            self.errors.len()
+        )
+    }
+}
+
+/// Run dbx-ignore with the given configuration, printing progress as the CLI does
 pub fn run(config: Config) -> Result<()> {
+    run_with_summary(config).map(|_| ())
+}
+
+/// Run dbx-ignore and report what happened
+///
+/// Output is printed as by [`run`], subject to `quiet`. Watch and unwatch runs report
+/// only the files marked before the daemon starts.
+pub fn run_with_summary(mut config: Config) -> Result<RunSummary> {
+    // Cache current directory for the entire run
+    let current_dir = match config.repo {
+        Some(ref repo) => resolve_repo_dir(repo)?,
+        None => utils::path_utils::current_dir()?,
+    };
+
+    // Settings from .dbx-ignore/config.toml fill in what the command line left unset
+    core::repo_config::RepoConfig::load(&current_dir)?.apply_to(&mut config);
+
     // Check platform support
     if !CurrentPlatform::is_supported() {
         if !config.quiet {
@@ -78,11 +301,37 @@ pub fn run(config: Config) -> Result<()> {
             );
             println!("Supported platforms: macOS, Linux, Windows");
         }
-        return Ok(());
+        return Ok(RunSummary::default());
     }
 
-    // Cache current directory for the entire run
-    let current_dir = std::env::current_dir()?;
+    if config.undo {
+        return undo_last_operation(config, &current_dir);
+    }
+
+    // Lower priority before the tokio and rayon worker threads exist, so they inherit it
+    if config.nice
+        && let Err(e) = utils::priority::lower_priority()
+        && !config.quiet
+    {
+        eprintln!("{} Could not lower process priority: {}", "⚠".yellow(), e);
+    }
+
+    if !config.quiet
+        && !matches!(
+            config.action,
+            Action::Reset | Action::Unwatch | Action::List
+        )
+    {
+        warn_if_inside_ignored_tree(&current_dir);
+    }
+
+    // Dropbox folder detection is only dependable where the client reports its folders
+    if cfg!(any(target_os = "macos", target_os = "windows"))
+        && !config.quiet
+        && matches!(config.action, Action::Ignore | Action::Watch)
+    {
+        warn_if_outside_dropbox(&current_dir);
+    }
 
     // Handle watch/unwatch modes
     match config.action {
@@ -91,15 +340,18 @@ pub fn run(config: Config) -> Result<()> {
 
             // Check if daemon is already running
             if let Some(status) = core::daemon::DaemonStatus::read(&repo_path)? {
-                println!(
-                    "{} A daemon is already watching this repository (PID: {})",
-                    "⚠".yellow(),
-                    status.pid
-                );
-                return Ok(());
+                if !config.silent {
+                    println!(
+                        "{} A daemon is already watching this repository (PID: {})",
+                        "⚠".yellow(),
+                        status.pid
+                    );
+                }
+                return Ok(RunSummary::default());
             }
 
             // If files/patterns provided with --watch, process them first
+            let mut summary = RunSummary::default();
             if !config.files.is_empty() && !config.daemon_mode {
                 if !config.quiet {
                     println!(
@@ -111,9 +363,10 @@ pub fn run(config: Config) -> Result<()> {
                 // Create a temporary config for marking files
                 let mut mark_config = config.clone();
                 mark_config.action = Action::Ignore;
+                mark_config.dry_run |= config.watch_observes();
 
                 // Process the files/patterns
-                process_files_and_patterns(&mark_config, &current_dir)?;
+                summary = process_files_and_patterns(&mark_config, &current_dir)?;
 
                 if !config.quiet {
                     println!();
@@ -124,29 +377,85 @@ pub fn run(config: Config) -> Result<()> {
             if config.daemon_mode {
                 // Running as daemon - start the watcher
                 let runtime = tokio::runtime::Runtime::new()?;
-                let watch_config = core::watch::WatchConfig::new(repo_path.clone());
+                let mut watch_config = core::watch::WatchConfig::new(repo_path.clone());
+                watch_config.on_change = config.on_change.clone();
+                watch_config.recurse_submodules = config.recurse_submodules;
+                watch_config.rate_limit = config.rate_limit;
+                watch_config.max_depth = config.max_depth;
+                watch_config.close_write = config.close_write;
+                watch_config.defer_during_sync = config.defer_during_sync;
+                watch_config.pattern_source = config.pattern_source.clone();
+                watch_config.rescan_interval = config.rescan_interval;
+                watch_config.max_runtime = config.max_runtime;
+                watch_config.metrics_file = config.metrics_file.clone();
+                watch_config.notify_on_error = config.notify_on_error;
+                watch_config.observe = config.watch_observes();
+                watch_config.combined = config.combined;
+                watch_config.respect_gitignore = config.respect_gitignore;
+                watch_config.marker_value = config.marker_value().to_vec();
+                watch_config.parallel_walk_threshold = config.parallel_walk_threshold();
+                watch_config.log_file = Some(core::daemon::log_file_path(&repo_path));
+                if let Some(size) = config.log_max_size {
+                    watch_config.log_max_size = size;
+                }
+                if let Some(debounce) = config.debounce {
+                    // Shorter windows would turn a burst of writes into a rescan loop
+                    watch_config.debounce_duration = debounce.max(core::watch::MIN_DEBOUNCE);
+                }
+                if let Some(refresh) = config.pattern_refresh {
+                    watch_config.pattern_refresh = refresh;
+                }
+
+                // A pause left behind by an earlier daemon doesn't carry over
+                core::daemon::set_paused(&repo_path, false)?;
 
                 // Save daemon status
                 let status = core::daemon::DaemonStatus {
                     pid: std::process::id(),
                     repo_path: repo_path.clone(),
                     started_at: chrono::Utc::now(),
+                    last_heartbeat: Some(chrono::Utc::now()),
+                    pidfile: config.pidfile.clone(),
+                    log_file: watch_config.log_file.clone(),
                 };
                 status.write(&repo_path)?;
+                println!(
+                    "[{}] Daemon watcher started (PID: {})",
+                    status.started_at.to_rfc3339(),
+                    status.pid
+                );
+                if let Some(ref pidfile) = config.pidfile {
+                    core::daemon::write_pidfile(pidfile, status.pid)?;
+                }
 
                 // Run the watcher
                 let result = runtime.block_on(core::watch::watch_repository(watch_config));
 
+                // Log the final line before the status file goes, since anything
+                // waiting on it takes its removal to mean the daemon is done
+                println!(
+                    "[{}] Daemon watcher stopped (PID: {})",
+                    chrono::Utc::now().to_rfc3339(),
+                    status.pid
+                );
+                let _ = std::io::Write::flush(&mut std::io::stdout());
+
                 // Clean up status file on exit
                 let _ = core::daemon::DaemonStatus::remove(&repo_path);
+                let _ = core::daemon::DaemonRegistry::unregister(status.pid);
+                if let Some(ref pidfile) = config.pidfile {
+                    let _ = core::daemon::remove_pidfile(pidfile, status.pid);
+                }
 
-                return result;
+                return result.map(|()| summary);
             }
             // Spawn daemon in background
-            let pid = core::daemon::spawn_daemon(&repo_path)?;
-            println!("{} Started daemon watcher (PID: {})", "✓".green(), pid);
-            println!("Run 'dbx-ignore --unwatch' to stop the daemon");
-            return Ok(());
+            let pid = core::daemon::spawn_daemon(&repo_path, &daemon_args(&config))?;
+            if !config.silent {
+                println!("{} Started daemon watcher (PID: {})", "✓".green(), pid);
+                println!("Run 'dbx-ignore --unwatch' to stop the daemon");
+            }
+            return Ok(summary);
         }
         Action::Unwatch => {
             let repo_path = current_dir.clone();
@@ -154,18 +463,35 @@ pub fn run(config: Config) -> Result<()> {
             if let Some(status) = core::daemon::DaemonStatus::read(&repo_path)? {
                 core::daemon::stop_daemon(status.pid)?;
                 core::daemon::DaemonStatus::remove(&repo_path)?;
-                println!(
-                    "{} Stopped daemon watcher (PID: {})",
-                    "✓".green(),
-                    status.pid
-                );
-            } else {
+                core::daemon::set_paused(&repo_path, false)?;
+                if let Some(ref pidfile) = status.pidfile {
+                    core::daemon::remove_pidfile(pidfile, status.pid)?;
+                }
+                if !config.silent {
+                    println!(
+                        "{} Stopped daemon watcher (PID: {})",
+                        "✓".green(),
+                        status.pid
+                    );
+                }
+            } else if !config.silent {
                 println!(
                     "{} No active daemon found for this repository",
                     "⚠".yellow()
                 );
             }
-            return Ok(());
+            return Ok(RunSummary::default());
+        }
+        Action::List if config.silent => return Ok(RunSummary::default()),
+        Action::List => {
+            core::marker_query::list_marked(
+                &current_dir,
+                config.recurse_submodules,
+                config.max_depth,
+                config.verbose,
+                config.null,
+            )?;
+            return Ok(RunSummary::default());
         }
         _ => {} // Continue with normal processing
     }
@@ -173,22 +499,337 @@ pub fn run(config: Config) -> Result<()> {
     process_files_and_patterns(&config, &current_dir)
 }
 
-fn process_files_and_patterns(config: &Config, current_dir: &Path) -> Result<()> {
-    let mut files_to_process = if config.git_mode && config.files.is_empty() {
-        utils::git_utils::get_git_ignored_files()?
-    } else {
-        get_files_from_paths(&config.files)?
+/// Build the watch options forwarded to a spawned daemon process
+fn daemon_args(config: &Config) -> Vec<String> {
+    let mut args = Vec::new();
+    if config.recurse_submodules {
+        args.push("--recurse-submodules".to_string());
+    }
+    if let Some(rate) = config.rate_limit {
+        args.push("--rate-limit".to_string());
+        args.push(rate.to_string());
+    }
+    if config.nice {
+        args.push("--nice".to_string());
+    }
+    if let Some(threshold) = config.parallel_walk_threshold {
+        args.push("--parallel-walk-threshold".to_string());
+        args.push(threshold.to_string());
+    }
+    if config.close_write {
+        args.push("--close-write".to_string());
+    }
+    if config.defer_during_sync {
+        args.push("--defer-during-sync".to_string());
+    }
+    if let Some(ref pidfile) = config.pidfile {
+        args.push("--pidfile".to_string());
+        args.push(pidfile.display().to_string());
+    }
+    if let Some(depth) = config.max_depth {
+        args.push("--max-depth".to_string());
+        args.push(depth.to_string());
+    }
+    if let Some(ref source) = config.pattern_source {
+        args.extend(source.to_args());
+    }
+    if let Some(refresh) = config.pattern_refresh {
+        args.push("--patterns-refresh".to_string());
+        args.push(refresh.as_secs().to_string());
+    }
+    if let Some(interval) = config.rescan_interval {
+        args.push("--rescan-interval".to_string());
+        args.push(interval.as_secs().to_string());
+    }
+    if let Some(runtime) = config.max_runtime {
+        args.push("--max-runtime".to_string());
+        args.push(runtime.as_secs().to_string());
+    }
+    if let Some(ref command) = config.on_change {
+        args.push("--on-change".to_string());
+        args.push(command.clone());
+    }
+    if let Some(ref metrics_file) = config.metrics_file {
+        args.push("--metrics-file".to_string());
+        args.push(metrics_file.display().to_string());
+    }
+    if let Some(debounce) = config.debounce {
+        args.push("--debounce".to_string());
+        args.push(debounce.as_millis().to_string());
+    }
+    if let Some(size) = config.log_max_size {
+        args.push("--log-max-size".to_string());
+        args.push(size.to_string());
+    }
+    if config.notify_on_error {
+        args.push("--notify-on-error".to_string());
+    }
+    if config.watch_observes() {
+        args.push("--observe".to_string());
+    }
+    if config.combined {
+        args.push("--mode".to_string());
+        args.push("combined".to_string());
+    }
+    if config.respect_gitignore {
+        args.push("--respect-gitignore".to_string());
+    }
+    args
+}
+
+/// Apply the inverse of the most recent recorded operation to the files it changed
+///
+/// Files deleted since are skipped, not reported as errors. The undo is recorded like
+/// any other run, so undoing again redoes the operation.
+fn undo_last_operation(mut config: Config, current_dir: &Path) -> Result<RunSummary> {
+    let Some(record) = core::history::OperationRecord::last_undoable(current_dir)? else {
+        if !config.quiet {
+            println!("{}", "Nothing to undo: no operation recorded yet.".yellow());
+        }
+        return Ok(RunSummary::default());
     };
 
+    let (files, deleted): (Vec<PathBuf>, Vec<PathBuf>) = record
+        .files
+        .into_iter()
+        .partition(|path| current_dir.join(path).symlink_metadata().is_ok());
+    if !config.quiet {
+        println!(
+            "{} Undoing {} from {}: {} path(s)",
+            "↶".blue(),
+            record.action,
+            record
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            files.len()
+        );
+        if !deleted.is_empty() {
+            println!("   Skipping {} path(s) deleted since then", deleted.len());
+        }
+    }
+    if files.is_empty() {
+        return Ok(RunSummary::default());
+    }
+
+    config.action = match record.action {
+        Action::Ignore => Action::Reset,
+        _ => Action::Ignore,
+    };
+    config.files = files;
+    config.literal_paths = true;
+    config.git_mode = false;
+    config.patterns.clear();
+    config.presets.clear();
+
+    // The recorded files are exactly the ones to revert, so no selection option applies
+    config.skip_worktree = false;
+    config.git_status = false;
+    config.synced_only = false;
+    config.dockerignore = None;
+    config.older_than = None;
+    config.since = None;
+    config.min_size = None;
+    config.where_predicate = None;
+    config.where_xattr = None;
+    config.cwd_scope = false;
+    config.max_depth = None;
+    config.pre_scan_hook = None;
+    config.tag = None;
+    config.exclude.clear();
+    config.reset_excluded = false;
+    process_files_and_patterns(&config, current_dir)
+}
+
+/// Check a `--repo` directory and return it in canonical form
+fn resolve_repo_dir(repo: &Path) -> Result<PathBuf> {
+    if !repo.is_dir() {
+        anyhow::bail!("--repo path is not a directory: {}", repo.display());
+    }
+    utils::git_utils::repo_root(repo).with_context(|| {
+        format!(
+            "--repo path is not inside a git repository: {}",
+            repo.display()
+        )
+    })?;
+    repo.canonicalize()
+        .with_context(|| format!("Failed to resolve --repo path {}", repo.display()))
+}
+
+/// Directories above `paths` (below `current_dir`) that an earlier run marked as a unit
+///
+/// Only directories recorded in the tracked state and still carrying a marker count, so
+/// markers placed by hand or by other tools are left alone. Directories are returned as
+/// recorded, so resetting them also drops them from the tracked state.
+fn marked_tracked_ancestors(
+    paths: &[PathBuf],
+    tracked: &core::tracked_files::TrackedFiles,
+    current_dir: &Path,
+) -> BTreeSet<PathBuf> {
+    let tracked_dirs: HashMap<PathBuf, &PathBuf> = tracked
+        .marked_files
+        .iter()
+        .map(|path| (current_dir.join(path), path))
+        .collect();
+
+    let mut dirs = BTreeSet::new();
+    for path in paths {
+        let path = current_dir.join(path);
+        for ancestor in path.ancestors().skip(1) {
+            if ancestor == current_dir || !ancestor.starts_with(current_dir) {
+                break;
+            }
+            if let Some(recorded) = tracked_dirs.get(ancestor)
+                && utils::platform_utils::has_any_ignore_attribute(ancestor)
+            {
+                dirs.insert((*recorded).clone());
+            }
+        }
+    }
+    dirs
+}
+
+/// Run the `--pre-scan-hook`, if any, returning the paths it excludes
+///
+/// A failing hook aborts the run, unless `--keep-going` is set, in which case
+/// nothing is excluded.
+fn run_pre_scan_hook(config: &Config, current_dir: &Path) -> Result<Vec<PathBuf>> {
+    let Some(ref command) = config.pre_scan_hook else {
+        return Ok(Vec::new());
+    };
+
+    match core::hooks::PreScanHook::new(command.clone(), current_dir.to_path_buf()).run() {
+        Ok(excluded) => {
+            if config.verbose {
+                println!(
+                    "{} Pre-scan hook excluded {} paths",
+                    "✓".green(),
+                    excluded.len()
+                );
+            }
+            Ok(excluded)
+        }
+        Err(e) if config.keep_going => {
+            if !config.quiet {
+                eprintln!(
+                    "{} Warning: {:#} (continuing without exclusions)",
+                    "⚠".yellow(),
+                    e
+                );
+            }
+            Ok(Vec::new())
+        }
+        Err(e) => Err(e.context("Pre-scan hook failed (use --keep-going to continue anyway)")),
+    }
+}
+
+/// Warn when the working directory sits inside a tree that is already ignored
+fn warn_if_inside_ignored_tree(current_dir: &Path) {
+    if let Some(ignored) = utils::platform_utils::find_ignored_ancestor(current_dir) {
+        println!(
+            "{} {} is already marked as ignored; operations inside it may have no effect or conflict with its marker",
+            "⚠".yellow(),
+            ignored.display()
+        );
+        println!(
+            "  Run 'dbx-ignore --reset {}' first to operate on its contents individually",
+            ignored.display()
+        );
+    }
+}
+
+/// Warn when the working directory isn't inside any detected Dropbox folder
+fn warn_if_outside_dropbox(current_dir: &Path) {
+    if utils::platform_utils::dropbox_root_of(current_dir).is_none() {
+        println!(
+            "{} {} is not inside a detected Dropbox folder; Dropbox won't see the markers set here",
+            "⚠".yellow(),
+            current_dir.display()
+        );
+        println!("  Run 'dbx-ignore --doctor' to see the Dropbox folders found on this system");
+    }
+}
+
+fn process_files_and_patterns(config: &Config, current_dir: &Path) -> Result<RunSummary> {
+    let use_scan_cache = scan_cache_applies(config);
+    if use_scan_cache
+        && !config.force
+        && core::scan_cache::ScanCache::load(current_dir)
+            .is_some_and(|cache| cache.is_current(current_dir, config.marker_value()))
+    {
+        if !config.quiet {
+            println!(
+                "{} Nothing to do: no ignore file or tracked state changed since the last run (use --force to re-scan)",
+                "✓".green()
+            );
+        }
+        return Ok(RunSummary::default());
+    }
+
+    let hook_exclusions = run_pre_scan_hook(config, current_dir)?;
+
+    // Keep only paths last modified before the --older-than cutoff
+    let age_cutoff = config.older_than.map(|age| {
+        SystemTime::now()
+            .checked_sub(age)
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    });
+    let since_cutoff = config.since.map(|age| {
+        SystemTime::now()
+            .checked_sub(age)
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    });
+    let selection = build_selector(
+        config,
+        current_dir,
+        age_cutoff,
+        since_cutoff,
+        hook_exclusions,
+    )?
+    .select()?;
+    let unsynced_skipped = selection.dropped_by(SYNCED_ONLY_FILTER);
+    let not_recent_skipped = selection.dropped_by("modified-since");
+    let small_skipped = selection.dropped_by("min-size");
+    let excluded = selection.excluded;
+    let mut files_to_process = selection.files;
+
     // Always add .dbx-ignore folder to be marked as ignored if it exists
     let dbx_ignore_folder = current_dir.join(".dbx-ignore");
     if dbx_ignore_folder.exists() && dbx_ignore_folder.is_dir() {
         // Only add if not already in the list
         if !files_to_process.iter().any(|f| f == &dbx_ignore_folder) {
-            files_to_process.push(dbx_ignore_folder);
+            files_to_process.push(dbx_ignore_folder.clone());
         }
     }
 
+    // A directory marked as a unit keeps everything in it ignored, so resetting a path
+    // inside it resets the directory too
+    let marked_dirs = if config.action == Action::Reset {
+        let tracked = core::tracked_files::TrackedFiles::load(current_dir)?;
+        let dirs = marked_tracked_ancestors(&files_to_process, &tracked, current_dir);
+        for dir in &dirs {
+            if !files_to_process.contains(dir) {
+                files_to_process.push(dir.clone());
+            }
+        }
+        dirs
+    } else {
+        BTreeSet::new()
+    };
+
+    // Symlinks are skipped unless --follow-symlinks swaps them for their targets
+    let (files_to_process, symlinks_skipped) =
+        utils::path_utils::apply_symlink_policy(files_to_process, config.follow_symlinks);
+
+    // Never mark the running binary or its directory when they sit inside the tree
+    let own_binary = utils::path_utils::own_binary_paths(current_dir);
+    let (own_binary_skipped, files_to_process): (Vec<PathBuf>, Vec<PathBuf>) = files_to_process
+        .into_iter()
+        .partition(|path| utils::path_utils::is_own_binary_path(path, &own_binary));
+
+    // Hard links share their attributes, so each linked file is processed once
+    let (files_to_process, hard_links) = utils::path_utils::split_hard_links(files_to_process);
+
     if !config.quiet {
         if config.dry_run {
             println!("{}", "🔍 Dry run mode - no changes will be made".yellow());
@@ -205,9 +846,46 @@ fn process_files_and_patterns(config: &Config, current_dir: &Path) -> Result<()>
             Action::Reset => "Removing ignore markers from",
             Action::Watch => "Setting up monitoring for",
             Action::Unwatch => "Stopping monitoring for",
+            Action::List => "Listing markers on",
         };
 
-        if config.git_mode && config.files.is_empty() {
+        if config.skip_worktree {
+            println!(
+                "{} Mode: {} skip-worktree/assume-unchanged files",
+                "✓".green(),
+                action_description.green()
+            );
+        } else if config.git_status {
+            println!(
+                "{} Mode: {} untracked and ignored files",
+                "✓".green(),
+                action_description.green()
+            );
+        } else if let Some(ref dockerignore) = config.dockerignore {
+            println!(
+                "{} Mode: {} files excluded by {}",
+                "✓".green(),
+                action_description.green(),
+                dockerignore.display()
+            );
+        } else if let Some(ref tag) = config.tag
+            && config.action == Action::Reset
+            && config.files.is_empty()
+        {
+            println!(
+                "{} Mode: {} files tagged '{}'",
+                "✓".green(),
+                action_description.green(),
+                tag
+            );
+        } else if !config.presets.is_empty() {
+            println!(
+                "{} Mode: {} files matched by preset(s) {}",
+                "✓".green(),
+                action_description.green(),
+                config.presets.join(", ")
+            );
+        } else if config.git_mode && config.files.is_empty() {
             println!(
                 "{} Mode: {} git-ignored files",
                 "✓".green(),
@@ -220,18 +898,149 @@ fn process_files_and_patterns(config: &Config, current_dir: &Path) -> Result<()>
                 action_description.green()
             );
         }
+
+        if config.synced_only {
+            if !cfg!(target_os = "macos") {
+                println!(
+                    "{} Sync detection is only available on macOS, treating all files as synced",
+                    "⚠".yellow()
+                );
+            }
+            println!(
+                "{} Synced only: skipped {} candidate(s) Dropbox isn't syncing",
+                "✓".green(),
+                unsynced_skipped
+            );
+        }
+
+        if !config.exclude.is_empty() {
+            println!(
+                "{} Excluded: {} path(s) matching --exclude",
+                "✓".green(),
+                excluded.len()
+            );
+        }
+
+        if !marked_dirs.is_empty() {
+            println!(
+                "{} Marked directories: {} directory(ies) holding reset paths are reset as a whole",
+                "✓".green(),
+                marked_dirs.len()
+            );
+            if config.verbose {
+                for dir in &marked_dirs {
+                    println!("   {} {}", "-".yellow(), dir.display());
+                }
+            }
+        }
+
+        if !symlinks_skipped.is_empty() {
+            println!(
+                "{} Symlinks: skipped {} symlink(s) (use --follow-symlinks to process their targets)",
+                "✓".green(),
+                symlinks_skipped.len()
+            );
+            if config.verbose {
+                for path in &symlinks_skipped {
+                    println!("   {} {}", "-".yellow(), path.display());
+                }
+            }
+        }
+
+        if config.verbose && !own_binary_skipped.is_empty() {
+            println!(
+                "{} Skipped {} path(s) holding the running dbx-ignore binary:",
+                "✓".green(),
+                own_binary_skipped.len()
+            );
+            for path in &own_binary_skipped {
+                println!("   {} {}", "-".yellow(), path.display());
+            }
+        }
+
+        if !hard_links.is_empty() {
+            println!(
+                "{} Hard links: {} path(s) share a file with another selected path, handled once",
+                "✓".green(),
+                hard_links.len()
+            );
+            if config.verbose {
+                for (link, first) in &hard_links {
+                    println!(
+                        "   {} {}: hard link to {}, handled with it",
+                        "-".yellow(),
+                        link.display(),
+                        first.display()
+                    );
+                }
+            }
+        }
+
+        if let Some(cutoff) = since_cutoff {
+            println!(
+                "{} Since filter: skipped {} path(s) not modified since {}",
+                "✓".green(),
+                not_recent_skipped,
+                chrono::DateTime::<chrono::Local>::from(cutoff).format("%Y-%m-%d %H:%M")
+            );
+        }
+
+        if let Some(min_size) = config.min_size {
+            println!(
+                "{} Size filter: skipped {} path(s) smaller than {}",
+                "✓".green(),
+                small_skipped,
+                utils::parse_utils::format_size(min_size)
+            );
+        }
+
+        if let Some(cutoff) = age_cutoff {
+            let total_size: u64 = files_to_process
+                .iter()
+                .filter_map(|path| std::fs::metadata(path).ok())
+                .map(|metadata| metadata.len())
+                .sum();
+            println!(
+                "{} Age filter: {} files last modified before {} ({})",
+                "✓".green(),
+                files_to_process.len(),
+                chrono::DateTime::<chrono::Local>::from(cutoff).format("%Y-%m-%d %H:%M"),
+                utils::parse_utils::format_size(total_size)
+            );
+        }
     }
 
+    // Explain which rule selected each file in verbose output
+    let reasons = if config.verbose && config.action == Action::Ignore {
+        selection_reasons(config, current_dir, &files_to_process)
+    } else {
+        HashMap::new()
+    };
+
     let total_files = files_to_process.len();
     let processed_count = Arc::new(AtomicUsize::new(0));
     let operation_count = Arc::new(AtomicUsize::new(0));
+    let skipped_count = Arc::new(AtomicUsize::new(0));
+    let errors = Arc::new(std::sync::Mutex::new(Vec::new()));
 
     // Track files that are being marked/unmarked
     let mut tracked = core::tracked_files::TrackedFiles::load(current_dir)?;
     let files_to_add = Arc::new(std::sync::Mutex::new(Vec::new()));
     let files_to_remove = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let changed_files = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    // Shared by all workers so the limit applies to the run as a whole
+    let rate_limiter = config.rate_limit.map(utils::rate_limit::RateLimiter::new);
 
-    let progress = if !config.quiet && !config.verbose {
+    let send_progress = |event: ProgressEvent| {
+        if let Some(ref sink) = config.progress_sink {
+            let _ = sink.send(event);
+        }
+    };
+    send_progress(ProgressEvent::Started { total: total_files });
+
+    // A progress sink replaces the bar
+    let progress = if !config.quiet && !config.verbose && config.progress_sink.is_none() {
         let pb = ProgressBar::new(total_files as u64);
         pb.set_style(
             ProgressStyle::default_bar()
@@ -246,19 +1055,30 @@ fn process_files_and_patterns(config: &Config, current_dir: &Path) -> Result<()>
         None
     };
 
-    // Process files in parallel
-    files_to_process.par_iter().for_each(|path| {
+    let process_one = |path: &PathBuf| {
         if let Some(ref pb) = progress {
             pb.set_message(format!("Processing: {}", path.display()));
         }
 
-        match process_path(path, config) {
+        match process_path_isolated(path, config, rate_limiter.as_ref()) {
             Ok(operations_performed) => {
+                send_progress(ProgressEvent::FileDone {
+                    path: path.clone(),
+                    ops: operations_performed,
+                });
                 processed_count.fetch_add(1, Ordering::Relaxed);
                 operation_count.fetch_add(operations_performed, Ordering::Relaxed);
 
-                // Collect files to update after parallel processing
-                if operations_performed > 0 && !config.dry_run {
+                if operations_performed > 0 {
+                    changed_files.lock().unwrap().push(path.clone());
+                } else {
+                    skipped_count.fetch_add(1, Ordering::Relaxed);
+                }
+
+                // Collect files to update after parallel processing; with a tag, files
+                // that were already marked are tracked too so they pick up the tag
+                let retag = config.tag.is_some() && *path != dbx_ignore_folder;
+                if (operations_performed > 0 || retag) && !config.dry_run {
                     match config.action {
                         Action::Ignore => files_to_add.lock().unwrap().push(path.clone()),
                         Action::Reset => files_to_remove.lock().unwrap().push(path.clone()),
@@ -266,22 +1086,43 @@ fn process_files_and_patterns(config: &Config, current_dir: &Path) -> Result<()>
                     }
                 }
 
-                if config.verbose {
+                if let Some(template) = config.format.as_ref().filter(|_| !config.silent) {
+                    println!(
+                        "{}",
+                        template.render(&FileOutcome {
+                            path,
+                            action: config.action,
+                            ops: operations_performed,
+                            error: None,
+                        })
+                    );
+                } else if config.verbose {
                     let item_type = if path.is_dir() { "directory" } else { "file" };
+                    let reason = reasons
+                        .get(path)
+                        .map(|reason| {
+                            format!(
+                                " {}",
+                                format!("— {}", reason.describe(current_dir)).dimmed()
+                            )
+                        })
+                        .unwrap_or_default();
                     if operations_performed > 0 {
                         let operation_msg = match config.action {
                             Action::Ignore => "ignore markers added",
                             Action::Reset => "ignore markers removed",
                             Action::Watch => "monitoring set up",
                             Action::Unwatch => "monitoring stopped",
+                            Action::List => "markers found",
                         };
                         println!(
-                            "   {} {} {}: {} {}",
+                            "   {} {} {}: {} {}{}",
                             "✓".green(),
                             item_type,
                             path.display(),
                             operations_performed,
-                            operation_msg
+                            operation_msg,
+                            reason
                         );
                     } else {
                         let status_msg = match config.action {
@@ -289,30 +1130,58 @@ fn process_files_and_patterns(config: &Config, current_dir: &Path) -> Result<()>
                             Action::Reset => "no markers to remove",
                             Action::Watch => "already monitored",
                             Action::Unwatch => "not monitored",
+                            Action::List => "not marked",
                         };
                         println!(
-                            "   {} {} {}: {}",
+                            "   {} {} {}: {}{}",
                             "-".yellow(),
                             item_type,
                             path.display(),
-                            status_msg
+                            status_msg,
+                            reason
                         );
                     }
                 }
             }
             Err(e) => {
-                if config.verbose {
+                if let Some(template) = config.format.as_ref().filter(|_| !config.silent) {
+                    println!(
+                        "{}",
+                        template.render(&FileOutcome {
+                            path,
+                            action: config.action,
+                            ops: 0,
+                            error: Some(&e.to_string()),
+                        })
+                    );
+                } else if config.verbose {
                     println!("   {} {}: {}", "✘".red(), path.display(), e);
                 } else if !config.quiet {
                     eprintln!("   {} Warning: {}: {}", "⚠".yellow(), path.display(), e);
                 }
+                send_progress(ProgressEvent::FileFailed {
+                    path: path.clone(),
+                    error: e.to_string(),
+                });
+                errors.lock().unwrap().push((path.clone(), e.to_string()));
             }
         }
 
         if let Some(ref pb) = progress {
             pb.inc(1);
         }
-    });
+    };
+
+    // Process files in parallel, on a dedicated pool when the thread count is set
+    match config.jobs {
+        Some(1) => files_to_process.iter().for_each(process_one),
+        Some(jobs) => rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build()
+            .context("Failed to start worker threads")?
+            .install(|| files_to_process.par_iter().for_each(process_one)),
+        None => files_to_process.par_iter().for_each(process_one),
+    }
 
     if let Some(ref pb) = progress {
         pb.finish_with_message("Complete!");
@@ -320,38 +1189,163 @@ fn process_files_and_patterns(config: &Config, current_dir: &Path) -> Result<()>
 
     let final_processed = processed_count.load(Ordering::Relaxed);
     let final_operations = operation_count.load(Ordering::Relaxed);
+    send_progress(ProgressEvent::Finished {
+        processed: final_processed,
+        ops: final_operations,
+    });
+
+    // Excluded paths lose markers left by earlier runs, and so do the directories above
+    // them (up to the working directory), since a marker there would still cover them
+    if config.reset_excluded && config.action == Action::Ignore {
+        let mut candidates = BTreeSet::new();
+        for path in &excluded {
+            let path = current_dir.join(path);
+            for ancestor in path.ancestors().skip(1) {
+                if ancestor == current_dir || !ancestor.starts_with(current_dir) {
+                    break;
+                }
+                candidates.insert(ancestor.to_path_buf());
+            }
+            candidates.insert(path);
+        }
+        let marked: Vec<&PathBuf> = candidates
+            .iter()
+            .filter(|path| utils::platform_utils::has_any_ignore_attribute(path))
+            .collect();
+        let mut reset_count = 0;
+        for path in marked {
+            if config.dry_run {
+                reset_count += 1;
+                continue;
+            }
+            match utils::platform_utils::remove_ignore_attributes(path) {
+                Ok(_) => {
+                    reset_count += 1;
+                    files_to_remove.lock().unwrap().push(path.clone());
+                }
+                Err(e) if !config.quiet => {
+                    eprintln!("   {} Warning: {}: {}", "⚠".yellow(), path.display(), e);
+                }
+                Err(_) => {}
+            }
+        }
+        if !config.quiet && reset_count > 0 {
+            println!(
+                "{} Reset excluded: {} {} marked path(s)",
+                "✓".green(),
+                if config.dry_run {
+                    "would remove markers from"
+                } else {
+                    "removed markers from"
+                },
+                reset_count
+            );
+        }
+    }
 
     // Apply collected changes and save tracked files state
+    let mut delta = None;
+    let mut pattern_changes = None;
     if !config.dry_run && (config.action == Action::Ignore || config.action == Action::Reset) {
-        // Apply file changes collected during parallel processing
-        let files_to_add = files_to_add.lock().unwrap();
+        // The state left by the previous run is the baseline for the delta
+        let previous = tracked.marked_files.clone();
+
+        // Apply file changes collected during parallel processing; skipped hard links
+        // changed along with the path they share a file with
+        let mut files_to_add = files_to_add.lock().unwrap();
+        let mut files_to_remove = files_to_remove.lock().unwrap();
+        for (link, first) in &hard_links {
+            if files_to_add.contains(first) {
+                files_to_add.push(link.clone());
+            }
+            if files_to_remove.contains(first) {
+                files_to_remove.push(link.clone());
+            }
+        }
+
         if !files_to_add.is_empty() {
             tracked.add_files(&files_to_add);
+            if let Some(ref tag) = config.tag {
+                let tagged: Vec<PathBuf> = files_to_add
+                    .iter()
+                    .filter(|path| **path != dbx_ignore_folder)
+                    .cloned()
+                    .collect();
+                tracked.tag_files(&tagged, tag);
+            }
         }
 
-        let files_to_remove = files_to_remove.lock().unwrap();
         if !files_to_remove.is_empty() {
             tracked.remove_files(&files_to_remove);
         }
 
-        // Store patterns if we're ignoring files
-        if config.action == Action::Ignore && !config.patterns.is_empty() {
-            tracked.add_patterns(&config.patterns);
-        } else if config.action == Action::Reset && !config.patterns.is_empty() {
-            tracked.remove_patterns(&config.patterns);
+        // Store patterns if we're ignoring files; presets are stored as the patterns they stand for
+        let mut patterns = config.patterns.clone();
+        patterns.extend(core::presets::preset_patterns(&config.presets));
+        // Order matters to negated patterns, so repeats are dropped in place
+        let mut seen = HashSet::new();
+        patterns.retain(|pattern| seen.insert(pattern.clone()));
+        if config.action == Action::Ignore && !patterns.is_empty() {
+            let added = tracked.add_patterns(&patterns);
+            pattern_changes = Some((added.len(), patterns.len() - added.len()));
+        } else if config.action == Action::Reset && !patterns.is_empty() {
+            let removed = tracked.remove_patterns(&patterns);
+            pattern_changes = Some((removed.len(), patterns.len() - removed.len()));
         }
 
         tracked.save(current_dir)?;
+        delta = Some(tracked.delta_since(&previous));
 
-        // Ensure .dbx-ignore/ is in .gitignore when in a git repo
-        if let Err(e) = utils::gitignore_manager::ensure_dbx_ignore_in_gitignore(current_dir) {
-            if config.verbose {
-                eprintln!(
-                    "   {} Warning: Could not update .gitignore: {}",
-                    "⚠".yellow(),
-                    e
-                );
-            }
+        // Ensure .dbx-ignore/ is in .gitignore when in a git repo (and state is kept there)
+        if !core::state_location::uses_global_state()
+            && let Err(e) = utils::gitignore_manager::ensure_dbx_ignore_in_gitignore(current_dir)
+            && config.verbose
+        {
+            eprintln!(
+                "   {} Warning: Could not update .gitignore: {}",
+                "⚠".yellow(),
+                e
+            );
+        }
+    }
+
+    // Preflight: make sure the real run would be able to persist its state
+    if config.dry_run
+        && !config.quiet
+        && (config.action == Action::Ignore || config.action == Action::Reset)
+        && let Err(e) =
+            utils::json_utils::check_dir_writable(&core::state_location::state_dir(current_dir))
+    {
+        eprintln!(
+            "{} Warning: State directory is not writable, a real run would fail to save tracked files: {:#}",
+            "⚠".yellow(),
+            e
+        );
+    }
+
+    // Record the operation in the history log (dry runs never create the state directory).
+    // An undo that changed nothing is left out, so the next `--undo` still sees the
+    // operation it would revert.
+    let changed_files = std::mem::take(&mut *changed_files.lock().unwrap());
+    if (config.action == Action::Ignore || config.action == Action::Reset)
+        && (!config.dry_run || core::state_location::state_dir(current_dir).is_dir())
+        && !(config.undo && changed_files.is_empty())
+    {
+        let record = core::history::OperationRecord::new(
+            config.action,
+            config.dry_run,
+            final_processed,
+            final_operations,
+            changed_files,
+        );
+        if let Err(e) = record.save(current_dir)
+            && config.verbose
+        {
+            eprintln!(
+                "   {} Warning: Could not record operation history: {}",
+                "⚠".yellow(),
+                e
+            );
         }
     }
 
@@ -362,6 +1356,7 @@ fn process_files_and_patterns(config: &Config, current_dir: &Path) -> Result<()>
             Action::Reset => "ignore markers removed",
             Action::Watch => "items set up for monitoring",
             Action::Unwatch => "monitoring stopped",
+            Action::List => "markers found",
         };
 
         if config.dry_run {
@@ -381,135 +1376,365 @@ fn process_files_and_patterns(config: &Config, current_dir: &Path) -> Result<()>
                 operation_description
             );
         }
-    }
-
-    Ok(())
-}
 
-/// Check if a path string contains glob pattern characters
-pub fn is_glob_pattern(path_str: &str) -> bool {
-    path_str.contains('*') || path_str.contains('?') || path_str.contains('[')
-}
+        if let Some(delta) = delta {
+            println!("{} Marked set: {}", "Δ".blue(), delta);
+        }
+        if let Some((changed, unchanged)) = pattern_changes {
+            let (changed_label, unchanged_label) = if config.action == Action::Reset {
+                ("removed", "not tracked")
+            } else {
+                ("added", "already tracked")
+            };
+            println!(
+                "{} Patterns: {} {}, {} {}",
+                "Δ".blue(),
+                changed,
+                changed_label,
+                unchanged,
+                unchanged_label
+            );
+        }
+    }
 
-/// Classification of path types for special handling
-enum PathType {
-    CurrentDirectory,
-    GitIgnoreFile,
-    Regular,
-}
+    let mut errors = std::mem::take(&mut *errors.lock().unwrap());
+    errors.sort();
 
-/// Classify a path for special handling
-fn classify_path(path: &Path) -> PathType {
-    // Check if it's the current directory
-    if path.to_str() == Some(".") || path.file_name().and_then(|n| n.to_str()) == Some(".") {
-        PathType::CurrentDirectory
+    // Captured last, after .gitignore may have gained the state directory entry
+    if use_scan_cache && errors.is_empty() {
+        let saved = core::scan_cache::ScanCache::capture(current_dir, config.marker_value())
+            .and_then(|cache| cache.save(current_dir));
+        if let Err(e) = saved
+            && config.verbose
+        {
+            eprintln!(
+                "   {} Warning: Could not update scan cache: {:#}",
+                "⚠".yellow(),
+                e
+            );
+        }
     }
-    // Check if it's a .gitignore file
-    else if path.file_name().and_then(|n| n.to_str()) == Some(".gitignore") {
-        PathType::GitIgnoreFile
-    } else {
-        PathType::Regular
+
+    if !errors.is_empty() {
+        if config.strict {
+            let listed: Vec<String> = errors
+                .iter()
+                .take(MAX_ERRORS_LISTED)
+                .map(|(path, error)| format!("  {}: {}", path.display(), error))
+                .collect();
+            let more = errors.len().saturating_sub(MAX_ERRORS_LISTED);
+            anyhow::bail!(
+                "{} of {} file(s) could not be processed:\n{}{}",
+                errors.len(),
+                total_files,
+                listed.join("\n"),
+                if more > 0 {
+                    format!("\n  ... and {} more", more)
+                } else {
+                    String::new()
+                }
+            );
+        } else if !config.quiet {
+            // Per-file warnings scroll by in a large run; repeat the count at the end
+            eprintln!(
+                "{} {} file(s) could not be processed (use --strict to fail the run)",
+                "⚠".yellow(),
+                errors.len()
+            );
+        }
     }
+
+    Ok(RunSummary {
+        files_processed: final_processed,
+        operations_performed: final_operations,
+        errors,
+        skipped: skipped_count.load(Ordering::Relaxed),
+    })
 }
 
-/// Check if a path is a hidden file (starts with .)
-fn is_hidden_file(path: &Path) -> bool {
-    path.file_name()
-        .and_then(|n| n.to_str())
-        .map(|name| name.starts_with('.'))
-        .unwrap_or(false)
+/// Whether the run is a plain `--git` marking run the scan cache can stand in for
+///
+/// Any option narrowing or widening the selection makes the cached outcome meaningless.
+fn scan_cache_applies(config: &Config) -> bool {
+    config.scan_cache
+        && config.action == Action::Ignore
+        && config.git_mode
+        && config.files.is_empty()
+        && !config.dry_run
+        && !config.skip_worktree
+        && !config.git_status
+        && !config.synced_only
+        && config.dockerignore.is_none()
+        && config.older_than.is_none()
+        && config.since.is_none()
+        && config.min_size.is_none()
+        && config.where_predicate.is_none()
+        && config.where_xattr.is_none()
+        && !config.recurse_submodules
+        && !config.cwd_scope
+        && !config.follow_symlinks
+        && !config.allow_outside
+        && config.max_depth.is_none()
+        && config.pre_scan_hook.is_none()
+        && config.tag.is_none()
+        && config.exclude.is_empty()
 }
 
-/// Process a glob pattern and add matching files to items
-/// Returns true if any matches were found
-fn process_glob_pattern(pattern: &str, items: &mut Vec<PathBuf>) -> Result<bool> {
-    let initial_count = items.len();
-
-    match glob::glob(pattern) {
-        Ok(mut glob_paths) => {
-            for entry in &mut glob_paths {
-                match entry {
-                    Ok(p) => {
-                        if p.exists() {
-                            items.push(p);
-                        }
-                    }
-                    Err(e) => {
-                        return Err(anyhow::anyhow!("Glob error: {}", e));
-                    }
-                }
-            }
-            Ok(items.len() > initial_count)
-        }
-        Err(e) => Err(anyhow::anyhow!("Invalid pattern '{}': {}", pattern, e)),
+/// Failed paths listed in a `--strict` error; the rest are only counted
+const MAX_ERRORS_LISTED: usize = 10;
+
+/// Name of the `--synced-only` filter, for its drop count
+const SYNCED_ONLY_FILTER: &str = "synced-only";
+
+/// Name of the `--reset --tag` filter, for its drop count
+const TAG_FILTER: &str = "tag";
+
+/// Turn the command-line selection options into a `FileSelector`
+fn build_selector(
+    config: &Config,
+    current_dir: &Path,
+    age_cutoff: Option<SystemTime>,
+    since_cutoff: Option<SystemTime>,
+    hook_exclusions: Vec<PathBuf>,
+) -> Result<FileSelector> {
+    let paths_source = |paths: Vec<PathBuf>| Source::Paths {
+        paths,
+        literal: config.literal_paths,
+        local_only: config.local_only,
+        confine_to: if config.allow_outside {
+            None
+        } else {
+            // Outside a repository, the working directory is the boundary
+            Some(
+                utils::git_utils::repo_root(current_dir)
+                    .unwrap_or_else(|_| current_dir.to_path_buf()),
+            )
+        },
+    };
+    let paths = paths_source(config.files.clone());
+
+    let selector =
+        FileSelector::new(current_dir).parallel_walk_threshold(config.parallel_walk_threshold());
+    let mut selector = if config.skip_worktree {
+        selector
+            .source(Source::SkipWorktree(current_dir.to_path_buf()))
+            .source(paths)
+    } else if config.git_status {
+        selector
+            .source(Source::GitStatus(current_dir.to_path_buf()))
+            .source(paths)
+    } else if let Some(ref dockerignore) = config.dockerignore {
+        selector
+            .source(Source::DockerIgnore {
+                file: dockerignore.clone(),
+                max_depth: config.max_depth,
+            })
+            .source(paths)
+    } else if let Some(ref tag) = config.tag
+        && config.action == Action::Reset
+        && config.files.is_empty()
+    {
+        // Reset everything marked with the tag that still exists
+        let tagged = core::tracked_files::TrackedFiles::load(current_dir)?
+            .files_tagged(tag)
+            .into_iter()
+            .filter(|path| current_dir.join(path).exists())
+            .collect();
+        selector.source(Source::Paths {
+            paths: tagged,
+            literal: true,
+            local_only: false,
+            confine_to: None,
+        })
+    } else if config.git_mode && config.files.is_empty() {
+        // Git mode covers the whole repository, wherever in it we're run from
+        let root = if config.cwd_scope {
+            current_dir.to_path_buf()
+        } else {
+            utils::git_utils::repo_root(current_dir)?
+        };
+        selector.source(Source::GitIgnored {
+            root,
+            recurse_submodules: config.recurse_submodules,
+            max_depth: config.max_depth,
+        })
+    } else if config.has_filters() && config.files.is_empty() {
+        // Filter selection without explicit paths considers every file under the root
+        selector.source(Source::Walk {
+            root: current_dir.to_path_buf(),
+            max_depth: config.max_depth,
+        })
+    } else if !config.presets.is_empty() {
+        // Preset patterns select files anywhere under the directory, next to any paths given
+        selector
+            .source(Source::Patterns {
+                root: current_dir.to_path_buf(),
+                patterns: core::presets::preset_patterns(&config.presets),
+                max_depth: config.max_depth,
+            })
+            .source(paths)
+    } else if config.action == Action::Reset && !config.patterns.is_empty() && !config.literal_paths
+    {
+        // Unmarking by pattern covers every match under the directory, tracked or not
+        let literal_files = config
+            .files
+            .iter()
+            .filter(|path| !path.to_str().is_some_and(is_glob_pattern))
+            .cloned()
+            .collect();
+        selector
+            .source(Source::Patterns {
+                root: current_dir.to_path_buf(),
+                patterns: config.patterns.clone(),
+                max_depth: config.max_depth,
+            })
+            .source(paths_source(literal_files))
+    } else {
+        selector.source(paths)
+    };
+
+    if let Some(cutoff) = age_cutoff {
+        selector = selector.filter(Filter::ModifiedBefore(cutoff));
+    }
+    if let Some(cutoff) = since_cutoff {
+        selector = selector.filter(Filter::ModifiedSince(cutoff));
+    }
+    if let Some(min_size) = config.min_size {
+        selector = selector.filter(Filter::MinSize(min_size));
+    }
+    if let Some(ref predicate) = config.where_predicate {
+        selector = selector.filter(Filter::Where(predicate.clone()));
     }
+    if let Some(ref condition) = config.where_xattr {
+        selector = selector.filter(Filter::Attribute(condition.clone()));
+    }
+    if config.synced_only {
+        selector = selector.filter(Filter::Custom {
+            name: SYNCED_ONLY_FILTER,
+            keep: Box::new(utils::platform_utils::is_synced),
+        });
+    }
+    if !hook_exclusions.is_empty() {
+        selector = selector.filter(Filter::Exclude(hook_exclusions));
+    }
+    if !config.exclude.is_empty() {
+        selector = selector.exclude(&config.exclude)?;
+    }
+    if let Some(ref tag) = config.tag
+        && config.action == Action::Reset
+    {
+        // Explicit paths are narrowed down to those carrying the tag
+        let base = current_dir.to_path_buf();
+        let tagged: HashSet<PathBuf> = core::tracked_files::TrackedFiles::load(current_dir)?
+            .files_tagged(tag)
+            .iter()
+            .map(|path| base.join(path))
+            .collect();
+        selector = selector.filter(Filter::Custom {
+            name: TAG_FILTER,
+            keep: Box::new(move |path| tagged.contains(&base.join(path))),
+        });
+    }
+
+    Ok(selector)
 }
 
-fn get_files_from_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
-    let mut items = Vec::new();
-    let mut regular_paths = Vec::new();
-    let mut empty_patterns = Vec::new();
+/// Work out which rule selected each path, for verbose marking output
+fn selection_reasons(
+    config: &Config,
+    current_dir: &Path,
+    paths: &[PathBuf],
+) -> HashMap<PathBuf, IgnoreReason> {
+    let state_dir = current_dir.join(utils::path_utils::STATE_DIR_NAME);
+    let patterns: Vec<(&String, globset::GlobMatcher)> = config
+        .patterns
+        .iter()
+        .filter_map(|pattern| Some((pattern, core::file_selector::compile_glob(pattern).ok()?)))
+        .collect();
+    let git_selection = config.git_mode
+        && config.files.is_empty()
+        && !config.skip_worktree
+        && !config.git_status
+        && config.dockerignore.is_none();
+    let filter_selection = config.has_filters()
+        && config.files.is_empty()
+        && !config.skip_worktree
+        && !config.git_status
+        && config.dockerignore.is_none();
+    let mut explainer = git_selection.then(|| GitignoreExplainer::new(current_dir));
 
-    // Process each path, categorizing as pattern or regular path
+    let mut reasons = HashMap::new();
     for path in paths {
-        let path_str = path.to_string_lossy();
-
-        if is_glob_pattern(&path_str) {
-            // Handle glob patterns
-            match process_glob_pattern(&path_str, &mut items) {
-                Ok(found_matches) => {
-                    if !found_matches {
-                        empty_patterns.push(path_str.to_string());
-                    }
-                }
-                Err(e) => return Err(e),
-            }
+        let reason = if *path == state_dir {
+            Some(IgnoreReason::StateDir)
+        } else if let Some(ref mut explainer) = explainer {
+            explainer.explain(path)
+        } else if filter_selection {
+            Some(IgnoreReason::Filter)
+        } else if let Some((pattern, _)) = patterns.iter().find(|(_, p)| p.is_match(path)) {
+            Some(IgnoreReason::Pattern((*pattern).clone()))
+        } else if config.skip_worktree && !config.files.contains(path) {
+            Some(IgnoreReason::SkipWorktree)
+        } else if config.git_status && !config.files.contains(path) {
+            Some(IgnoreReason::GitStatus)
+        } else if let Some(ref dockerignore) = config.dockerignore
+            && !config.files.contains(path)
+        {
+            Some(IgnoreReason::DockerIgnore(dockerignore.clone()))
         } else {
-            regular_paths.push(path.clone());
+            Some(IgnoreReason::Explicit)
+        };
+        if let Some(reason) = reason {
+            reasons.insert(path.clone(), reason);
         }
     }
+    reasons
+}
 
-    // Report error if any patterns matched nothing
-    if !empty_patterns.is_empty() {
-        return Err(anyhow::anyhow!(
-            "No files found matching patterns: {}",
-            empty_patterns.join(", ")
-        ));
+/// Run a marker change on `path`, keeping its times intact with `--preserve-times`
+fn apply_markers(
+    path: &Path,
+    config: &Config,
+    operation: impl FnOnce() -> Result<usize>,
+) -> Result<usize> {
+    if config.preserve_times {
+        utils::path_utils::with_preserved_times(path, operation)
+    } else {
+        operation()
     }
+}
 
-    // Process regular paths
-    for path in regular_paths {
-        if !path.exists() {
-            return Err(anyhow::anyhow!("Path not found: {}", path.display()));
+/// Run [`process_path`], turning a panic into an error for that path
+///
+/// A panic in platform or path handling then fails only its own file instead of
+/// tearing down the whole parallel batch.
+fn process_path_isolated(
+    path: &Path,
+    config: &Config,
+    rate_limiter: Option<&utils::rate_limit::RateLimiter>,
+) -> Result<usize> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        #[cfg(feature = "test-support")]
+        if let Some(hook) = test_support::BEFORE_PROCESS_PATH.get() {
+            hook(path);
         }
-
-        match classify_path(&path) {
-            PathType::CurrentDirectory => {
-                // Expand current directory contents, skipping hidden files
-                for entry in std::fs::read_dir(path)? {
-                    let entry_path = entry?.path();
-                    if !is_hidden_file(&entry_path) {
-                        items.push(entry_path);
-                    }
-                }
-            }
-            PathType::GitIgnoreFile => {
-                // Process .gitignore file and add the ignored files
-                let gitignore_files =
-                    utils::git_utils::get_git_ignored_files_from_gitignore(&path)?;
-                items.extend(gitignore_files);
-            }
-            PathType::Regular => {
-                // Add the path directly
-                items.push(path);
-            }
-        }
-    }
-
-    Ok(items)
+        process_path(path, config, rate_limiter)
+    }))
+    .unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown cause");
+        Err(anyhow::anyhow!("Panicked while processing: {}", message))
+    })
 }
 
-fn process_path(path: &Path, config: &Config) -> Result<usize> {
+fn process_path(
+    path: &Path,
+    config: &Config,
+    rate_limiter: Option<&utils::rate_limit::RateLimiter>,
+) -> Result<usize> {
     match config.action {
         Action::Ignore => {
             if config.dry_run {
@@ -522,7 +1747,10 @@ fn process_path(path: &Path, config: &Config) -> Result<usize> {
                 }
                 Ok(count)
             } else {
-                utils::platform_utils::add_ignore_attributes(path, true)
+                utils::rate_limit::throttle(rate_limiter);
+                apply_markers(path, config, || {
+                    utils::platform_utils::add_ignore_attributes(path, true, config.marker_value())
+                })
             }
         }
         Action::Reset => {
@@ -536,12 +1764,15 @@ fn process_path(path: &Path, config: &Config) -> Result<usize> {
                 }
                 Ok(count)
             } else {
-                utils::platform_utils::remove_ignore_attributes(path)
+                utils::rate_limit::throttle(rate_limiter);
+                apply_markers(path, config, || {
+                    utils::platform_utils::remove_ignore_attributes(path)
+                })
             }
         }
-        Action::Watch | Action::Unwatch => {
-            // Watch/Unwatch modes are handled separately in the run function
-            unreachable!("Watch/Unwatch modes should be handled before reaching process_path");
+        Action::Watch | Action::Unwatch | Action::List => {
+            // Watch/Unwatch/List modes are handled separately in the run function
+            unreachable!("Watch/Unwatch/List modes should be handled before reaching process_path");
         }
     }
 }
@@ -1,14 +1,29 @@
 use anyhow::Result;
-use clap::{Arg, Command};
+use clap::{Arg, ArgGroup, Command};
 use colored::Colorize;
-use dbx_ignore::{Action, Config, run};
-use std::path::PathBuf;
+use dbx_ignore::core::marker_query::QueryFormat;
+use dbx_ignore::core::pattern_source::PatternSource;
+use dbx_ignore::core::repo_config::RepoConfig;
+use dbx_ignore::core::status::StatusFormat;
+use dbx_ignore::utils::output_template::OutputTemplate;
+use dbx_ignore::utils::platform_utils::AttributeCondition;
+use dbx_ignore::utils::predicate::Predicate;
+use dbx_ignore::{Action, Config, run_with_summary};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 fn main() -> Result<()> {
     let mut app = Command::new("dbx-ignore")
         .version(env!("CARGO_PKG_VERSION"))
         .author(env!("CARGO_PKG_AUTHORS"))
         .about("Manage Dropbox ignore markers on files and directories")
+        .arg(
+            Arg::new("ignore")
+                .long("ignore")
+                .help("Add ignore markers (the default, unless the repository config sets default_action)")
+                .conflicts_with_all(["reset", "watch", "unwatch"])
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("reset")
                 .long("reset")
@@ -30,6 +45,34 @@ fn main() -> Result<()> {
                 .help("Stop the daemon watcher")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("list")
+                .long("list")
+                .help("List every file and directory that carries an ignore marker, one per line (with --verbose, show tags)")
+                .conflicts_with_all(["ignore", "reset", "watch", "unwatch", "files"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("unwatch-all")
+                .long("unwatch-all")
+                .help("Stop the daemon watchers of every repository, pruning registry entries for daemons that already exited")
+                .conflicts_with_all(["watch", "unwatch", "reset"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("pause")
+                .long("pause")
+                .help("Pause the running daemon: it stays alive but holds off scanning until --resume")
+                .conflicts_with_all(["resume", "watch", "unwatch"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("resume")
+                .long("resume")
+                .help("Resume a daemon paused with --pause, scanning the changes it held back")
+                .conflicts_with_all(["watch", "unwatch"])
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("status")
                 .long("status")
@@ -37,6 +80,298 @@ fn main() -> Result<()> {
                 .help("Show the status of the current folder")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("recursive")
+                .long("recursive")
+                .help("With --status: count files in all subdirectories too")
+                .requires("status")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("short")
+                .long("short")
+                .help("With --status: print a single terse line, for shell prompts")
+                .requires("status")
+                .conflicts_with_all(["json", "verbose", "recursive"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("log")
+                .long("log")
+                .help("Show the log of past operations (use --verbose for file lists)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("undo")
+                .long("undo")
+                .help("Revert the most recent marking or reset, skipping files deleted since")
+                .conflicts_with_all(["files", "reset", "watch", "unwatch", "git"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("drift")
+                .long("drift")
+                .help("Compare tracked files with actual markers; exits with status 1 if they differ")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("verify")
+                .long("verify")
+                .help("Check tracked files against the markers on disk, like --drift; exits with status 1 if drift remains")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("repair")
+                .long("repair")
+                .help("With --verify: re-apply markers that fell off tracked files")
+                .requires("verify")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .help("Check that every git-ignored file is marked, changing nothing; exits with status 1 and lists the unmarked files otherwise")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("import-markers")
+                .long("import-markers")
+                .help("Track paths marked outside dbx-ignore (e.g. with xattr -w); with --git, only git-ignored ones")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("diff-gitignore")
+                .long("diff-gitignore")
+                .help("Preview which files would be marked or unmarked if FILE replaced the root .gitignore (read-only)")
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::new("detect-conflicts")
+                .long("detect-conflicts")
+                .help("Report files carrying several cloud-ignore attributes (e.g. Dropbox and File Provider); exits with status 1 if any are found")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("is-marked")
+                .long("is-marked")
+                .help("Report whether each given path carries an ignore marker; exits with status 1 unless all are marked")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("any")
+                .long("any")
+                .help("With --is-marked: exit with status 0 if at least one path is marked")
+                .requires("is-marked")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("With --is-marked or --status: print the results as JSON")
+                .requires("json-report")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("null")
+                .short('0')
+                .long("null")
+                .help("Paths are NUL-separated: when reading them from stdin with '-', and in --list output")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("print0")
+                .long("print0")
+                .help("With --is-marked: print only the marked paths, NUL-separated")
+                .requires("is-marked")
+                .conflicts_with("json")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("schema")
+                .long("schema")
+                .value_name("OUTPUT")
+                .help("Print the JSON Schema of a --json output: status or is-marked")
+                .value_parser(["status", "is-marked"]),
+        )
+        .arg(
+            Arg::new("doctor")
+                .long("doctor")
+                .help("Show the detected platform, ignore mechanism, marker attribute and Dropbox folder")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("selective-sync-report")
+                .long("selective-sync-report")
+                .help("Show which folders are fully or partially ignored, for Dropbox selective sync")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("pre-scan-hook")
+                .long("pre-scan-hook")
+                .help("Run COMMAND before selection; it prints NUL-separated paths to exclude from the run")
+                .value_name("COMMAND"),
+        )
+        .arg(
+            Arg::new("keep-going")
+                .long("keep-going")
+                .help("Continue when the pre-scan hook fails instead of aborting")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("rate-limit")
+                .long("rate-limit")
+                .help("Limit marker operations to N per second, to go easy on shared network volumes (also applies to the daemon)")
+                .value_name("OPS_PER_SEC")
+                .value_parser(clap::value_parser!(u32).range(1..)),
+        )
+        .arg(
+            Arg::new("jobs")
+                .short('j')
+                .long("jobs")
+                .visible_alias("parallelism")
+                .help("Process files on N threads; 1 processes them one at a time, which helps on network filesystems and when debugging")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u32).range(1..)),
+        )
+        .arg(
+            Arg::new("parallel-walk-threshold")
+                .long("parallel-walk-threshold")
+                .help("Walk directories on several threads once the starting directory has N entries or more (default 64; 0 always walks in parallel)")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("nice")
+                .long("nice")
+                .help("Run at a lower CPU scheduling priority, to stay out of the way of interactive work (also applies to the daemon)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("close-write")
+                .long("close-write")
+                .help("With --watch: mark files once their writer closes them instead of on every modification (Linux)")
+                .requires("watch")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("defer-during-sync")
+                .long("defer-during-sync")
+                .help("With --watch: postpone re-scans while Dropbox is actively syncing (macOS)")
+                .requires("watch")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("pidfile")
+                .long("pidfile")
+                .help("With --watch: write the daemon's PID to FILE for process supervisors, removed on exit")
+                .value_name("FILE")
+                .requires("watch"),
+        )
+        .arg(
+            Arg::new("max-depth")
+                .long("max-depth")
+                .help("Search at most N directory levels below the current directory (also applies to the daemon)")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("patterns-url")
+                .long("patterns-url")
+                .help("With --watch: follow a central pattern list fetched from URL, re-reconciling when it changes")
+                .value_name("URL")
+                .requires("watch")
+                .conflicts_with("patterns-file"),
+        )
+        .arg(
+            Arg::new("patterns-file")
+                .long("patterns-file")
+                .help("With --watch: follow a central pattern list read from FILE, re-reconciling when it changes")
+                .value_name("FILE")
+                .requires("watch"),
+        )
+        .arg(
+            Arg::new("patterns-refresh")
+                .long("patterns-refresh")
+                .help("How often to re-fetch --patterns-url/--patterns-file (default: 5m)")
+                .value_name("DURATION")
+                .value_parser(parse_interval_arg),
+        )
+        .arg(
+            Arg::new("rescan-interval")
+                .long("rescan-interval")
+                .help("With --watch: also run a full scan every DURATION, even without filesystem events (e.g. 10m)")
+                .value_name("DURATION")
+                .requires("watch")
+                .value_parser(parse_interval_arg),
+        )
+        .arg(
+            Arg::new("max-runtime")
+                .long("max-runtime")
+                .help("With --watch: run a final scan and exit after DURATION (e.g. 2h), for CI and short-lived sessions")
+                .value_name("DURATION")
+                .requires("watch")
+                .value_parser(parse_interval_arg),
+        )
+        .arg(
+            Arg::new("metrics-file")
+                .long("metrics-file")
+                .help("With --watch: keep scan counters in FILE in the Prometheus text format, rewritten after each scan")
+                .value_name("FILE")
+                .requires("watch"),
+        )
+        .arg(
+            Arg::new("debounce")
+                .long("debounce")
+                .help("With --watch: wait until changes have settled for MS milliseconds before re-scanning (default 500, at least 50)")
+                .value_name("MS")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .requires("watch"),
+        )
+        .arg(
+            Arg::new("log-max-size")
+                .long("log-max-size")
+                .help("With --watch: cap the daemon log (daemon.log in the state directory) at SIZE, dropping the oldest lines (default 1MB)")
+                .value_name("SIZE")
+                .value_parser(parse_size_arg)
+                .requires("watch"),
+        )
+        .arg(
+            Arg::new("mode")
+                .long("mode")
+                .help("With --watch: what the daemon reconciles; 'combined' keeps tracked files, git-ignored files and tracked patterns marked together")
+                .value_name("MODE")
+                .value_parser(["auto", "combined"])
+                .default_value("auto")
+                .requires("watch"),
+        )
+        .arg(
+            Arg::new("notify-on-error")
+                .long("notify-on-error")
+                .help("With --watch: show a desktop notification when a scan hits errors (at most one every 5 minutes)")
+                .requires("watch")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("observe")
+                .long("observe")
+                .help("With --watch: log the markers each scan would add or remove without changing anything (same as --dry-run)")
+                .requires("watch")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("respect-gitignore")
+                .long("respect-gitignore")
+                .help("With --watch: don't mark pattern matches the repository's .gitignore files ignore")
+                .requires("watch")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("on-change")
+                .long("on-change")
+                .help("With --watch: run COMMAND after each scan that changed markers (counts in DBX_MARKED, DBX_UNMARKED, DBX_ERRORS)")
+                .value_name("COMMAND")
+                .requires("watch"),
+        )
         .arg(
             Arg::new("daemon-mode")
                 .long("daemon-mode")
@@ -65,6 +400,31 @@ fn main() -> Result<()> {
                 .help("Suppress output")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("color")
+                .long("color")
+                .value_name("WHEN")
+                .help("When to color output: auto (only on a terminal), always or never")
+                .value_parser(["auto", "always", "never"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("silent")
+                .long("silent")
+                .help("Suppress all output except fatal errors; rely on the exit code")
+                .conflicts_with_all([
+                    "verbose",
+                    "format",
+                    "list",
+                    "status",
+                    "log",
+                    "schema",
+                    "doctor",
+                    "selective-sync-report",
+                    "diff-gitignore",
+                ])
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("git")
                 .long("git")
@@ -72,32 +432,249 @@ fn main() -> Result<()> {
                 .help("Process git-ignored files (default if no files specified)")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("recurse-submodules")
+                .long("recurse-submodules")
+                .help("In git mode, descend into submodules and apply their own ignore rules")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("repo")
+                .long("repo")
+                .help("In git mode, run against the repository at PATH instead of the current directory")
+                .value_name("PATH")
+                .value_parser(clap::value_parser!(PathBuf))
+                .requires("git")
+                .conflicts_with("files"),
+        )
+        .arg(
+            Arg::new("cwd-scope")
+                .long("cwd-scope")
+                .help("In git mode, only process git-ignored files under the current directory instead of the whole repository")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("local-only")
+                .long("local-only")
+                .help("For .gitignore files given as arguments, apply only that file's own rules, not those inherited from parent directories or the repository")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("exclude")
+                .long("exclude")
+                .value_name("PATTERN")
+                .help("Leave out paths matching this gitignore-style pattern (repeatable)")
+                .action(clap::ArgAction::Append)
+                .conflicts_with_all(["watch", "unwatch"]),
+        )
+        .arg(
+            Arg::new("reset-excluded")
+                .long("reset-excluded")
+                .help("Remove markers left on excluded paths by earlier runs")
+                .requires("exclude")
+                .conflicts_with("reset")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .help("Exit with an error if any file couldn't be processed")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Re-scan even when the scan cache says nothing changed since the last run")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("preserve-times")
+                .long("preserve-times")
+                .help("Restore each file's access and modification times after changing its markers")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("tag")
+                .long("tag")
+                .value_name("NAME")
+                .help("Record a tag for the files marked; with --reset, only reset files carrying this tag")
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .conflicts_with_all(["watch", "unwatch"]),
+        )
+        .arg(
+            Arg::new("allow-outside")
+                .long("allow-outside")
+                .help("Process paths that resolve outside the repository (or current directory)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("follow-symlinks")
+                .long("follow-symlinks")
+                .help("Mark the targets of symlinks, even outside the repository (symlinks are skipped by default)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("skip-worktree")
+                .long("skip-worktree")
+                .help("Process files flagged skip-worktree or assume-unchanged in the git index")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("git-status")
+                .long("git-status")
+                .help("Process the untracked and ignored entries listed by git status, e.g. after a build")
+                .conflicts_with_all(["skip-worktree", "dockerignore"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("synced-only")
+                .long("synced-only")
+                .help("Only mark files Dropbox is currently syncing, skipping already excluded and online-only ones (macOS)")
+                .conflicts_with_all(["reset", "watch", "unwatch"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("preset")
+                .long("preset")
+                .help("Select the dependency and build directories of an ecosystem; repeat or comma-separate to combine")
+                .value_name("NAME")
+                .value_parser(dbx_ignore::core::presets::preset_names())
+                .value_delimiter(',')
+                .action(clap::ArgAction::Append)
+                .conflicts_with_all(["git", "skip-worktree", "git-status", "dockerignore"]),
+        )
+        .arg(
+            Arg::new("dockerignore")
+                .long("dockerignore")
+                .help("Process files a .dockerignore leaves out of the Docker build context (default: ./.dockerignore)")
+                .value_name("FILE")
+                .num_args(0..=1)
+                .default_missing_value(".dockerignore"),
+        )
+        .arg(
+            Arg::new("files0-from")
+                .long("files0-from")
+                .help("Read NUL-separated literal paths from a file ('-' for stdin)")
+                .value_name("FILE")
+                .conflicts_with("files"),
+        )
+        .arg(
+            Arg::new("older-than")
+                .long("older-than")
+                .help("Only process files last modified longer ago than DURATION (e.g. 30d, 12h). Scans the whole tree when no files are given")
+                .value_name("DURATION")
+                .value_parser(parse_duration_arg),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .help("Only process files modified within DURATION (e.g. 10m, 2h), such as the output of the last build")
+                .value_name("DURATION")
+                .value_parser(parse_duration_arg),
+        )
+        .arg(
+            Arg::new("min-size")
+                .long("min-size")
+                .help("Only process paths of at least SIZE (e.g. 512K, 10M); directories count the total size of their contents")
+                .value_name("SIZE")
+                .value_parser(parse_size_arg),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Print one line per processed path from TEMPLATE instead of the usual output, e.g. \"{action} {path} {changed}\" (placeholders: {path}, {action}, {changed}, {attrs}, {error})")
+                .value_name("TEMPLATE")
+                .value_parser(parse_template_arg)
+                .conflicts_with("verbose"),
+        )
+        .arg(
+            Arg::new("count-only")
+                .long("count-only")
+                .help("Print only a line of counts (processed=N operations=N errors=N) instead of the usual output")
+                .conflicts_with_all(["verbose", "format", "silent"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("where")
+                .long("where")
+                .help("Only process files matching EXPR, e.g. \"size>100MB && ext==mp4\" (fields: size, ext, name, age, type). Scans the whole tree when no files are given")
+                .value_name("EXPR")
+                .value_parser(parse_predicate_arg),
+        )
+        .arg(
+            Arg::new("where-xattr")
+                .long("where-xattr")
+                .help("Only process files carrying the extended attribute NAME, optionally with exactly VALUE (e.g. user.archive=true). Scans the whole tree when no files are given")
+                .value_name("NAME[=VALUE]")
+                .value_parser(parse_attribute_condition_arg),
+        )
         .arg(
             Arg::new("files")
-                .help("Files, directories, wildcards, or .gitignore files to process. Use '.' for current directory contents")
+                .help("Files, directories, wildcards, or .gitignore files to process. Use '.' for current directory contents, or '-' to read newline-separated paths from stdin")
                 .num_args(0..)
                 .value_name("FILE"),
+        )
+        .group(
+            // Reports that can print JSON
+            ArgGroup::new("json-report")
+                .args(["is-marked", "status"])
+                .multiple(true),
         );
 
     let matches = app.clone().get_matches();
+    match matches.get_one::<String>("color").map(String::as_str) {
+        Some("always") => colored::control::set_override(true),
+        Some("never") => colored::control::set_override(false),
+        _ => {}
+    }
+    let silent = matches.get_flag("silent");
+    let quiet = matches.get_flag("quiet") || silent;
+
+    // Schemas describe the output format only, so print them before looking at the tree
+    if let Some(output) = matches.get_one::<String>("schema") {
+        return dbx_ignore::show_schema(output.parse()?);
+    }
 
     // Handle no arguments case - check if we're in a git repo
-    let no_file_args = matches.get_many::<String>("files").is_none();
+    let files0_from = matches.get_one::<String>("files0-from");
+    let no_file_args = matches.get_many::<String>("files").is_none() && files0_from.is_none();
     let no_action_flags = !matches.get_flag("reset")
         && !matches.get_flag("watch")
         && !matches.get_flag("unwatch")
+        && !matches.get_flag("list")
+        && !matches.get_flag("unwatch-all")
+        && !matches.get_flag("pause")
+        && !matches.get_flag("resume")
         && !matches.get_flag("status")
-        && !matches.get_flag("git");  // Don't show help if --git is explicitly provided
+        && !matches.get_flag("log")
+        && !matches.get_flag("undo")
+        && !matches.get_flag("drift")
+        && !matches.get_flag("verify")
+        && !matches.get_flag("check")
+        && !matches.get_flag("import-markers")
+        && !matches.contains_id("diff-gitignore")
+        && !matches.get_flag("detect-conflicts")
+        && !matches.get_flag("is-marked")
+        && !matches.get_flag("selective-sync-report")
+        && !matches.get_flag("doctor")
+        && !matches.get_flag("skip-worktree")
+        && !matches.get_flag("git-status")
+        && !matches.contains_id("dockerignore")
+        && !matches.contains_id("preset")
+        && !matches.contains_id("older-than")
+        && !matches.contains_id("where")
+        && !matches.contains_id("where-xattr")
+        && !matches.get_flag("git"); // Don't show help if --git is explicitly provided
 
     if no_file_args && no_action_flags {
         // Check if we're in a git repository with a .gitignore
-        let current_dir = std::env::current_dir()?;
+        let current_dir = dbx_ignore::utils::path_utils::current_dir()?;
         let has_gitignore = current_dir.join(".gitignore").exists();
         let in_git_repo = git2::Repository::discover(&current_dir).is_ok();
 
         if in_git_repo && has_gitignore {
             // Process git-ignored files automatically
-            if !matches.get_flag("quiet") {
+            if !quiet {
                 println!(
                     "{} No arguments provided. Processing git-ignored files...",
                     "🔍".yellow()
@@ -106,8 +683,10 @@ fn main() -> Result<()> {
             // Continue with normal processing - git_mode will be set to true later
         } else {
             // Not in a git repo or no .gitignore - show help
-            app.print_help()?;
-            println!();
+            if !silent {
+                app.print_help()?;
+                println!();
+            }
             return Ok(());
         }
     }
@@ -115,7 +694,116 @@ fn main() -> Result<()> {
     // Check if status mode is requested
     if matches.get_flag("status") {
         let verbose = matches.get_flag("verbose");
-        return dbx_ignore::show_status(verbose);
+        let format = if matches.get_flag("json") {
+            StatusFormat::Json
+        } else if matches.get_flag("short") {
+            StatusFormat::Short
+        } else {
+            StatusFormat::Text
+        };
+        let recursive = matches.get_flag("recursive");
+        return dbx_ignore::show_status(verbose, recursive, format);
+    }
+
+    // Check if every registered daemon should be stopped
+    if matches.get_flag("unwatch-all") {
+        return dbx_ignore::unwatch_all(silent);
+    }
+
+    // Check if the daemon should be paused or resumed
+    if matches.get_flag("pause") || matches.get_flag("resume") {
+        return dbx_ignore::pause_watcher(matches.get_flag("pause"), silent);
+    }
+
+    // Check if the operation log is requested
+    if matches.get_flag("log") {
+        let verbose = matches.get_flag("verbose");
+        return dbx_ignore::show_log(verbose);
+    }
+
+    // The checks below answer "no" with exit status 1, like `test` and `grep`
+    // (listed under Exit Codes in README.md and API.md)
+
+    // Check if the drift report is requested
+    if matches.get_flag("drift") {
+        let verbose = matches.get_flag("verbose");
+        if dbx_ignore::show_drift(verbose, silent)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Check if verification against tracked state is requested
+    if matches.get_flag("verify") {
+        let verbose = matches.get_flag("verbose");
+        if dbx_ignore::show_verify(verbose, matches.get_flag("repair"), silent)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Check if the read-only CI check is requested
+    if matches.get_flag("check") {
+        if dbx_ignore::show_check(matches.get_flag("verbose"), silent)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Check if markers applied outside the tool should be imported
+    if matches.get_flag("import-markers") {
+        return dbx_ignore::show_import_markers(
+            matches.get_flag("git"),
+            matches.get_flag("dry-run"),
+            matches.get_flag("verbose"),
+            silent,
+        );
+    }
+
+    // Check if a .gitignore change preview is requested
+    if let Some(proposed) = matches.get_one::<String>("diff-gitignore") {
+        return dbx_ignore::show_gitignore_diff(Path::new(proposed));
+    }
+
+    // Check if a marker query is requested
+    if matches.get_flag("is-marked") {
+        let paths: Vec<PathBuf> = match files0_from {
+            Some(source) => dbx_ignore::utils::input_utils::read_nul_paths(source)?,
+            None => matches
+                .get_many::<String>("files")
+                .unwrap_or_default()
+                .map(PathBuf::from)
+                .collect(),
+        };
+        let format = if matches.get_flag("json") {
+            QueryFormat::Json
+        } else if matches.get_flag("print0") {
+            QueryFormat::Print0
+        } else {
+            QueryFormat::Text
+        };
+        if !dbx_ignore::show_is_marked(&paths, format, matches.get_flag("any"), silent)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Check if the conflicting marker audit is requested
+    if matches.get_flag("detect-conflicts") {
+        if dbx_ignore::show_conflicts(silent)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Check if the setup diagnostic is requested
+    if matches.get_flag("doctor") {
+        return dbx_ignore::show_doctor();
+    }
+
+    // Check if the selective sync report is requested
+    if matches.get_flag("selective-sync-report") {
+        return dbx_ignore::show_selective_sync_report();
     }
 
     // Determine action based on flags
@@ -136,8 +824,19 @@ fn main() -> Result<()> {
         Action::Watch
     } else if matches.get_flag("unwatch") {
         Action::Unwatch
-    } else {
+    } else if matches.get_flag("list") {
+        Action::List
+    } else if matches.get_flag("ignore") {
         Action::Ignore
+    } else {
+        // No action flag: use the repository's configured default, if any
+        let current_dir = match matches.get_one::<PathBuf>("repo") {
+            Some(repo) => repo.clone(),
+            None => dbx_ignore::utils::path_utils::current_dir()?,
+        };
+        RepoConfig::load(&current_dir)?
+            .default_action
+            .unwrap_or(Action::Ignore)
     };
 
     let file_args: Vec<String> = matches
@@ -146,7 +845,17 @@ fn main() -> Result<()> {
         .cloned()
         .collect();
 
-    let files: Vec<PathBuf> = file_args.iter().map(PathBuf::from).collect();
+    // A lone `-` reads paths from stdin, one per line (or NUL-separated with -0),
+    // taken literally
+    let stdin_list = file_args == ["-"];
+    let files: Vec<PathBuf> = match files0_from {
+        Some(source) => dbx_ignore::utils::input_utils::read_nul_paths(source)?,
+        None if stdin_list && matches.get_flag("null") => {
+            dbx_ignore::utils::input_utils::read_nul_paths("-")?
+        }
+        None if stdin_list => dbx_ignore::utils::input_utils::read_stdin_line_paths()?,
+        None => file_args.iter().map(PathBuf::from).collect(),
+    };
 
     // Detect which arguments are patterns (contain wildcards)
     let patterns: Vec<String> = file_args
@@ -170,12 +879,15 @@ fn main() -> Result<()> {
             .any(|f| dangerous_patterns.contains(f) || f.to_str() == Some("*"))
         {
             // Check if we have a git repository with .gitignore
-            let current_dir = std::env::current_dir().unwrap_or_default();
+            let current_dir = dbx_ignore::utils::path_utils::current_dir().unwrap_or_default();
             let has_gitignore = current_dir.join(".gitignore").exists();
             let in_git_repo = git2::Repository::discover(&current_dir).is_ok();
 
             if !has_gitignore || !in_git_repo {
                 eprintln!("{}", "Error: Cannot mark entire directory without a .gitignore file in a git repository.".red());
+                if silent {
+                    std::process::exit(1);
+                }
                 eprintln!(
                     "{}",
                     "This safeguard prevents accidentally marking all files for Dropbox ignore."
@@ -195,15 +907,103 @@ fn main() -> Result<()> {
         }
     }
 
+    let older_than = matches.get_one::<Duration>("older-than").copied();
+    let tag = matches.get_one::<String>("tag").cloned();
+    let where_predicate = matches.get_one::<Predicate>("where").cloned();
+    let where_xattr = matches
+        .get_one::<AttributeCondition>("where-xattr")
+        .cloned();
+
+    let pattern_source = if let Some(url) = matches.get_one::<String>("patterns-url") {
+        Some(PatternSource::Url(url.clone()))
+    } else {
+        matches.get_one::<String>("patterns-file").map(|file| {
+            PatternSource::File(std::path::absolute(file).unwrap_or_else(|_| file.into()))
+        })
+    };
+
     let config = Config {
         action,
         dry_run: matches.get_flag("dry-run"),
         verbose: matches.get_flag("verbose"),
-        quiet: matches.get_flag("quiet"),
+        quiet: quiet || matches.contains_id("format") || matches.get_flag("count-only"),
+        format: matches.get_one::<OutputTemplate>("format").cloned(),
         files,
         patterns,
-        git_mode: matches.get_flag("git") || matches.get_many::<String>("files").is_none(),
+        presets: matches
+            .get_many::<String>("preset")
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        git_mode: matches.get_flag("git")
+            || (no_file_args
+                && older_than.is_none()
+                && where_predicate.is_none()
+                && where_xattr.is_none()
+                && !matches.contains_id("dockerignore")
+                && !matches.contains_id("preset")
+                && !(action == Action::Reset && tag.is_some())),
         daemon_mode: matches.get_flag("daemon-mode"),
+        skip_worktree: matches.get_flag("skip-worktree"),
+        git_status: matches.get_flag("git-status"),
+        dockerignore: matches.get_one::<String>("dockerignore").map(PathBuf::from),
+        synced_only: matches.get_flag("synced-only"),
+        literal_paths: files0_from.is_some() || stdin_list,
+        older_than,
+        since: matches.get_one::<Duration>("since").copied(),
+        min_size: matches.get_one::<u64>("min-size").copied(),
+        where_predicate,
+        where_xattr,
+        recurse_submodules: matches.get_flag("recurse-submodules"),
+        cwd_scope: matches.get_flag("cwd-scope"),
+        repo: matches.get_one::<PathBuf>("repo").cloned(),
+        local_only: matches.get_flag("local-only"),
+        allow_outside: matches.get_flag("allow-outside"),
+        follow_symlinks: matches.get_flag("follow-symlinks"),
+        rate_limit: matches.get_one::<u32>("rate-limit").copied(),
+        jobs: matches.get_one::<u32>("jobs").map(|&jobs| jobs as usize),
+        parallel_walk_threshold: matches.get_one::<usize>("parallel-walk-threshold").copied(),
+        marker_value: None,
+        nice: matches.get_flag("nice"),
+        max_depth: matches.get_one::<usize>("max-depth").copied(),
+        close_write: matches.get_flag("close-write"),
+        defer_during_sync: matches.get_flag("defer-during-sync"),
+        pidfile: matches
+            .get_one::<String>("pidfile")
+            .map(|file| std::path::absolute(file).unwrap_or_else(|_| file.into())),
+        pattern_source,
+        pattern_refresh: matches.get_one::<Duration>("patterns-refresh").copied(),
+        rescan_interval: matches.get_one::<Duration>("rescan-interval").copied(),
+        max_runtime: matches.get_one::<Duration>("max-runtime").copied(),
+        pre_scan_hook: matches.get_one::<String>("pre-scan-hook").cloned(),
+        keep_going: matches.get_flag("keep-going"),
+        on_change: matches.get_one::<String>("on-change").cloned(),
+        metrics_file: matches
+            .get_one::<String>("metrics-file")
+            .map(|file| std::path::absolute(file).unwrap_or_else(|_| file.into())),
+        log_max_size: matches.get_one::<u64>("log-max-size").copied(),
+        notify_on_error: matches.get_flag("notify-on-error"),
+        observe: matches.get_flag("observe"),
+        combined: matches.get_one::<String>("mode").map(String::as_str) == Some("combined"),
+        respect_gitignore: matches.get_flag("respect-gitignore"),
+        silent,
+        tag,
+        debounce: matches
+            .get_one::<u64>("debounce")
+            .map(|&ms| Duration::from_millis(ms)),
+        exclude: matches
+            .get_many::<String>("exclude")
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        reset_excluded: matches.get_flag("reset-excluded"),
+        strict: matches.get_flag("strict"),
+        preserve_times: matches.get_flag("preserve-times"),
+        null: matches.get_flag("null"),
+        scan_cache: false,
+        force: matches.get_flag("force"),
+        undo: matches.get_flag("undo"),
+        progress_sink: None,
     };
 
     if config.verbose && config.quiet {
@@ -211,5 +1011,48 @@ fn main() -> Result<()> {
         std::process::exit(1);
     }
 
-    run(config)
+    // Files that failed on their own exit with 2, apart from the hard failures' 1
+    let summary = run_with_summary(config)?;
+    if matches.get_flag("count-only") {
+        println!("{}", summary.count_line());
+    }
+    if !summary.errors.is_empty() {
+        std::process::exit(2);
+    }
+    Ok(())
+}
+
+/// Parse a duration argument such as `30d` for clap
+fn parse_duration_arg(value: &str) -> Result<Duration, String> {
+    dbx_ignore::utils::parse_utils::parse_duration(value).map_err(|e| e.to_string())
+}
+
+/// Parse a non-zero interval argument such as `5m` for clap
+fn parse_interval_arg(value: &str) -> Result<Duration, String> {
+    match parse_duration_arg(value)? {
+        interval if interval.is_zero() => Err("interval must be greater than zero".to_string()),
+        interval => Ok(interval),
+    }
+}
+
+/// Parse and validate a `--format` template for clap
+fn parse_template_arg(value: &str) -> Result<OutputTemplate, String> {
+    OutputTemplate::parse(value).map_err(|e| e.to_string())
+}
+
+/// Parse a non-zero size argument such as `10MB` for clap
+fn parse_size_arg(value: &str) -> Result<u64, String> {
+    match dbx_ignore::utils::parse_utils::parse_size(value).map_err(|e| e.to_string())? {
+        0 => Err("size must be greater than zero".to_string()),
+        size => Ok(size),
+    }
+}
+
+/// Parse and validate a `--where` expression for clap
+fn parse_predicate_arg(value: &str) -> Result<Predicate, String> {
+    Predicate::parse(value).map_err(|e| e.to_string())
+}
+
+fn parse_attribute_condition_arg(value: &str) -> Result<AttributeCondition, String> {
+    value.parse().map_err(|e: anyhow::Error| e.to_string())
 }
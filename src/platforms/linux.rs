@@ -2,15 +2,65 @@ use crate::traits::PlatformHandler;
 use crate::utils::platform_utils;
 use anyhow::{Context, Result};
 use std::path::Path;
+use std::sync::OnceLock;
+
+/// Environment variable overriding the attributes to manage, as a comma-separated list
+pub const ATTRS_ENV_VAR: &str = "DBX_IGNORE_ATTRS";
+
+/// The attribute the Linux Dropbox client honours
+const DEFAULT_ATTRIBUTES: &[&str] = &["user.com.dropbox.ignored"];
+
+/// Extended attribute namespaces a name may already carry
+const NAMESPACES: &[&str] = &["user.", "trusted.", "security.", "system."];
 
 pub struct LinuxHandler;
 
+impl LinuxHandler {
+    /// Parse a comma-separated list of attribute names, as given in `DBX_IGNORE_ATTRS`
+    ///
+    /// Names are trimmed and blank entries skipped. A name without a namespace is put in
+    /// `user.`, the only one unprivileged processes can write.
+    pub fn parse_attribute_names(value: &str) -> Vec<String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                if NAMESPACES.iter().any(|ns| name.starts_with(ns)) {
+                    name.to_string()
+                } else {
+                    format!("user.{}", name)
+                }
+            })
+            .collect()
+    }
+}
+
 impl PlatformHandler for LinuxHandler {
     fn get_target_attributes() -> &'static [&'static str] {
-        &["com.dropbox.ignored"]
+        // Read once: the list must stay the same for the whole process
+        static ATTRIBUTES: OnceLock<Vec<&'static str>> = OnceLock::new();
+        ATTRIBUTES.get_or_init(|| {
+            let names = std::env::var(ATTRS_ENV_VAR)
+                .map(|value| Self::parse_attribute_names(&value))
+                .unwrap_or_default();
+            if names.is_empty() {
+                DEFAULT_ATTRIBUTES.to_vec()
+            } else {
+                names
+                    .into_iter()
+                    .map(|name| &*Box::leak(name.into_boxed_str()))
+                    .collect()
+            }
+        })
     }
 
     fn has_attribute(path: &Path, attr: &str) -> Result<bool> {
+        // An empty name can never be present (the kernel rejects it with ERANGE)
+        if attr.is_empty() {
+            return Ok(false);
+        }
+
         match xattr::get(path, attr) {
             Ok(Some(_)) => Ok(true),
             Ok(None) => Ok(false),
@@ -28,9 +78,28 @@ impl PlatformHandler for LinuxHandler {
         }
     }
 
-    fn add_attribute(path: &Path, attr: &str) -> Result<()> {
-        // Add the attribute with a simple marker value
-        xattr::set(path, attr, b"1")
+    fn read_attribute(path: &Path, attr: &str) -> Result<Option<Vec<u8>>> {
+        if attr.is_empty() {
+            return Ok(None);
+        }
+
+        match xattr::get(path, attr) {
+            Ok(value) => Ok(value),
+            Err(e) => match e.kind() {
+                // Unsupported filesystems and unreadable files carry nothing we can see
+                std::io::ErrorKind::Other
+                | std::io::ErrorKind::PermissionDenied
+                | std::io::ErrorKind::NotFound
+                | std::io::ErrorKind::InvalidInput => Ok(None),
+                _ => Err(e).with_context(|| {
+                    format!("Failed to read xattr {} from {}", attr, path.display())
+                }),
+            },
+        }
+    }
+
+    fn add_attribute(path: &Path, attr: &str, value: &[u8]) -> Result<()> {
+        xattr::set(path, attr, value)
             .with_context(|| format!("Failed to add xattr {} to {}", attr, path.display()))
     }
 
@@ -47,6 +116,16 @@ impl PlatformHandler for LinuxHandler {
         }
     }
 
+    fn ignore_mechanism() -> String {
+        if std::env::var(ATTRS_ENV_VAR)
+            .is_ok_and(|value| !Self::parse_attribute_names(&value).is_empty())
+        {
+            format!("extended attributes (names from {})", ATTRS_ENV_VAR)
+        } else {
+            "extended attributes".to_string()
+        }
+    }
+
     fn platform_name() -> &'static str {
         "Linux"
     }
@@ -1,34 +1,75 @@
 use crate::traits::PlatformHandler;
 use crate::utils::platform_utils;
 use anyhow::{Context, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
 
 pub struct MacOSHandler;
 
 /// Cached result of File Provider detection
 static IS_FILE_PROVIDER: OnceLock<bool> = OnceLock::new();
 
+/// Dropbox folders in `~/Library/CloudStorage`, where the File Provider client keeps them
+///
+/// Each account gets its own folder: `Dropbox`, `Dropbox (Personal)`, `Dropbox-Team`...
+fn file_provider_roots() -> Vec<PathBuf> {
+    let Ok(home) = std::env::var("HOME") else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(Path::new(&home).join("Library/CloudStorage")) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("Dropbox"))
+        .map(|entry| entry.path())
+        .collect()
+}
+
 /// Detect if Dropbox is using the File Provider API
 fn is_using_file_provider() -> bool {
-    *IS_FILE_PROVIDER.get_or_init(|| {
-        // Check if the user's home directory contains the CloudStorage path
-        if let Ok(home) = std::env::var("HOME") {
-            let cloud_storage_path = format!("{}/Library/CloudStorage", home);
-            if std::path::Path::new(&cloud_storage_path).exists() {
-                // Check for Dropbox folder in CloudStorage
-                let dropbox_patterns = ["Dropbox", "Dropbox (Personal)", "Dropbox (Business)"];
-
-                for pattern in &dropbox_patterns {
-                    let dropbox_path = format!("{}/{}", cloud_storage_path, pattern);
-                    if std::path::Path::new(&dropbox_path).exists() {
-                        return true;
-                    }
-                }
-            }
-        }
-        false
-    })
+    *IS_FILE_PROVIDER.get_or_init(|| !file_provider_roots().is_empty())
+}
+
+/// `st_flags` bit for dataless files, whose content lives only in the cloud
+const SF_DATALESS: u32 = 0x4000_0000;
+
+/// Check if a path is an online-only placeholder without local content
+fn is_dataless(path: &Path) -> bool {
+    use std::os::macos::fs::MetadataExt;
+
+    path.symlink_metadata()
+        .is_ok_and(|metadata| metadata.st_flags() & SF_DATALESS != 0)
+}
+
+/// How recently Dropbox must have touched its staging cache to count as syncing
+const SYNC_SETTLE_WINDOW: Duration = Duration::from_secs(30);
+
+/// Check if a path lies in Dropbox's File Provider folder under `~/Library/CloudStorage`
+fn is_in_file_provider_domain(path: &Path) -> bool {
+    let Ok(home) = std::env::var("HOME") else {
+        return false;
+    };
+    path.strip_prefix(Path::new(&home).join("Library/CloudStorage"))
+        .ok()
+        .and_then(|relative| relative.components().next())
+        .is_some_and(|folder| folder.as_os_str().to_string_lossy().starts_with("Dropbox"))
+}
+
+/// Find the `.dropbox.cache` staging directory of the Dropbox folder containing `path`
+///
+/// Dropbox keeps it at the root of the Dropbox folder, which lives under
+/// `~/Library/CloudStorage` with File Provider and anywhere the user chose otherwise.
+fn dropbox_cache_dir(path: &Path) -> Option<PathBuf> {
+    if is_using_file_provider() && !is_in_file_provider_domain(path) {
+        // Outside the File Provider domain, nothing here is synced
+        return None;
+    }
+
+    path.ancestors()
+        .map(|dir| dir.join(".dropbox.cache"))
+        .find(|cache| cache.is_dir())
 }
 
 impl PlatformHandler for MacOSHandler {
@@ -39,17 +80,16 @@ impl PlatformHandler for MacOSHandler {
 
     fn has_attribute(path: &Path, attr: &str) -> Result<bool> {
         // Only check for the appropriate attribute based on File Provider detection
-        let should_check = if is_using_file_provider() {
-            attr == "com.apple.fileprovider.ignore#P"
-        } else {
-            attr == "com.dropbox.ignored"
-        };
-
-        if !should_check {
+        if !Self::is_expected_attribute(attr) {
             // If it's not the appropriate attribute for this system, consider it as not present
             return Ok(false);
         }
 
+        Self::attribute_present(path, attr)
+    }
+
+    fn attribute_present(path: &Path, attr: &str) -> Result<bool> {
+        // Read the raw xattr so the variant for the other Dropbox mode shows up too
         match xattr::get(path, attr) {
             Ok(Some(_)) => Ok(true),
             Ok(None) => Ok(false),
@@ -66,16 +106,40 @@ impl PlatformHandler for MacOSHandler {
         }
     }
 
-    fn add_attribute(path: &Path, attr: &str) -> Result<()> {
-        // Only add the appropriate attribute based on File Provider detection
-        let should_add = if is_using_file_provider() {
+    fn is_expected_attribute(attr: &str) -> bool {
+        if is_using_file_provider() {
             attr == "com.apple.fileprovider.ignore#P"
         } else {
             attr == "com.dropbox.ignored"
-        };
+        }
+    }
+
+    fn read_attribute(path: &Path, attr: &str) -> Result<Option<Vec<u8>>> {
+        if attr.is_empty() {
+            return Ok(None);
+        }
+
+        match xattr::get(path, attr) {
+            Ok(value) => Ok(value),
+            Err(e) => match e.kind() {
+                // Unsupported filesystems and unreadable files carry nothing we can see
+                std::io::ErrorKind::Other
+                | std::io::ErrorKind::PermissionDenied
+                | std::io::ErrorKind::NotFound
+                | std::io::ErrorKind::InvalidInput => Ok(None),
+                _ => Err(e).with_context(|| {
+                    format!("Failed to read xattr {} from {}", attr, path.display())
+                }),
+            },
+        }
+    }
+
+    fn add_attribute(path: &Path, attr: &str, value: &[u8]) -> Result<()> {
+        // Only add the appropriate attribute based on File Provider detection
+        let should_add = Self::is_expected_attribute(attr);
 
         if should_add {
-            xattr::set(path, attr, b"1")
+            xattr::set(path, attr, value)
                 .with_context(|| format!("Failed to add xattr {} to {}", attr, path.display()))
         } else {
             // Silently skip the inappropriate attribute
@@ -85,11 +149,7 @@ impl PlatformHandler for MacOSHandler {
 
     fn remove_attribute(path: &Path, attr: &str) -> Result<()> {
         // Only remove the appropriate attribute based on File Provider detection
-        let should_remove = if is_using_file_provider() {
-            attr == "com.apple.fileprovider.ignore#P"
-        } else {
-            attr == "com.dropbox.ignored"
-        };
+        let should_remove = Self::is_expected_attribute(attr);
 
         if should_remove {
             match xattr::remove(path, attr) {
@@ -102,6 +162,60 @@ impl PlatformHandler for MacOSHandler {
         }
     }
 
+    fn is_sync_in_progress(path: &Path) -> bool {
+        // Transfers are staged in the cache, so its entries change while syncing
+        dropbox_cache_dir(path)
+            .and_then(|cache| cache.metadata().ok())
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age < SYNC_SETTLE_WINDOW)
+    }
+
+    fn is_synced_by_client(path: &Path) -> bool {
+        let managed = if is_using_file_provider() {
+            // Everything inside the File Provider domain belongs to Dropbox
+            is_in_file_provider_domain(path)
+        } else {
+            // Dropbox tags each item it syncs with its own metadata
+            matches!(xattr::get(path, "com.dropbox.attrs"), Ok(Some(_)))
+        };
+        managed && !is_dataless(path)
+    }
+
+    fn ignore_mechanism() -> String {
+        if is_using_file_provider() {
+            "File Provider (Dropbox in ~/Library/CloudStorage)".to_string()
+        } else {
+            "Dropbox extended attribute (legacy sync)".to_string()
+        }
+    }
+
+    fn dropbox_folder(path: &Path) -> Option<PathBuf> {
+        if is_using_file_provider() {
+            let home = std::env::var("HOME").ok()?;
+            let cloud_storage = Path::new(&home).join("Library/CloudStorage");
+            let folder = path
+                .strip_prefix(&cloud_storage)
+                .ok()?
+                .components()
+                .next()?;
+            is_in_file_provider_domain(path).then(|| cloud_storage.join(folder))
+        } else {
+            dropbox_cache_dir(path).and_then(|cache| cache.parent().map(Path::to_path_buf))
+        }
+    }
+
+    fn dropbox_roots() -> Vec<PathBuf> {
+        // File Provider folders, plus any the legacy client still lists
+        let mut roots = file_provider_roots();
+        if let Ok(home) = std::env::var("HOME") {
+            roots.extend(platform_utils::dropbox_roots_from_info_json(
+                &Path::new(&home).join(".dropbox/info.json"),
+            ));
+        }
+        roots
+    }
+
     fn platform_name() -> &'static str {
         "macOS"
     }
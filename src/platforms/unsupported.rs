@@ -13,7 +13,7 @@ impl PlatformHandler for UnsupportedHandler {
         Ok(false)
     }
 
-    fn add_attribute(_path: &Path, _attr: &str) -> Result<()> {
+    fn add_attribute(_path: &Path, _attr: &str, _value: &[u8]) -> Result<()> {
         // No-op on unsupported platforms - cannot add ignore markers
         Err(anyhow::anyhow!(
             "Adding ignore markers not supported on this platform"
@@ -1,6 +1,7 @@
 use crate::traits::PlatformHandler;
+use crate::utils::platform_utils;
 use anyhow::{Context, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[cfg(target_os = "windows")]
 use std::ffi::OsStr;
@@ -9,6 +10,81 @@ use std::os::windows::ffi::OsStrExt;
 
 pub struct WindowsHandler;
 
+impl WindowsHandler {
+    /// Names of the alternate data streams on a file or directory
+    ///
+    /// Streams are enumerated with `FindFirstStreamW`/`FindNextStreamW` rather than by
+    /// opening `path:stream`, which misfires on some paths and filesystems. Names are
+    /// returned without the `:` prefix and `:$DATA` suffix; the unnamed main stream is
+    /// left out.
+    #[cfg(target_os = "windows")]
+    pub fn list_streams(path: &Path) -> std::io::Result<Vec<String>> {
+        use windows_sys::Win32::Foundation::{
+            ERROR_HANDLE_EOF, GetLastError, INVALID_HANDLE_VALUE,
+        };
+        use windows_sys::Win32::Storage::FileSystem::{
+            FindClose, FindFirstStreamW, FindNextStreamW, FindStreamInfoStandard,
+            WIN32_FIND_STREAM_DATA,
+        };
+
+        let wide_path: Vec<u16> = OsStr::new(path)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+        // SAFETY: WIN32_FIND_STREAM_DATA is plain data, for which all zeroes is valid
+        let mut data: WIN32_FIND_STREAM_DATA = unsafe { std::mem::zeroed() };
+
+        // SAFETY: the path is NUL-terminated and `data` matches FindStreamInfoStandard
+        let handle = unsafe {
+            FindFirstStreamW(
+                wide_path.as_ptr(),
+                FindStreamInfoStandard,
+                (&mut data as *mut WIN32_FIND_STREAM_DATA).cast(),
+                0,
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            // Directories without named streams report end-of-file straight away
+            let error = unsafe { GetLastError() };
+            return if error == ERROR_HANDLE_EOF {
+                Ok(Vec::new())
+            } else {
+                Err(std::io::Error::from_raw_os_error(error as i32))
+            };
+        }
+
+        let mut streams = Vec::new();
+        let result = loop {
+            let len = data
+                .cStreamName
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(data.cStreamName.len());
+            let name = String::from_utf16_lossy(&data.cStreamName[..len]);
+            let name = name.strip_prefix(':').unwrap_or(&name);
+            let name = name.strip_suffix(":$DATA").unwrap_or(name);
+            if !name.is_empty() {
+                streams.push(name.to_string());
+            }
+
+            // SAFETY: `handle` is a valid find handle until FindClose below
+            if unsafe { FindNextStreamW(handle, (&mut data as *mut WIN32_FIND_STREAM_DATA).cast()) }
+                == 0
+            {
+                let error = unsafe { GetLastError() };
+                break if error == ERROR_HANDLE_EOF {
+                    Ok(streams)
+                } else {
+                    Err(std::io::Error::from_raw_os_error(error as i32))
+                };
+            }
+        };
+        // SAFETY: `handle` came from FindFirstStreamW and is closed exactly once
+        unsafe { FindClose(handle) };
+        result
+    }
+}
+
 impl PlatformHandler for WindowsHandler {
     fn get_target_attributes() -> &'static [&'static str] {
         &["com.dropbox.ignored"]
@@ -17,9 +93,8 @@ impl PlatformHandler for WindowsHandler {
     fn has_attribute(path: &Path, attr: &str) -> Result<bool> {
         #[cfg(target_os = "windows")]
         {
-            let stream_path = format!("{}:{}", path.display(), attr);
-            match std::fs::metadata(&stream_path) {
-                Ok(_) => Ok(true),
+            match Self::list_streams(path) {
+                Ok(streams) => Ok(streams.iter().any(|name| name == attr)),
                 Err(e) => match e.kind() {
                     std::io::ErrorKind::NotFound => Ok(false),
                     std::io::ErrorKind::PermissionDenied => Ok(false),
@@ -34,16 +109,35 @@ impl PlatformHandler for WindowsHandler {
         }
     }
 
-    fn add_attribute(path: &Path, attr: &str) -> Result<()> {
+    fn read_attribute(path: &Path, attr: &str) -> Result<Option<Vec<u8>>> {
         #[cfg(target_os = "windows")]
         {
             let stream_path = format!("{}:{}", path.display(), attr);
-            std::fs::write(&stream_path, b"1")
-                .with_context(|| format!("Failed to add stream {} to {}", attr, path.display()))
+            match std::fs::read(&stream_path) {
+                Ok(value) => Ok(Some(value)),
+                Err(e) => match e.kind() {
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied => Ok(None),
+                    _ => Err(anyhow::anyhow!("Failed to read stream {}: {}", attr, e)),
+                },
+            }
         }
         #[cfg(not(target_os = "windows"))]
         {
             let _ = (path, attr);
+            Ok(None)
+        }
+    }
+
+    fn add_attribute(path: &Path, attr: &str, value: &[u8]) -> Result<()> {
+        #[cfg(target_os = "windows")]
+        {
+            let stream_path = format!("{}:{}", path.display(), attr);
+            std::fs::write(&stream_path, value)
+                .with_context(|| format!("Failed to add stream {} to {}", attr, path.display()))
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (path, attr, value);
             Err(anyhow::anyhow!(
                 "Windows ADS not supported on this platform"
             ))
@@ -79,6 +173,23 @@ impl PlatformHandler for WindowsHandler {
         }
     }
 
+    fn ignore_mechanism() -> String {
+        "NTFS alternate data stream".to_string()
+    }
+
+    fn dropbox_roots() -> Vec<PathBuf> {
+        // The client keeps info.json under the roaming or the local application data folder
+        ["APPDATA", "LOCALAPPDATA"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .flat_map(|dir| {
+                platform_utils::dropbox_roots_from_info_json(
+                    &Path::new(&dir).join("Dropbox").join("info.json"),
+                )
+            })
+            .collect()
+    }
+
     fn platform_name() -> &'static str {
         "Windows"
     }
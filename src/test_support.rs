@@ -0,0 +1,121 @@
+//! Helpers for driving the watch daemon in-process from tests (`test-support` feature)
+//!
+//! [`BEFORE_PROCESS_PATH`] injects failures into ordinary runs.
+//!
+//! [`WatchHarness`] runs the real watch loop with a [`ChannelEventSource`], so tests can
+//! inject synthetic filesystem events and wait for the resulting scan instead of
+//! spawning the binary and sleeping. Pair it with a paused Tokio clock
+//! (`#[tokio::test(start_paused = true)]`) and debounce delays elapse instantly.
+
+use anyhow::{Context, Result};
+use notify::Event;
+use notify::event::{CreateKind, EventKind, RemoveKind};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::core::watch::{self, EventSource, ScanSummary, WatchConfig};
+
+/// Called with each path right before a run processes it
+pub static BEFORE_PROCESS_PATH: OnceLock<fn(&Path)> = OnceLock::new();
+
+/// Event source fed by hand through a channel
+pub struct ChannelEventSource {
+    injected: Option<mpsc::UnboundedReceiver<Event>>,
+}
+
+impl ChannelEventSource {
+    /// Create the source along with the sender used to inject events into it
+    pub fn new() -> (Self, mpsc::UnboundedSender<Event>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let source = Self {
+            injected: Some(receiver),
+        };
+        (source, sender)
+    }
+}
+
+impl EventSource for ChannelEventSource {
+    fn start(&mut self, _repo_root: &Path, events: mpsc::UnboundedSender<Event>) -> Result<()> {
+        let mut injected = self
+            .injected
+            .take()
+            .context("Event source was already started")?;
+        tokio::spawn(async move {
+            while let Some(event) = injected.recv().await {
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// A watch loop running in the background of the current Tokio runtime
+pub struct WatchHarness {
+    events: mpsc::UnboundedSender<Event>,
+    scans: mpsc::UnboundedReceiver<ScanSummary>,
+    shutdown: Arc<AtomicBool>,
+    task: JoinHandle<Result<()>>,
+}
+
+impl WatchHarness {
+    /// Start watching as configured; the initial scan is the first [`next_scan`](Self::next_scan)
+    pub fn start(config: WatchConfig) -> Self {
+        let (source, events) = ChannelEventSource::new();
+        let (scan_sender, scans) = mpsc::unbounded_channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let task = tokio::spawn(watch::run_watcher(
+            config,
+            source,
+            shutdown.clone(),
+            Some(scan_sender),
+        ));
+
+        Self {
+            events,
+            scans,
+            shutdown,
+            task,
+        }
+    }
+
+    /// Deliver a filesystem event to the watcher
+    pub fn inject(&self, event: Event) {
+        let _ = self.events.send(event);
+    }
+
+    /// Report that a file was created at `path`
+    pub fn file_created(&self, path: &Path) {
+        self.inject(Event::new(EventKind::Create(CreateKind::File)).add_path(path.to_path_buf()));
+    }
+
+    /// Report that the file at `path` was removed
+    pub fn file_removed(&self, path: &Path) {
+        self.inject(Event::new(EventKind::Remove(RemoveKind::File)).add_path(path.to_path_buf()));
+    }
+
+    /// Wait for the watcher's next scan to finish
+    ///
+    /// Fails with the watcher's error if it stopped instead.
+    pub async fn next_scan(&mut self) -> Result<ScanSummary> {
+        if let Some(summary) = self.scans.recv().await {
+            return Ok(summary);
+        }
+
+        match (&mut self.task).await {
+            Ok(Err(e)) => Err(e.context("Watcher stopped")),
+            Ok(Ok(())) => anyhow::bail!("Watcher stopped"),
+            Err(e) => Err(anyhow::anyhow!("Watcher panicked: {}", e)),
+        }
+    }
+
+    /// Shut the watcher down and wait for it to exit
+    pub async fn stop(self) -> Result<()> {
+        self.shutdown.store(true, Ordering::SeqCst);
+        self.task.await.context("Watcher panicked")?
+    }
+}
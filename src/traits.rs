@@ -1,5 +1,6 @@
+use crate::utils::platform_utils;
 use anyhow::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Platform abstraction trait for handling extended attributes/metadata
 pub trait PlatformHandler: Send + Sync {
@@ -9,8 +10,30 @@ pub trait PlatformHandler: Send + Sync {
     /// Check if a specific attribute exists on the given path
     fn has_attribute(path: &Path, attr: &str) -> Result<bool>;
 
-    /// Add a specific attribute to the given path to mark it as ignored
-    fn add_attribute(path: &Path, attr: &str) -> Result<()>;
+    /// Check if an attribute is physically present on the path, even if it isn't the
+    /// variant this system expects (used to spot legacy or mismatched markers)
+    fn attribute_present(path: &Path, attr: &str) -> Result<bool> {
+        Self::has_attribute(path, attr)
+    }
+
+    /// Check if an attribute is the variant Dropbox currently honours on this system
+    fn is_expected_attribute(_attr: &str) -> bool {
+        true
+    }
+
+    /// Read the value of any attribute on the given path, or `None` if it isn't set
+    ///
+    /// Unlike the marker checks, `attr` can be any name, so users can select files by
+    /// their own metadata conventions. Platforms with no way to read them report `None`.
+    fn read_attribute(_path: &Path, _attr: &str) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Add a specific attribute holding `value` to the given path to mark it as ignored
+    ///
+    /// Only the attribute's presence marks the path; [`has_attribute`](Self::has_attribute)
+    /// doesn't look at the value.
+    fn add_attribute(path: &Path, attr: &str, value: &[u8]) -> Result<()>;
 
     /// Remove a specific attribute from the given path to unmark it as ignored
     fn remove_attribute(path: &Path, attr: &str) -> Result<()>;
@@ -18,6 +41,47 @@ pub trait PlatformHandler: Send + Sync {
     /// Get the platform name for display purposes
     fn platform_name() -> &'static str;
 
+    /// Check if the sync client is busy transferring files in the folder containing `path`
+    ///
+    /// Used to defer daemon scans; platforms with no way to tell report `false`.
+    fn is_sync_in_progress(_path: &Path) -> bool {
+        false
+    }
+
+    /// Check if the sync client currently syncs `path`'s content
+    ///
+    /// Used by `--synced-only`; platforms with no way to tell treat every path as synced.
+    fn is_synced_by_client(_path: &Path) -> bool {
+        true
+    }
+
+    /// How ignore markers are stored on this system, for `--doctor`
+    fn ignore_mechanism() -> String {
+        "extended attributes".to_string()
+    }
+
+    /// Root of the Dropbox folder containing `path`, if one can be detected
+    ///
+    /// The desktop client keeps a `.dropbox.cache` directory at the root of its folder.
+    fn dropbox_folder(path: &Path) -> Option<PathBuf> {
+        path.ancestors()
+            .find(|dir| dir.join(".dropbox.cache").is_dir())
+            .map(Path::to_path_buf)
+    }
+
+    /// Roots of the Dropbox folders set up on this system
+    ///
+    /// The desktop client lists one per linked account in `~/.dropbox/info.json`.
+    fn dropbox_roots() -> Vec<PathBuf> {
+        std::env::var_os("HOME")
+            .map(|home| {
+                platform_utils::dropbox_roots_from_info_json(
+                    &Path::new(&home).join(".dropbox").join("info.json"),
+                )
+            })
+            .unwrap_or_default()
+    }
+
     /// Check if this platform is supported
     fn is_supported() -> bool {
         true
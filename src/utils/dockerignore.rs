@@ -0,0 +1,78 @@
+use crate::utils::pattern_matcher::PatternMatcher;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Translate `.dockerignore` lines into gitignore patterns with Docker's semantics
+///
+/// Docker differs from git in a few ways, which the translation accounts for:
+/// - Patterns are always relative to the context root, so `*.log` only matches at the
+///   top level (use `**/*.log` for any depth); a leading `/` or `./` changes nothing
+/// - Paths are cleaned like Go's `filepath.Clean`, and a trailing `/` is dropped, so
+///   `build/` also matches a file named `build`
+/// - Excluding a directory excludes everything inside it, yet a later `!` exception can
+///   still re-include a path in there, which git refuses to do
+/// - Surrounding whitespace is trimmed, and comments must start the line
+pub fn to_gitignore_patterns<S: AsRef<str>>(lines: &[S]) -> Vec<String> {
+    let mut patterns = Vec::new();
+    for line in lines {
+        let line = line.as_ref().trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (negation, pattern) = match line.strip_prefix('!') {
+            Some(rest) => ("!", rest.trim()),
+            None => ("", line),
+        };
+        let Some(pattern) = clean(pattern) else {
+            continue;
+        };
+
+        // Anchor at the root, and cover the contents in case the path is a directory
+        patterns.push(format!("{}/{}", negation, pattern));
+        patterns.push(format!("{}/{}/**", negation, pattern));
+    }
+    patterns
+}
+
+/// Clean a path pattern the way Docker does, or `None` if nothing is left of it
+fn clean(pattern: &str) -> Option<String> {
+    let mut components: Vec<&str> = Vec::new();
+    for component in pattern.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            _ => components.push(component),
+        }
+    }
+    (!components.is_empty()).then(|| components.join("/"))
+}
+
+/// Get the files Docker would leave out of a build context described by a `.dockerignore`
+///
+/// The context root is the directory containing the file.
+pub fn get_files_from_dockerignore(
+    dockerignore_path: &Path,
+    max_depth: Option<usize>,
+    parallel_walk_threshold: usize,
+) -> Result<Vec<PathBuf>> {
+    let context_root = match dockerignore_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let contents = std::fs::read_to_string(dockerignore_path)
+        .with_context(|| format!("Failed to read {}", dockerignore_path.display()))?;
+    let lines: Vec<&str> = contents.lines().collect();
+
+    let matcher = PatternMatcher::new(context_root, &to_gitignore_patterns(&lines), false)?
+        .with_max_depth(max_depth)
+        .with_parallel_walk_threshold(parallel_walk_threshold);
+    let mut files = matcher.find_matching_files(context_root)?;
+
+    // Sort for consistent output
+    files.sort();
+
+    Ok(files)
+}
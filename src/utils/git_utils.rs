@@ -1,66 +1,192 @@
-use crate::utils::pattern_matcher;
+use crate::utils::ignore_reason::GitignoreExplainer;
+use crate::utils::{path_utils, pattern_matcher};
 use anyhow::{Context, Result};
-use std::collections::HashSet;
+use ignore::WalkBuilder;
 use std::path::{Path, PathBuf};
 
 /// Get all git-ignored files in the current directory
 pub fn get_git_ignored_files() -> Result<Vec<PathBuf>> {
-    get_git_ignored_files_in_path(&std::env::current_dir()?)
+    get_git_ignored_files_in_path(&path_utils::current_dir()?)
+}
+
+/// Get the working directory root of the repository containing `path`
+pub fn repo_root(path: &Path) -> Result<PathBuf> {
+    let repo = git2::Repository::discover(path)
+        .context("Not in a git repository or git repository not found")?;
+    let workdir = repo
+        .workdir()
+        .context("Repository has no working directory")?;
+    Ok(workdir.components().collect())
 }
 
 /// Get all git-ignored files in a specific path using our own implementation
 pub fn get_git_ignored_files_in_path(path: &Path) -> Result<Vec<PathBuf>> {
+    get_git_ignored_files_scoped(path, false, None)
+}
+
+/// Get git-ignored files under `path`, optionally descending into submodules
+///
+/// By default submodules (and other nested repositories) are skipped, as the outer
+/// repository doesn't manage their contents. With `recurse_submodules`, files inside
+/// them are reported according to each submodule's own ignore rules. `max_depth` limits
+/// how many directory levels below `path` are searched (unlimited if `None`).
+pub fn get_git_ignored_files_scoped(
+    path: &Path,
+    recurse_submodules: bool,
+    max_depth: Option<usize>,
+) -> Result<Vec<PathBuf>> {
+    get_git_ignored_entries_scoped(path, recurse_submodules, max_depth).map(|entries| entries.files)
+}
+
+/// Git-ignored entries found by [`get_git_ignored_entries_scoped`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GitIgnoredEntries {
+    /// Ignored files, including those inside ignored directories
+    pub files: Vec<PathBuf>,
+    /// Directories ignored as a whole, outermost only
+    pub dirs: Vec<PathBuf>,
+}
+
+impl GitIgnoredEntries {
+    /// Whether `path` is, or lies inside, a directory ignored as a whole
+    pub fn in_ignored_dir(&self, path: &Path) -> bool {
+        self.dirs.iter().any(|dir| path.starts_with(dir))
+    }
+}
+
+/// Get git-ignored files under `path` along with the directories ignored as a whole
+///
+/// Submodules and `max_depth` are handled as by [`get_git_ignored_files_scoped`].
+pub fn get_git_ignored_entries_scoped(
+    path: &Path,
+    recurse_submodules: bool,
+    max_depth: Option<usize>,
+) -> Result<GitIgnoredEntries> {
+    get_git_ignored_entries_among(
+        path,
+        std::slice::from_ref(&path.to_path_buf()),
+        recurse_submodules,
+        max_depth,
+    )
+}
+
+/// Get the git-ignored entries at or below `paths`, as a walk of all of `path` would report them
+///
+/// Only the directories leading to `paths` are visited on the way down, so checking a few
+/// changed files doesn't cost a walk of the whole tree. Entries above `paths` that are
+/// ignored directories are reported too, as they decide whether the files below are.
+pub fn get_git_ignored_entries_among(
+    path: &Path,
+    paths: &[PathBuf],
+    recurse_submodules: bool,
+    max_depth: Option<usize>,
+) -> Result<GitIgnoredEntries> {
     // Check if we're in a git repository
     let _repo = git2::Repository::discover(path)
         .context("Not in a git repository or git repository not found")?;
 
-    // Build two walkers - one that respects gitignore, one that doesn't
-    use ignore::WalkBuilder;
-
-    // Walker that sees everything (to get all files)
-    let mut all_files_builder = WalkBuilder::new(path);
-    all_files_builder
+    // A single walk sees everything; each entry is matched against the gitignore rules
+    // in effect for it, and everything below an ignored directory is ignored outright
+    let walker = WalkBuilder::new(path)
+        .standard_filters(false)
         .hidden(false)
-        .git_ignore(false)
-        .git_global(false)
-        .git_exclude(false);
+        .max_depth(max_depth)
+        .filter_entry({
+            let targets = paths.to_vec();
+            move |entry| {
+                !path_utils::is_internal_dir_name(entry.file_name())
+                    && (recurse_submodules || !path_utils::is_nested_repo_root(entry))
+                    && path_utils::leads_to_any(entry.path(), &targets)
+            }
+        })
+        .build();
 
-    // Walker that respects gitignore (to get non-ignored files)
-    let mut filtered_builder = WalkBuilder::new(path);
-    filtered_builder
-        .hidden(false)
-        .git_ignore(true)
-        .git_global(true)
-        .git_exclude(true);
+    // One explainer per repository the walk is in: submodules follow their own rules
+    let mut explainers = vec![GitignoreExplainer::new(path)];
+    let mut ignored_dir: Option<PathBuf> = None;
+    let mut ignored_files = Vec::new();
+    let mut ignored_dirs = Vec::new();
 
-    // Collect all files
-    let mut all_files = HashSet::new();
-    for entry in all_files_builder.build().flatten() {
-        let path = entry.path();
-        // Skip .git directory and only collect files (not directories)
-        if !path.components().any(|c| c.as_os_str() == ".git") && path.is_file() {
-            all_files.insert(path.to_path_buf());
+    for entry in walker.flatten() {
+        let entry_path = entry.path();
+        if entry.depth() == 0 {
+            continue;
         }
-    }
 
-    // Collect non-ignored files
-    let mut non_ignored_files = HashSet::new();
-    for entry in filtered_builder.build().flatten() {
-        let path = entry.path();
-        // Skip .git directory and only collect files (not directories)
-        if !path.components().any(|c| c.as_os_str() == ".git") && path.is_file() {
-            non_ignored_files.insert(path.to_path_buf());
+        // Leave the ignored directory and the submodules the walk has moved out of
+        if ignored_dir
+            .as_ref()
+            .is_some_and(|dir| !entry_path.starts_with(dir))
+        {
+            ignored_dir = None;
         }
-    }
+        while explainers.len() > 1
+            && !entry_path.starts_with(explainers[explainers.len() - 1].repo_root())
+        {
+            explainers.pop();
+        }
+
+        let Some(file_type) = entry.file_type() else {
+            continue;
+        };
+        let is_dir = file_type.is_dir();
+        let ignored = ignored_dir.is_some()
+            || explainers
+                .last_mut()
+                .is_some_and(|explainer| explainer.is_ignored(entry_path, is_dir));
 
-    // The ignored files are the difference
-    let mut ignored_files: Vec<PathBuf> =
-        all_files.difference(&non_ignored_files).cloned().collect();
+        if is_dir {
+            if ignored && ignored_dir.is_none() {
+                ignored_dir = Some(entry_path.to_path_buf());
+                ignored_dirs.push(entry_path.to_path_buf());
+            } else if path_utils::is_nested_repo_root(&entry) {
+                explainers.push(GitignoreExplainer::new(entry_path));
+            }
+        } else if ignored && (file_type.is_file() || entry_path.is_file()) {
+            // Symlinks count when they point at a file, as they always have
+            ignored_files.push(entry_path.to_path_buf());
+        }
+    }
 
     // Sort for consistent output
     ignored_files.sort();
+    ignored_dirs.sort();
+
+    Ok(GitIgnoredEntries {
+        files: ignored_files,
+        dirs: ignored_dirs,
+    })
+}
+
+/// Find every `.gitignore` file under `repo_root`, outside `.git` and the state directory
+pub fn find_gitignore_files(repo_root: &Path) -> Result<Vec<PathBuf>> {
+    let mut gitignore_files = Vec::new();
+
+    let walker = WalkBuilder::new(repo_root)
+        .standard_filters(false)
+        .hidden(false)
+        .git_ignore(false)
+        .git_global(false)
+        .git_exclude(false)
+        .filter_entry(|entry| {
+            // Skip .git and the state directory
+            !path_utils::is_internal_dir_name(entry.file_name())
+        })
+        .build();
+
+    for entry in walker.flatten() {
+        let path = entry.path();
+        if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name == ".gitignore")
+            .unwrap_or(false)
+        {
+            gitignore_files.push(path.to_path_buf());
+        }
+    }
 
-    Ok(ignored_files)
+    Ok(gitignore_files)
 }
 
 /// Get git-ignored files from a specific .gitignore file's directory
@@ -80,8 +206,130 @@ pub fn get_git_ignored_files_from_gitignore(gitignore_path: &Path) -> Result<Vec
         .collect())
 }
 
+/// Get files under a .gitignore file's directory matched by that file's own rules only
+///
+/// Unlike `get_git_ignored_files_from_gitignore`, rules inherited from parent
+/// directories, nested `.gitignore` files, `.git/info/exclude` and the global excludes
+/// are not applied, and no git repository is required.
+pub fn get_files_matching_gitignore_only(
+    gitignore_path: &Path,
+    parallel_walk_threshold: usize,
+) -> Result<Vec<PathBuf>> {
+    let gitignore_dir = match gitignore_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let contents = std::fs::read_to_string(gitignore_path)
+        .with_context(|| format!("Failed to read {}", gitignore_path.display()))?;
+    let patterns: Vec<String> = contents.lines().map(String::from).collect();
+
+    let mut files = pattern_matcher::find_files_matching_patterns(
+        gitignore_dir,
+        &patterns,
+        false,
+        parallel_walk_threshold,
+    )?;
+
+    // Sort for consistent output
+    files.sort();
+
+    Ok(files)
+}
+
+/// Get files flagged skip-worktree or assume-unchanged in the git index under a path
+pub fn get_skip_worktree_files_in_path(path: &Path) -> Result<Vec<PathBuf>> {
+    let repo = git2::Repository::discover(path)
+        .context("Not in a git repository or git repository not found")?;
+    let workdir = repo
+        .workdir()
+        .context("Repository has no working directory")?
+        .canonicalize()?;
+    let scope = path.canonicalize()?;
+    let index = repo.index().context("Failed to read git index")?;
+
+    let mut files = Vec::new();
+    for entry in index.iter() {
+        let assume_unchanged = git2::IndexEntryFlag::from_bits_truncate(entry.flags).is_valid();
+        let skip_worktree = git2::IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended)
+            .is_skip_worktree();
+
+        if assume_unchanged || skip_worktree {
+            let relative = String::from_utf8_lossy(&entry.path).into_owned();
+            let file_path = workdir.join(relative);
+            if file_path.starts_with(&scope) && file_path.exists() {
+                files.push(file_path);
+            }
+        }
+    }
+
+    // Sort for consistent output
+    files.sort();
+
+    Ok(files)
+}
+
+/// Get the untracked and ignored entries `git status --porcelain --ignored` lists under `path`
+///
+/// As in that output, a directory holding nothing but untracked (or nothing but ignored)
+/// entries is reported as the directory itself rather than file by file.
+pub fn get_untracked_and_ignored_in_path(path: &Path) -> Result<Vec<PathBuf>> {
+    let repo = git2::Repository::discover(path)
+        .context("Not in a git repository or git repository not found")?;
+    let workdir = repo
+        .workdir()
+        .context("Repository has no working directory")?
+        .canonicalize()?;
+    let scope = path.canonicalize()?;
+
+    let mut options = git2::StatusOptions::new();
+    options
+        .include_untracked(true)
+        .include_ignored(true)
+        .recurse_untracked_dirs(false)
+        .recurse_ignored_dirs(false)
+        .exclude_submodules(true);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .context("Failed to read git status")?;
+
+    let mut files = Vec::new();
+    for entry in statuses.iter() {
+        if !entry
+            .status()
+            .intersects(git2::Status::WT_NEW | git2::Status::IGNORED)
+        {
+            continue;
+        }
+        let relative = String::from_utf8_lossy(entry.path_bytes()).into_owned();
+        let file_path = workdir.join(relative.trim_end_matches('/'));
+        if file_path.starts_with(&scope)
+            && file_path != scope
+            && !file_path
+                .components()
+                .any(|c| path_utils::is_internal_dir_name(c.as_os_str()))
+        {
+            files.push(file_path);
+        }
+    }
+
+    // Sort for consistent output
+    files.sort();
+
+    Ok(files)
+}
+
 /// Find files matching patterns using gitignore-style pattern matching
 /// This ensures consistent behavior whether in a git repository or not
-pub fn find_files_matching_patterns(base_path: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
-    pattern_matcher::find_files_matching_patterns(base_path, patterns)
+pub fn find_files_matching_patterns(
+    base_path: &Path,
+    patterns: &[String],
+    respect_gitignore: bool,
+    parallel_walk_threshold: usize,
+) -> Result<Vec<PathBuf>> {
+    pattern_matcher::find_files_matching_patterns(
+        base_path,
+        patterns,
+        respect_gitignore,
+        parallel_walk_threshold,
+    )
 }
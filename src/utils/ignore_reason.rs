@@ -0,0 +1,177 @@
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The rule that caused a path to be selected for marking
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IgnoreReason {
+    /// A `.gitignore` (or git exclude) rule; `source` is the file it came from
+    GitIgnore {
+        pattern: String,
+        source: Option<PathBuf>,
+    },
+    /// A pattern given on the command line
+    Pattern(String),
+    /// Flagged skip-worktree or assume-unchanged in the git index
+    SkipWorktree,
+    /// Listed as untracked or ignored by git status
+    GitStatus,
+    /// Left out of the Docker build context by this `.dockerignore`
+    DockerIgnore(PathBuf),
+    /// Selected by `--older-than`/`--where` filters alone
+    Filter,
+    /// dbx-ignore's own state directory, which is always marked
+    StateDir,
+    /// Named explicitly on the command line
+    Explicit,
+}
+
+impl IgnoreReason {
+    /// Human-readable explanation, with rule files shown relative to `base`
+    pub fn describe(&self, base: &Path) -> String {
+        match self {
+            IgnoreReason::GitIgnore {
+                pattern,
+                source: Some(source),
+            } => {
+                let source = match source.strip_prefix(base) {
+                    Ok(relative) => format!("./{}", relative.display()),
+                    Err(_) => source.display().to_string(),
+                };
+                format!("matched `{}` in {}", pattern, source)
+            }
+            IgnoreReason::GitIgnore {
+                pattern,
+                source: None,
+            } => format!("matched gitignore rule `{}`", pattern),
+            IgnoreReason::Pattern(pattern) => format!("matched pattern `{}`", pattern),
+            IgnoreReason::SkipWorktree => "flagged skip-worktree/assume-unchanged".to_string(),
+            IgnoreReason::GitStatus => "untracked or ignored in git status".to_string(),
+            IgnoreReason::DockerIgnore(source) => format!("excluded by {}", source.display()),
+            IgnoreReason::Filter => {
+                "matched --older-than/--where/--where-xattr filters".to_string()
+            }
+            IgnoreReason::StateDir => "dbx-ignore state directory".to_string(),
+            IgnoreReason::Explicit => "explicitly selected".to_string(),
+        }
+    }
+}
+
+/// Finds the gitignore rule that decides whether a path is ignored
+///
+/// Rules are consulted the way git does: the nearest `.gitignore` first, then those
+/// of parent directories up to the repository root, then `.git/info/exclude` and the
+/// global excludes file. Matchers are cached per directory.
+pub struct GitignoreExplainer {
+    repo_root: PathBuf,
+    matchers: HashMap<PathBuf, Option<Gitignore>>,
+    fallbacks: Vec<Gitignore>,
+}
+
+impl GitignoreExplainer {
+    /// Create an explainer for the repository containing `path`
+    pub fn new(path: &Path) -> Self {
+        let repo_root = path
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(path)
+            .to_path_buf();
+
+        let mut fallbacks = Vec::new();
+        let exclude = repo_root.join(".git").join("info").join("exclude");
+        if exclude.is_file() {
+            // Exclude rules are relative to the repository root, not to `.git/info`
+            let mut builder = GitignoreBuilder::new(&repo_root);
+            builder.add(&exclude);
+            if let Ok(matcher) = builder.build() {
+                fallbacks.push(matcher);
+            }
+        }
+        let (global, _) = GitignoreBuilder::new(&repo_root).build_global();
+        fallbacks.push(global);
+
+        Self {
+            repo_root,
+            matchers: HashMap::new(),
+            fallbacks,
+        }
+    }
+
+    /// Root of the repository whose rules are consulted
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    /// Get the rule that ignores `path`, or `None` if it isn't ignored
+    pub fn explain(&mut self, path: &Path) -> Option<IgnoreReason> {
+        self.explain_as(path, path.is_dir())
+    }
+
+    /// Check if `path` is ignored, with its type already known (saves a `stat`)
+    pub fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        self.explain_as(path, is_dir).is_some()
+    }
+
+    fn explain_as(&mut self, path: &Path, is_dir: bool) -> Option<IgnoreReason> {
+        if !path.starts_with(&self.repo_root) {
+            return None;
+        }
+
+        let mut dir = path.parent();
+        while let Some(current) = dir {
+            if let Some(matcher) = self.matcher_for(current) {
+                match matcher.matched_path_or_any_parents(path, is_dir) {
+                    Match::Ignore(glob) => return Some(reason_from(glob)),
+                    Match::Whitelist(_) => return None,
+                    Match::None => {}
+                }
+            }
+            if current == self.repo_root {
+                break;
+            }
+            dir = current.parent();
+        }
+
+        for matcher in &self.fallbacks {
+            match matcher.matched_path_or_any_parents(path, is_dir) {
+                Match::Ignore(glob) => return Some(reason_from(glob)),
+                Match::Whitelist(_) => return None,
+                Match::None => {}
+            }
+        }
+
+        None
+    }
+
+    /// Consult `gitignore` in place of the repository root's own `.gitignore`
+    ///
+    /// Its rules are read relative to the repository root, as if the file were there.
+    /// Nested `.gitignore` files and the fallbacks are consulted as usual.
+    pub fn with_root_gitignore(mut self, gitignore: &Path) -> Result<Self, ignore::Error> {
+        let mut builder = GitignoreBuilder::new(&self.repo_root);
+        if let Some(error) = builder.add(gitignore) {
+            return Err(error);
+        }
+        let matcher = builder.build()?;
+        self.matchers.insert(self.repo_root.clone(), Some(matcher));
+        Ok(self)
+    }
+
+    fn matcher_for(&mut self, dir: &Path) -> Option<&Gitignore> {
+        self.matchers
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let gitignore = dir.join(".gitignore");
+                gitignore.is_file().then(|| Gitignore::new(&gitignore).0)
+            })
+            .as_ref()
+    }
+}
+
+fn reason_from(glob: &ignore::gitignore::Glob) -> IgnoreReason {
+    IgnoreReason::GitIgnore {
+        pattern: glob.original().to_string(),
+        source: glob.from().map(Path::to_path_buf),
+    }
+}
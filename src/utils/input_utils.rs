@@ -0,0 +1,61 @@
+use anyhow::{Context, Result};
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Split NUL-separated path data into paths without any lossy conversion
+///
+/// Empty entries (e.g. a trailing NUL) are skipped.
+pub fn split_nul_paths(data: &[u8]) -> Result<Vec<PathBuf>> {
+    data.split(|b| *b == 0)
+        .filter(|entry| !entry.is_empty())
+        .map(bytes_to_path)
+        .collect()
+}
+
+/// Read NUL-separated paths from a file, or from stdin when `source` is `-`
+pub fn read_nul_paths(source: &str) -> Result<Vec<PathBuf>> {
+    let mut data = Vec::new();
+    if source == "-" {
+        std::io::stdin()
+            .read_to_end(&mut data)
+            .context("Failed to read paths from stdin")?;
+    } else {
+        data = std::fs::read(source)
+            .with_context(|| format!("Failed to read paths from {}", source))?;
+    }
+    split_nul_paths(&data)
+}
+
+/// Split newline-separated path data into paths
+///
+/// Surrounding whitespace (including `\r` from CRLF input) is trimmed, and blank lines
+/// are skipped. Use NUL-separated input for names with leading or trailing spaces.
+pub fn split_line_paths(data: &str) -> Vec<PathBuf> {
+    data.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Read newline-separated paths from stdin, for a lone `-` file argument
+pub fn read_stdin_line_paths() -> Result<Vec<PathBuf>> {
+    let mut data = String::new();
+    std::io::stdin()
+        .read_to_string(&mut data)
+        .context("Failed to read paths from stdin")?;
+    Ok(split_line_paths(&data))
+}
+
+#[cfg(unix)]
+fn bytes_to_path(bytes: &[u8]) -> Result<PathBuf> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    Ok(PathBuf::from(OsStr::from_bytes(bytes)))
+}
+
+#[cfg(not(unix))]
+fn bytes_to_path(bytes: &[u8]) -> Result<PathBuf> {
+    let path = std::str::from_utf8(bytes).context("Path is not valid UTF-8")?;
+    Ok(PathBuf::from(path))
+}
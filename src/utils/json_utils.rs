@@ -1,33 +1,52 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
 use std::fs;
 use std::io::Write;
 use std::path::Path;
+use std::time::{Duration, SystemTime};
 use tempfile::NamedTempFile;
 
+/// Name prefix of the temporary files `write_json_atomic` creates next to its target
+pub const TEMP_FILE_PREFIX: &str = ".tmp";
+
+/// Age after which a leftover temporary file is considered orphaned by a crashed write
+pub const STALE_TEMP_FILE_AGE: Duration = Duration::from_secs(10 * 60);
+
 /// Atomically write JSON data to a file
 ///
 /// This function ensures that the file is either fully written or not written at all,
 /// preventing partial writes that could corrupt the JSON file.
 pub fn write_json_atomic<T: Serialize>(path: &Path, data: &T) -> Result<()> {
+    // Serialize to JSON with pretty formatting
+    let json = serde_json::to_string_pretty(data).context("Failed to serialize to JSON")?;
+
+    // Validate the JSON by parsing it back
+    let _: serde_json::Value = serde_json::from_str(&json).context("Generated invalid JSON")?;
+
+    write_atomic(path, json.as_bytes())
+}
+
+/// Atomically write raw contents to a file, through a temporary file next to it
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
     // Ensure parent directory exists
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).context("Failed to create parent directory")?;
     }
 
     // Create a temporary file in the same directory
-    let dir = path.parent().unwrap_or(Path::new("."));
-    let mut temp_file = NamedTempFile::new_in(dir).context("Failed to create temporary file")?;
-
-    // Serialize to JSON with pretty formatting
-    let json = serde_json::to_string_pretty(data).context("Failed to serialize to JSON")?;
-
-    // Validate the JSON by parsing it back
-    let _: serde_json::Value = serde_json::from_str(&json).context("Generated invalid JSON")?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut temp_file = tempfile::Builder::new()
+        .prefix(TEMP_FILE_PREFIX)
+        .tempfile_in(dir)
+        .context("Failed to create temporary file")?;
 
     // Write to temporary file
     temp_file
-        .write_all(json.as_bytes())
+        .write_all(contents)
         .context("Failed to write to temporary file")?;
 
     // Ensure all data is flushed to disk
@@ -49,6 +68,62 @@ pub fn write_json_atomic<T: Serialize>(path: &Path, data: &T) -> Result<()> {
     Ok(())
 }
 
+/// Check that `write_json_atomic` could write into `dir` without changing anything
+///
+/// Creates and immediately deletes a temporary file the same way the atomic writer
+/// does. A missing directory is checked against its closest existing ancestor,
+/// since the writer would create it.
+pub fn check_dir_writable(dir: &Path) -> Result<()> {
+    if dir.exists() && !dir.is_dir() {
+        anyhow::bail!("{} exists but is not a directory", dir.display());
+    }
+
+    let probe_dir = dir
+        .ancestors()
+        .find(|ancestor| ancestor.is_dir())
+        .unwrap_or(Path::new("."));
+    NamedTempFile::new_in(probe_dir)
+        .with_context(|| format!("Cannot create files in {}", probe_dir.display()))?;
+
+    Ok(())
+}
+
+/// Check if a file name is one `write_json_atomic` uses for its temporary files
+pub fn is_temp_file_name(name: &OsStr) -> bool {
+    name.to_str()
+        .is_some_and(|name| name.starts_with(TEMP_FILE_PREFIX))
+}
+
+/// Remove temporary files left in `dir` by atomic writes that were interrupted
+///
+/// Only files last modified more than `max_age` ago are removed, so writes still in
+/// progress in another process are left alone. Returns the number of files removed.
+pub fn remove_stale_temp_files(dir: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let cutoff = SystemTime::now()
+        .checked_sub(max_age)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        if !is_temp_file_name(&entry.file_name()) {
+            continue;
+        }
+        let stale = entry
+            .metadata()
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .and_then(|metadata| metadata.modified().ok())
+            .is_some_and(|modified| modified < cutoff);
+        if stale && fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
 /// Read and deserialize JSON data from a file with validation
 pub fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T> {
     let contents = fs::read_to_string(path)
@@ -1,5 +1,14 @@
+pub mod dockerignore;
 pub mod git_utils;
 pub mod gitignore_manager;
+pub mod ignore_reason;
+pub mod input_utils;
 pub mod json_utils;
+pub mod output_template;
+pub mod parse_utils;
+pub mod path_utils;
 pub mod pattern_matcher;
 pub mod platform_utils;
+pub mod predicate;
+pub mod priority;
+pub mod rate_limit;
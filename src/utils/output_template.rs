@@ -0,0 +1,135 @@
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+use crate::Action;
+
+/// A value a `--format` template can insert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// The path as processed
+    Path,
+    /// `ignore` or `reset`
+    Action,
+    /// `true` if markers were added or removed, otherwise `false`
+    Changed,
+    /// Number of markers added or removed
+    Attrs,
+    /// The error message, empty on success
+    Error,
+}
+
+/// Placeholder names, as written between braces
+const FIELDS: &[(&str, Field)] = &[
+    ("path", Field::Path),
+    ("action", Field::Action),
+    ("changed", Field::Changed),
+    ("attrs", Field::Attrs),
+    ("error", Field::Error),
+];
+
+/// What happened to one path, as rendered by an [`OutputTemplate`]
+#[derive(Debug, Clone, Copy)]
+pub struct FileOutcome<'a> {
+    pub path: &'a Path,
+    pub action: Action,
+    /// Markers added or removed (or that would be, in a dry run)
+    pub ops: usize,
+    /// Why the path couldn't be processed
+    pub error: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+/// A parsed `--format` template, rendered once per processed path
+///
+/// Placeholders are written in braces, e.g. `{path}`; `{{` and `}}` stand for literal
+/// braces. Placeholders: `{path}`, `{action}`, `{changed}` (`true`/`false`), `{attrs}`
+/// (markers added or removed) and `{error}` (empty on success).
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputTemplate {
+    segments: Vec<Segment>,
+}
+
+impl OutputTemplate {
+    /// Parse a template, rejecting unknown placeholders and unbalanced braces
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(anyhow!(
+                                    "Invalid --format template: unclosed '{{' (use '{{{{' for a literal brace)"
+                                ));
+                            }
+                        }
+                    }
+                    let Some(&(_, field)) = FIELDS.iter().find(|(known, _)| *known == name) else {
+                        return Err(anyhow!(
+                            "Invalid --format template: unknown placeholder '{{{}}}'. Valid placeholders: {}",
+                            name,
+                            FIELDS
+                                .iter()
+                                .map(|(known, _)| format!("{{{}}}", known))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    };
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(field));
+                }
+                '}' => {
+                    return Err(anyhow!(
+                        "Invalid --format template: unmatched '}}' (use '}}}}' for a literal brace)"
+                    ));
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Render the line for one path
+    pub fn render(&self, outcome: &FileOutcome) -> String {
+        let mut line = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => line.push_str(text),
+                Segment::Field(Field::Path) => line.push_str(&outcome.path.display().to_string()),
+                Segment::Field(Field::Action) => line.push_str(&outcome.action.to_string()),
+                Segment::Field(Field::Changed) => {
+                    line.push_str(if outcome.ops > 0 { "true" } else { "false" })
+                }
+                Segment::Field(Field::Attrs) => line.push_str(&outcome.ops.to_string()),
+                Segment::Field(Field::Error) => line.push_str(outcome.error.unwrap_or("")),
+            }
+        }
+        line
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+/// Parse a human-friendly duration such as `30s`, `10m`, `2h`, `7d` or `4w`
+///
+/// A bare number is interpreted as seconds.
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+
+    let value: u64 = number.parse().with_context(|| {
+        format!(
+            "Invalid duration '{}': expected e.g. 30s, 10m, 2h, 7d",
+            input
+        )
+    })?;
+
+    let multiplier = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Invalid duration unit '{}' in '{}': use s, m, h, d or w",
+                unit,
+                input
+            ));
+        }
+    };
+
+    let seconds = value
+        .checked_mul(multiplier)
+        .with_context(|| format!("Duration '{}' is out of range", input))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Parse a human-friendly size such as `512`, `100KB`, `1.5G` or `2TB`
+///
+/// Units are binary (1 KB = 1024 bytes) and case-insensitive; a bare number is bytes.
+pub fn parse_size(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+
+    let value: f64 = number
+        .parse()
+        .with_context(|| format!("Invalid size '{}': expected e.g. 512, 100KB, 1.5GB", input))?;
+
+    let multiplier: u64 = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        "T" | "TB" => 1 << 40,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Invalid size unit '{}' in '{}': use B, KB, MB, GB or TB",
+                unit,
+                input
+            ));
+        }
+    };
+
+    Ok((value * multiplier as f64).round() as u64)
+}
+
+/// Format a byte count for display (e.g. `1.5 MB`)
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
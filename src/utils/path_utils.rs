@@ -0,0 +1,342 @@
+use anyhow::{Context, Result};
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// Name of the directory holding dbx-ignore's own state (tracked files, daemon status, temp files)
+pub const STATE_DIR_NAME: &str = ".dbx-ignore";
+
+/// Get the current working directory
+///
+/// Fails with an actionable message when the directory has been removed from under
+/// the process, which `std::env::current_dir` reports as a bare "No such file or directory".
+pub fn current_dir() -> Result<PathBuf> {
+    explain_current_dir_error(std::env::current_dir())
+}
+
+/// Map a failed `std::env::current_dir` lookup to an error a user can act on
+pub fn explain_current_dir_error(result: io::Result<PathBuf>) -> Result<PathBuf> {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(anyhow::anyhow!(
+            "The current directory no longer exists; cd into an existing directory (or use --git --repo PATH) and run again"
+        )),
+        result => result.context("Failed to get current directory"),
+    }
+}
+
+/// Apply the symlink policy to a list of paths to mark
+///
+/// Extended attributes on a link are ambiguous: Linux refuses `user.` attributes on
+/// symlinks, macOS marks the link itself, and Windows streams follow it to the target.
+/// By default symlinks are split off and returned separately, so nothing outside the
+/// tree is marked through one. With `follow`, each link is replaced by its target;
+/// dangling links are still split off, and targets already in the list are dropped.
+pub fn apply_symlink_policy(paths: Vec<PathBuf>, follow: bool) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut kept = Vec::with_capacity(paths.len());
+    let mut skipped = Vec::new();
+    let mut targets = Vec::new();
+    for path in paths {
+        if !path.is_symlink() {
+            kept.push(path);
+        } else if !follow {
+            skipped.push(path);
+        } else {
+            match std::fs::canonicalize(&path) {
+                Ok(target) => targets.push(target),
+                Err(_) => skipped.push(path),
+            }
+        }
+    }
+    for target in targets {
+        if !kept.contains(&target) {
+            kept.push(target);
+        }
+    }
+    (kept, skipped)
+}
+
+/// Split off paths that are hard links to a file earlier in the list
+///
+/// Hard links share one inode, and with it one set of extended attributes, so marking
+/// any of them marks them all. Returns the paths to process, in their original order,
+/// and each skipped link paired with the path it shares a file with. Only Unix exposes
+/// the device and inode numbers needed; elsewhere every path is kept.
+pub fn split_hard_links(paths: Vec<PathBuf>) -> (Vec<PathBuf>, Vec<(PathBuf, PathBuf)>) {
+    #[cfg(unix)]
+    {
+        use std::collections::HashMap;
+        use std::os::unix::fs::MetadataExt;
+
+        let mut first_link: HashMap<(u64, u64), PathBuf> = HashMap::new();
+        let mut kept = Vec::with_capacity(paths.len());
+        let mut links = Vec::new();
+        for path in paths {
+            let inode = std::fs::symlink_metadata(&path)
+                .ok()
+                .filter(|metadata| metadata.is_file() && metadata.nlink() > 1)
+                .map(|metadata| (metadata.dev(), metadata.ino()));
+            match inode {
+                Some(inode) => match first_link.get(&inode) {
+                    Some(first) => links.push((path, first.clone())),
+                    None => {
+                        first_link.insert(inode, path.clone());
+                        kept.push(path);
+                    }
+                },
+                None => kept.push(path),
+            }
+        }
+        (kept, links)
+    }
+
+    #[cfg(not(unix))]
+    {
+        (paths, Vec::new())
+    }
+}
+
+/// Run `operation` on `path`, then put back the access and modification times it had
+///
+/// Changing extended attributes leaves the mtime alone on most systems, but some tools
+/// and filesystems still report the file as modified. The change time (ctime) can't be
+/// restored: the kernel updates it for the attribute change and again for the restore.
+/// Paths whose times can't be read are processed without preserving them.
+pub fn with_preserved_times<T>(path: &Path, operation: impl FnOnce() -> Result<T>) -> Result<T> {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return operation();
+    };
+    let atime = filetime::FileTime::from_last_access_time(&metadata);
+    let mtime = filetime::FileTime::from_last_modification_time(&metadata);
+
+    let result = operation()?;
+    filetime::set_symlink_file_times(path, atime, mtime)
+        .with_context(|| format!("Failed to restore times on {}", path.display()))?;
+    Ok(result)
+}
+
+/// The running executable and the directory holding it, if they lie inside `root`
+///
+/// Self-contained distributions may ship the binary inside the tree it manages; marking
+/// it, or its directory, would stop Dropbox from syncing the tool itself.
+pub fn own_binary_paths(root: &Path) -> Vec<PathBuf> {
+    std::env::current_exe()
+        .map(|exe| binary_paths_within(&exe, root))
+        .unwrap_or_default()
+}
+
+/// `exe` and its parent directory, canonicalized, if `exe` lies inside `root`
+pub fn binary_paths_within(exe: &Path, root: &Path) -> Vec<PathBuf> {
+    let (Ok(exe), Ok(root)) = (exe.canonicalize(), root.canonicalize()) else {
+        return Vec::new();
+    };
+    if !exe.starts_with(&root) || exe == root {
+        return Vec::new();
+    }
+    let parent = exe.parent().map(Path::to_path_buf);
+    std::iter::once(exe).chain(parent).collect()
+}
+
+/// Check if `path` is one of the paths returned by [`own_binary_paths`]
+pub fn is_own_binary_path(path: &Path, own_binary: &[PathBuf]) -> bool {
+    !own_binary.is_empty()
+        && own_binary.contains(&path.canonicalize().unwrap_or_else(|_| path.to_path_buf()))
+}
+
+/// Resolve `.` and `..` components without touching the filesystem
+///
+/// An empty result is returned as `.`; `..` above a relative start is kept.
+pub fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    if normalized.as_os_str().is_empty() {
+        normalized.push(".");
+    }
+    normalized
+}
+
+/// Express `path` (absolute, or relative to `root`) relative to the canonical `root`
+///
+/// `.` and `..` are resolved and the directories above `path` canonicalized, so every
+/// spelling of a file yields the same result. The last component is kept as is: a
+/// symlink names itself, not its target. Paths outside `root` come back absolute.
+pub fn canonicalize_relative(root: &Path, path: &Path) -> PathBuf {
+    let joined = normalize_lexically(&root.join(path));
+    let resolved = match (joined.parent(), joined.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map_or_else(|_| joined.clone(), |parent| parent.join(name)),
+        _ => joined,
+    };
+    match resolved.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => PathBuf::from("."),
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => resolved,
+    }
+}
+
+/// Check if a directory entry name is one that walkers must never descend into
+///
+/// This covers `.git` and the state directory, so the tool never marks or trips over
+/// its own state, including temp files created while `json_utils::write_json_atomic`
+/// is in progress.
+pub fn is_internal_dir_name(name: &OsStr) -> bool {
+    name == ".git" || name == STATE_DIR_NAME
+}
+
+/// Check if a path is the state directory or lies inside it, relative to `root`
+pub fn is_in_state_dir(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .any(|c| c.as_os_str() == STATE_DIR_NAME)
+}
+
+/// Check if a walk has to pass through `path` to reach one of `targets`, or is inside one
+pub fn leads_to_any(path: &Path, targets: &[PathBuf]) -> bool {
+    targets
+        .iter()
+        .any(|target| target.starts_with(path) || path.starts_with(target))
+}
+
+/// Check if a walker entry is the root of a nested repository, such as a git submodule
+///
+/// Submodules have a `.git` file and nested clones a `.git` directory; either way the
+/// outer repository's ignore rules stop applying there. The walk root itself never counts.
+pub fn is_nested_repo_root(entry: &ignore::DirEntry) -> bool {
+    entry.depth() > 0
+        && entry.file_type().is_some_and(|t| t.is_dir())
+        && entry.path().join(".git").exists()
+}
+
+/// Collect every file under `root`, skipping `.git` and the state directory
+///
+/// `max_depth` limits how many directory levels below `root` are visited (unlimited if `None`).
+pub fn walk_files(
+    root: &Path,
+    max_depth: Option<usize>,
+    parallel_walk_threshold: usize,
+) -> Vec<PathBuf> {
+    let mut builder = ignore::WalkBuilder::new(root);
+    builder
+        .standard_filters(false)
+        .hidden(false)
+        .max_depth(max_depth)
+        .filter_entry(|entry| !is_internal_dir_name(entry.file_name()));
+    walk_selected(&builder, root, parallel_walk_threshold, |entry| {
+        entry.path().is_file()
+    })
+    .0
+}
+
+/// Size of a path in bytes: a file's length, or the total length of the files in a directory
+///
+/// Symlinks inside a directory aren't followed. `None` if the path can't be read.
+pub fn total_size(path: &Path) -> Option<u64> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_dir() {
+        return Some(metadata.len());
+    }
+    let size = walkdir::WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    Some(size)
+}
+
+/// Top-level entries at which walks switch to parallel, unless `--parallel-walk-threshold`
+/// says otherwise
+pub const DEFAULT_PARALLEL_WALK_THRESHOLD: usize = 64;
+
+/// How a directory tree is walked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkStrategy {
+    /// One thread; cheapest for small trees
+    Sequential,
+    /// Worker threads sharing the tree; pays off once the tree is large
+    Parallel,
+}
+
+/// Choose how to walk `root`, estimating its size from its top-level entries
+///
+/// Walks go parallel once `root` has `threshold` top-level entries (0: always). Counting
+/// stops at the threshold, so the estimate costs at most one partial `read_dir`. An
+/// unreadable root is walked sequentially.
+pub fn walk_strategy(root: &Path, threshold: usize) -> WalkStrategy {
+    if threshold == 0 {
+        return WalkStrategy::Parallel;
+    }
+    let entries = std::fs::read_dir(root)
+        .map(|entries| entries.take(threshold).count())
+        .unwrap_or(0);
+    if entries >= threshold {
+        WalkStrategy::Parallel
+    } else {
+        WalkStrategy::Sequential
+    }
+}
+
+/// Walk `root` with `builder`, collecting the paths of the entries `select` accepts
+///
+/// The walk is sequential or parallel as [`walk_strategy`] decides for
+/// `parallel_walk_threshold`; either way the paths come back sorted. Entries that
+/// couldn't be read are returned alongside, for the caller to report or skip.
+pub fn walk_selected<F>(
+    builder: &ignore::WalkBuilder,
+    root: &Path,
+    parallel_walk_threshold: usize,
+    select: F,
+) -> (Vec<PathBuf>, Vec<ignore::Error>)
+where
+    F: Fn(&ignore::DirEntry) -> bool + Sync,
+{
+    let mut selected = Vec::new();
+    let mut errors = Vec::new();
+
+    match walk_strategy(root, parallel_walk_threshold) {
+        WalkStrategy::Sequential => {
+            for entry in builder.build() {
+                match entry {
+                    Ok(entry) if select(&entry) => selected.push(entry.into_path()),
+                    Ok(_) => {}
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+        WalkStrategy::Parallel => {
+            let shared = Mutex::new((Vec::new(), Vec::new()));
+            builder.build_parallel().run(|| {
+                Box::new(|entry| {
+                    match entry {
+                        Ok(entry) if select(&entry) => {
+                            shared.lock().unwrap().0.push(entry.into_path())
+                        }
+                        Ok(_) => {}
+                        Err(e) => shared.lock().unwrap().1.push(e),
+                    }
+                    ignore::WalkState::Continue
+                })
+            });
+            (selected, errors) = shared.into_inner().unwrap();
+        }
+    }
+
+    selected.sort();
+    (selected, errors)
+}
@@ -1,17 +1,30 @@
+use crate::utils::ignore_reason::GitignoreExplainer;
+use crate::utils::path_utils;
 use anyhow::{Context, Result};
+use ignore::Match;
 use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 /// A pattern matcher that provides gitignore-style pattern matching
 /// Works consistently whether inside or outside a git repository
 pub struct PatternMatcher {
     gitignore: Gitignore,
     base_path: PathBuf,
+    max_depth: Option<usize>,
+    /// Top-level entries at which `find_matching_files` walks in parallel
+    parallel_walk_threshold: usize,
+    /// The repository's own ignore rules, when paths they ignore are to be left out
+    repo_rules: Option<Mutex<GitignoreExplainer>>,
 }
 
 impl PatternMatcher {
     /// Create a new pattern matcher with the given patterns
-    pub fn new(base_path: &Path, patterns: &[String]) -> Result<Self> {
+    ///
+    /// The repository's `.gitignore` files, `.git/info/exclude` and global excludes are
+    /// not consulted unless `respect_gitignore` is set, in which case paths they ignore
+    /// never match.
+    pub fn new(base_path: &Path, patterns: &[String], respect_gitignore: bool) -> Result<Self> {
         let mut builder = GitignoreBuilder::new(base_path);
 
         // Add each pattern to the builder
@@ -26,51 +39,122 @@ impl PatternMatcher {
         Ok(Self {
             gitignore,
             base_path: base_path.to_path_buf(),
+            max_depth: None,
+            parallel_walk_threshold: path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
+            repo_rules: respect_gitignore.then(|| Mutex::new(GitignoreExplainer::new(base_path))),
         })
     }
 
+    /// Limit how many directory levels below the root `find_matching_files` searches
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Set the number of top-level entries at which `find_matching_files` walks in
+    /// parallel (0: always)
+    pub fn with_parallel_walk_threshold(mut self, threshold: usize) -> Self {
+        self.parallel_walk_threshold = threshold;
+        self
+    }
+
     /// Check if a path matches any of the patterns
     pub fn is_ignored(&self, path: &Path) -> bool {
+        self.matching_pattern(path).is_some()
+    }
+
+    /// Get the pattern that decides `path` is ignored, if any
+    pub fn matching_pattern(&self, path: &Path) -> Option<&str> {
         // The ignore crate expects relative paths from the base
         let relative_path = if path.is_absolute() {
             match path.strip_prefix(&self.base_path) {
                 Ok(rel) => rel,
-                Err(_) => return false, // Path outside base directory
+                Err(_) => return None, // Path outside base directory
             }
         } else {
             path
         };
 
+        let is_dir = path.is_dir();
+        match self.gitignore.matched(relative_path, is_dir) {
+            Match::Ignore(_) if self.ignored_by_repo(path, is_dir) => None,
+            Match::Ignore(glob) => Some(glob.original()),
+            _ => None,
+        }
+    }
+
+    /// Check if the repository's own rules ignore `path`, when they are respected
+    fn ignored_by_repo(&self, path: &Path, is_dir: bool) -> bool {
+        self.repo_rules.as_ref().is_some_and(|rules| {
+            let path = if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                self.base_path.join(path)
+            };
+            rules
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .is_ignored(&path, is_dir)
+        })
+    }
+
+    /// Check if a path, or any directory above it up to the base, matches the patterns
+    ///
+    /// Unlike [`is_ignored`](Self::is_ignored), a file inside a matched directory counts
+    /// as matched too, the way git treats the contents of an ignored directory.
+    pub fn covers(&self, path: &Path) -> bool {
+        let relative_path = if path.is_absolute() {
+            match path.strip_prefix(&self.base_path) {
+                Ok(rel) => rel,
+                Err(_) => return false,
+            }
+        } else {
+            path.strip_prefix(".").unwrap_or(path)
+        };
+        if relative_path.as_os_str().is_empty() {
+            return false;
+        }
+
         self.gitignore
-            .matched(relative_path, path.is_dir())
+            .matched_path_or_any_parents(relative_path, path.is_dir())
             .is_ignore()
     }
 
     /// Find all files matching the patterns in a directory
     pub fn find_matching_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
-        use ignore::WalkBuilder;
-
-        let mut matching_files = Vec::new();
+        self.find_matching_files_to_depth(root, self.max_depth)
+    }
 
+    /// Find all files matching the patterns in a directory, searching at most
+    /// `max_depth` levels below it instead of the matcher's own limit
+    ///
+    /// Lets one matcher serve several directories that each get their own limit.
+    pub fn find_matching_files_to_depth(
+        &self,
+        root: &Path,
+        max_depth: Option<usize>,
+    ) -> Result<Vec<PathBuf>> {
         // Create a walker that respects our patterns
-        let walker = WalkBuilder::new(root)
+        let mut builder = ignore::WalkBuilder::new(root);
+        builder
             .standard_filters(false) // Don't use default filters
             .hidden(false) // Include hidden files
             .parents(false) // Don't look for .gitignore in parent dirs
             .git_ignore(false) // Don't use .gitignore files
             .git_global(false) // Don't use global gitignore
             .git_exclude(false) // Don't use .git/info/exclude
-            .build();
-
-        for entry in walker {
-            let entry = entry?;
-            let path = entry.path();
-
-            // Check if this path matches our patterns
-            // Only include files, not directories (to match git ls-files behavior)
-            if path.is_file() && self.is_ignored(path) {
-                matching_files.push(path.to_path_buf());
-            }
+            .max_depth(max_depth)
+            .filter_entry(|entry| !path_utils::is_internal_dir_name(entry.file_name()));
+
+        // Check if each path matches our patterns
+        // Only include files, not directories (to match git ls-files behavior)
+        let (matching_files, errors) =
+            path_utils::walk_selected(&builder, root, self.parallel_walk_threshold, |entry| {
+                let path = entry.path();
+                path.is_file() && self.is_ignored(path)
+            });
+        if let Some(error) = errors.into_iter().next() {
+            return Err(error.into());
         }
 
         Ok(matching_files)
@@ -79,14 +163,22 @@ impl PatternMatcher {
 
 /// Find files matching gitignore-style patterns
 /// This provides consistent behavior whether in a git repo or not
-pub fn find_files_matching_patterns(base_path: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
-    let matcher = PatternMatcher::new(base_path, patterns)?;
+///
+/// With `respect_gitignore`, files the repository's own ignore rules cover are left out.
+pub fn find_files_matching_patterns(
+    base_path: &Path,
+    patterns: &[String],
+    respect_gitignore: bool,
+    parallel_walk_threshold: usize,
+) -> Result<Vec<PathBuf>> {
+    let matcher = PatternMatcher::new(base_path, patterns, respect_gitignore)?
+        .with_parallel_walk_threshold(parallel_walk_threshold);
     matcher.find_matching_files(base_path)
 }
 
 /// Check if a file matches any of the given patterns
 pub fn matches_patterns(base_path: &Path, file_path: &Path, patterns: &[String]) -> Result<bool> {
-    let matcher = PatternMatcher::new(base_path, patterns)?;
+    let matcher = PatternMatcher::new(base_path, patterns, false)?;
     Ok(matcher.is_ignored(file_path))
 }
 
@@ -109,7 +201,7 @@ mod tests {
 
         // Test single wildcard
         let patterns = vec!["*.log".to_string()];
-        let matcher = PatternMatcher::new(base, &patterns).unwrap();
+        let matcher = PatternMatcher::new(base, &patterns, false).unwrap();
 
         assert!(matcher.is_ignored(&base.join("test.log")));
         assert!(!matcher.is_ignored(&base.join("src/main.rs")));
@@ -118,7 +210,7 @@ mod tests {
 
         // Test recursive wildcard
         let patterns = vec!["**/*.log".to_string()];
-        let matcher = PatternMatcher::new(base, &patterns).unwrap();
+        let matcher = PatternMatcher::new(base, &patterns, false).unwrap();
 
         assert!(matcher.is_ignored(&base.join("test.log")));
         assert!(matcher.is_ignored(&base.join("src/test.log")));
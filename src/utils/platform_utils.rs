@@ -1,7 +1,21 @@
+use crate::utils::path_utils;
 use crate::{platforms::CurrentPlatform, traits::PlatformHandler};
 use anyhow::Result;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Environment variable setting the value written with ignore markers
+pub const MARKER_VALUE_ENV_VAR: &str = "DBX_IGNORE_MARKER_VALUE";
+
+/// Value written with ignore markers unless one is configured
+pub const DEFAULT_MARKER_VALUE: &[u8] = b"1";
+
+/// Marker value given in `DBX_IGNORE_MARKER_VALUE`, if set to something non-empty
+pub fn marker_value_from_env() -> Option<Vec<u8>> {
+    std::env::var_os(MARKER_VALUE_ENV_VAR)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.into_encoded_bytes())
+}
 
 /// Check if a path has any of the target ignore attributes
 pub fn has_any_ignore_attribute(path: &Path) -> bool {
@@ -10,15 +24,226 @@ pub fn has_any_ignore_attribute(path: &Path) -> bool {
         .any(|attr| CurrentPlatform::has_attribute(path, attr).unwrap_or(false))
 }
 
+/// Which target attributes are present on a path
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct AttributeBreakdown {
+    /// Target attributes physically present on the path
+    pub present: Vec<&'static str>,
+    /// Present attributes that aren't the variant this system expects (legacy or mismatched)
+    pub unexpected: Vec<&'static str>,
+}
+
+impl AttributeBreakdown {
+    pub fn has_unexpected(&self) -> bool {
+        !self.unexpected.is_empty()
+    }
+}
+
+/// Report which target attributes are present on a path
+pub fn attribute_breakdown(path: &Path) -> AttributeBreakdown {
+    attribute_breakdown_with::<CurrentPlatform>(path)
+}
+
+/// Report which target attributes are present on a path, using a specific handler
+pub fn attribute_breakdown_with<H: PlatformHandler>(path: &Path) -> AttributeBreakdown {
+    let present: Vec<&'static str> = H::get_target_attributes()
+        .iter()
+        .copied()
+        .filter(|attr| H::attribute_present(path, attr).unwrap_or(false))
+        .collect();
+    let unexpected = present
+        .iter()
+        .copied()
+        .filter(|attr| !H::is_expected_attribute(attr))
+        .collect();
+
+    AttributeBreakdown {
+        present,
+        unexpected,
+    }
+}
+
+/// Find the closest of `path` and its ancestors that carries an ignore marker
+///
+/// Anything below such a directory is already excluded from sync, so marking or
+/// watching inside it has no visible effect.
+pub fn find_ignored_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|ancestor| has_any_ignore_attribute(ancestor))
+        .map(Path::to_path_buf)
+}
+
+/// Roots of the Dropbox folders detected on this system, sorted and without duplicates
+///
+/// Detection is best effort: a folder the desktop client doesn't list anywhere
+/// readable is missed.
+pub fn detect_dropbox_roots() -> Vec<PathBuf> {
+    let mut roots = CurrentPlatform::dropbox_roots();
+    roots.sort();
+    roots.dedup();
+    roots
+}
+
+/// Dropbox folder paths listed in one of the desktop client's `info.json` files
+///
+/// The file maps each linked account (`personal`, `business`) to an object whose
+/// `path` is that account's folder. A missing or unreadable file lists none.
+pub fn dropbox_roots_from_info_json(info_file: &Path) -> Vec<PathBuf> {
+    let Ok(info) = crate::utils::json_utils::read_json::<serde_json::Value>(info_file) else {
+        return Vec::new();
+    };
+    info.as_object()
+        .into_iter()
+        .flat_map(|accounts| accounts.values())
+        .filter_map(|account| account.get("path")?.as_str())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// The Dropbox folder containing `path`, from the detected roots or the folder's own layout
+pub fn dropbox_root_of(path: &Path) -> Option<PathBuf> {
+    // Compare resolved paths too, in case a root or `path` is reached through a symlink
+    let resolved = path.canonicalize().ok();
+    let contains = |root: &Path| {
+        path.starts_with(root)
+            || resolved.as_ref().is_some_and(|resolved| {
+                root.canonicalize()
+                    .is_ok_and(|root| resolved.starts_with(root))
+            })
+    };
+    detect_dropbox_roots()
+        .into_iter()
+        .find(|root| contains(root))
+        .or_else(|| CurrentPlatform::dropbox_folder(path))
+}
+
+/// Find every marked file and directory under `root`, in walk order
+///
+/// Reads markers from disk rather than the tracked state, so paths marked by hand or
+/// by other tools are included. Marked directories are walked into like any other.
+pub fn find_marked_files(
+    root: &Path,
+    recurse_submodules: bool,
+    max_depth: Option<usize>,
+    parallel_walk_threshold: usize,
+) -> Result<Vec<PathBuf>> {
+    find_marked_files_among(
+        root,
+        std::slice::from_ref(&root.to_path_buf()),
+        recurse_submodules,
+        max_depth,
+        parallel_walk_threshold,
+    )
+}
+
+/// Find the marked entries at or below `paths`, and the marked directories leading to them
+///
+/// The walk starts at `root`, so submodules and `max_depth` apply as they would to a walk
+/// of all of `root`, but only the directories leading to `paths` are visited on the way.
+pub fn find_marked_files_among(
+    root: &Path,
+    paths: &[PathBuf],
+    recurse_submodules: bool,
+    max_depth: Option<usize>,
+    parallel_walk_threshold: usize,
+) -> Result<Vec<PathBuf>> {
+    let targets = paths.to_vec();
+    let mut builder = ignore::WalkBuilder::new(root);
+    builder
+        .standard_filters(false)
+        .hidden(false)
+        .git_ignore(false)
+        .git_global(false)
+        .git_exclude(false)
+        .max_depth(max_depth)
+        .filter_entry(move |entry| {
+            // Skip .git and the state directory, and submodules unless asked to recurse
+            !path_utils::is_internal_dir_name(entry.file_name())
+                && (recurse_submodules || !path_utils::is_nested_repo_root(entry))
+                && path_utils::leads_to_any(entry.path(), &targets)
+        });
+
+    // Unreadable entries can't be checked for markers; they are skipped
+    let (marked_files, _) =
+        path_utils::walk_selected(&builder, root, parallel_walk_threshold, |entry| {
+            has_any_ignore_attribute(entry.path())
+        });
+    Ok(marked_files)
+}
+
+/// A `--where-xattr` condition: an attribute a path must carry, optionally with a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeCondition {
+    /// Attribute name as stored, e.g. `user.archive` on Linux or `archive` on macOS
+    pub name: String,
+    /// Value the attribute must hold exactly (any value if `None`)
+    pub value: Option<String>,
+}
+
+impl std::str::FromStr for AttributeCondition {
+    type Err = anyhow::Error;
+
+    /// Parse `NAME` or `NAME=VALUE`
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, value) = match s.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (s, None),
+        };
+        if name.is_empty() {
+            anyhow::bail!(
+                "missing attribute name in '{}' (expected NAME or NAME=VALUE)",
+                s
+            );
+        }
+        Ok(Self {
+            name: name.to_string(),
+            value,
+        })
+    }
+}
+
+impl AttributeCondition {
+    /// Check if `path` carries the attribute, holding the expected value if one is set
+    pub fn matches(&self, path: &Path) -> bool {
+        match CurrentPlatform::read_attribute(path, &self.name) {
+            Ok(Some(actual)) => self
+                .value
+                .as_ref()
+                .is_none_or(|value| actual == value.as_bytes()),
+            _ => false,
+        }
+    }
+}
+
+/// Check if the sync client currently syncs `path`
+///
+/// Paths that are already excluded, by their own marker or a marked parent folder,
+/// or that the client doesn't manage or only keeps online, don't count.
+pub fn is_synced(path: &Path) -> bool {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    CurrentPlatform::is_synced_by_client(&path) && find_ignored_ancestor(&path).is_none()
+}
+
+/// Keep only the paths `is_synced` accepts, returning how many were dropped
+pub fn retain_synced(paths: &mut Vec<PathBuf>, is_synced: impl Fn(&Path) -> bool) -> usize {
+    let before = paths.len();
+    paths.retain(|path| is_synced(path));
+    before - paths.len()
+}
+
 /// Add all target attributes to a path, optionally returning the count
 /// If skip_existing is true, will skip attributes that already exist
-pub fn add_ignore_attributes(path: &Path, skip_existing: bool) -> Result<usize> {
+///
+/// Attributes are written with `value`, usually [`RepoConfig::marker_value`].
+///
+/// [`RepoConfig::marker_value`]: crate::core::repo_config::RepoConfig::marker_value
+pub fn add_ignore_attributes(path: &Path, skip_existing: bool, value: &[u8]) -> Result<usize> {
     let mut count = 0;
     for attr in CurrentPlatform::get_target_attributes() {
         if skip_existing && CurrentPlatform::has_attribute(path, attr)? {
             continue;
         }
-        CurrentPlatform::add_attribute(path, attr)?;
+        CurrentPlatform::add_attribute(path, attr, value)?;
         count += 1;
     }
     Ok(count)
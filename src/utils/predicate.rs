@@ -0,0 +1,326 @@
+use anyhow::{Result, anyhow};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::utils::parse_utils;
+
+/// A parsed `--where` expression, evaluated per file during selection
+///
+/// Grammar (lowest to highest precedence):
+///
+/// ```text
+/// expr       := and ("||" and)*
+/// and        := unary ("&&" unary)*
+/// unary      := "!" unary | "(" expr ")" | comparison
+/// comparison := field op value
+/// ```
+///
+/// Fields: `size` (e.g. `size>100MB`), `ext` (e.g. `ext==mp4`), `name` (glob,
+/// e.g. `name=="*.log"`), `age` (time since last modification, e.g. `age>30d`)
+/// and `type` (`file` or `dir`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+    Not(Box<Predicate>),
+    Size(Comparison, u64),
+    Age(Comparison, Duration),
+    Ext { equal: bool, ext: String },
+    Name { equal: bool, pattern: glob::Pattern },
+    Type { equal: bool, dir: bool },
+}
+
+/// Comparison operator of a single predicate term
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Eq => "==",
+            Comparison::Ne => "!=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+
+    fn apply<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            Comparison::Eq => left == right,
+            Comparison::Ne => left != right,
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+        }
+    }
+}
+
+impl Predicate {
+    /// Parse an expression such as `size>100MB && ext==mp4`
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err(anyhow!("Invalid --where expression: expression is empty"));
+        }
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let predicate = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(anyhow!(
+                "Invalid --where expression: unexpected '{}'",
+                token.describe()
+            ));
+        }
+        Ok(predicate)
+    }
+
+    /// Evaluate the predicate against a path; unreadable paths never match
+    pub fn matches(&self, path: &Path) -> bool {
+        match self {
+            Predicate::And(left, right) => left.matches(path) && right.matches(path),
+            Predicate::Or(left, right) => left.matches(path) || right.matches(path),
+            Predicate::Not(inner) => !inner.matches(path),
+            Predicate::Size(op, size) => std::fs::metadata(path)
+                .map(|metadata| op.apply(metadata.len(), *size))
+                .unwrap_or(false),
+            Predicate::Age(op, age) => std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .map(|modified| {
+                    let elapsed = SystemTime::now()
+                        .duration_since(modified)
+                        .unwrap_or_default();
+                    op.apply(elapsed, *age)
+                })
+                .unwrap_or(false),
+            Predicate::Ext { equal, ext } => {
+                let matched = path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| e.eq_ignore_ascii_case(ext));
+                matched == *equal
+            }
+            Predicate::Name { equal, pattern } => {
+                let matched = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| pattern.matches(n));
+                matched == *equal
+            }
+            Predicate::Type { equal, dir } => (path.is_dir() == *dir) == *equal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Op(Comparison),
+    Word(String),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::And => "&&".to_string(),
+            Token::Or => "||".to_string(),
+            Token::Not => "!".to_string(),
+            Token::LParen => "(".to_string(),
+            Token::RParen => ")".to_string(),
+            Token::Op(op) => op.symbol().to_string(),
+            Token::Word(word) => word.clone(),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Op(Comparison::Eq), 2),
+            ('!', Some('=')) => (Token::Op(Comparison::Ne), 2),
+            ('<', Some('=')) => (Token::Op(Comparison::Le), 2),
+            ('>', Some('=')) => (Token::Op(Comparison::Ge), 2),
+            ('<', _) => (Token::Op(Comparison::Lt), 1),
+            ('>', _) => (Token::Op(Comparison::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('"', _) | ('\'', _) => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&q| q == c)
+                    .ok_or_else(|| anyhow!("Invalid --where expression: unterminated quote"))?;
+                let word: String = chars[i + 1..i + 1 + end].iter().collect();
+                (Token::Word(word), end + 2)
+            }
+            ('&', _) | ('|', _) | ('=', _) => {
+                return Err(anyhow!(
+                    "Invalid --where expression: unexpected '{}' (use &&, || or ==)",
+                    c
+                ));
+            }
+            _ => {
+                let len = chars[i..]
+                    .iter()
+                    .position(|&w| w.is_whitespace() || "&|=!<>()\"'".contains(w))
+                    .unwrap_or(chars.len() - i);
+                (Token::Word(chars[i..i + len].iter().collect()), len)
+            }
+        };
+        tokens.push(token);
+        i += len;
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Predicate> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Predicate::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Predicate> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Predicate::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Predicate> {
+        match self.next() {
+            Some(Token::Not) => Ok(Predicate::Not(Box::new(self.parse_unary()?))),
+            Some(Token::LParen) => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => Err(anyhow!("Invalid --where expression: missing ')'")),
+                }
+            }
+            Some(Token::Word(field)) => self.parse_comparison(&field),
+            Some(token) => Err(anyhow!(
+                "Invalid --where expression: expected a condition, found '{}'",
+                token.describe()
+            )),
+            None => Err(anyhow!(
+                "Invalid --where expression: expected a condition at end of input"
+            )),
+        }
+    }
+
+    fn parse_comparison(&mut self, field: &str) -> Result<Predicate> {
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            _ => {
+                return Err(anyhow!(
+                    "Invalid --where expression: expected an operator after '{}'",
+                    field
+                ));
+            }
+        };
+        let value = match self.next() {
+            Some(Token::Word(value)) => value,
+            _ => {
+                return Err(anyhow!(
+                    "Invalid --where expression: expected a value after '{}{}'",
+                    field,
+                    op.symbol()
+                ));
+            }
+        };
+
+        let equality = |op: Comparison| match op {
+            Comparison::Eq => Ok(true),
+            Comparison::Ne => Ok(false),
+            _ => Err(anyhow!(
+                "Invalid --where expression: '{}' only supports == and !=",
+                field
+            )),
+        };
+
+        match field {
+            "size" => Ok(Predicate::Size(op, parse_utils::parse_size(&value)?)),
+            "age" => Ok(Predicate::Age(op, parse_utils::parse_duration(&value)?)),
+            "ext" => Ok(Predicate::Ext {
+                equal: equality(op)?,
+                ext: value.trim_start_matches('.').to_string(),
+            }),
+            "name" => Ok(Predicate::Name {
+                equal: equality(op)?,
+                pattern: glob::Pattern::new(&value).map_err(|e| {
+                    anyhow!(
+                        "Invalid --where expression: bad name pattern '{}': {}",
+                        value,
+                        e
+                    )
+                })?,
+            }),
+            "type" => {
+                let dir = match value.as_str() {
+                    "file" | "f" => false,
+                    "dir" | "d" => true,
+                    _ => {
+                        return Err(anyhow!(
+                            "Invalid --where expression: type must be 'file' or 'dir', got '{}'",
+                            value
+                        ));
+                    }
+                };
+                Ok(Predicate::Type {
+                    equal: equality(op)?,
+                    dir,
+                })
+            }
+            _ => Err(anyhow!(
+                "Invalid --where expression: unknown field '{}' (expected size, ext, name, age or type)",
+                field
+            )),
+        }
+    }
+}
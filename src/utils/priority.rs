@@ -0,0 +1,53 @@
+use anyhow::Result;
+
+/// Niceness `--nice` lowers the process to on Unix
+pub const NICE_LEVEL: i32 = 10;
+
+/// Lower the scheduling priority of the current process for `--nice`
+///
+/// On Unix the niceness is raised to at least [`NICE_LEVEL`] with `setpriority`; a
+/// process that is already nicer is left alone. On Windows the process moves to the
+/// below-normal priority class with `SetPriorityClass`. Elsewhere this does nothing.
+///
+/// Linux applies niceness per thread and new threads inherit it from their creator,
+/// so this must run before any worker threads are spawned.
+pub fn lower_priority() -> Result<()> {
+    #[cfg(unix)]
+    {
+        if niceness() >= NICE_LEVEL {
+            return Ok(());
+        }
+        // SAFETY: plain syscall on the calling process, no pointers involved
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, NICE_LEVEL) };
+        if result != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+
+        #[link(name = "kernel32")]
+        unsafe extern "system" {
+            fn GetCurrentProcess() -> isize;
+            fn SetPriorityClass(process: isize, priority_class: u32) -> i32;
+        }
+
+        // SAFETY: GetCurrentProcess returns a pseudo-handle that needs no cleanup
+        let result = unsafe { SetPriorityClass(GetCurrentProcess(), BELOW_NORMAL_PRIORITY_CLASS) };
+        if result == 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Current niceness of the calling thread (Unix)
+#[cfg(unix)]
+pub fn niceness() -> i32 {
+    // getpriority can only fail for an invalid target, which the calling process isn't
+    // SAFETY: plain syscall on the calling process, no pointers involved
+    unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) }
+}
@@ -0,0 +1,45 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Token bucket limiting how many operations run per second, shared across threads
+///
+/// The bucket holds at most one token, so operations are spread evenly instead of
+/// running in bursts: `n` operations take at least `(n - 1) / ops_per_sec` seconds.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(ops_per_sec: u32) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / f64::from(ops_per_sec.max(1))),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Block until the next operation is allowed to run
+    pub fn acquire(&self) {
+        let wait_until = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            // An idle limiter doesn't bank unused slots
+            let slot = (*next_slot).max(now);
+            *next_slot = slot + self.interval;
+            slot
+        };
+
+        let now = Instant::now();
+        if wait_until > now {
+            std::thread::sleep(wait_until - now);
+        }
+    }
+}
+
+/// Wait for `limiter`, if any, before a mutating operation
+pub fn throttle(limiter: Option<&RateLimiter>) {
+    if let Some(limiter) = limiter {
+        limiter.acquire();
+    }
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::utils::parse_utils::{format_size, parse_duration};
+use dbx_ignore::utils::platform_utils;
+use filetime::FileTime;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+fn set_age(path: &std::path::Path, age: Duration) {
+    let mtime = FileTime::from_system_time(SystemTime::now() - age);
+    filetime::set_file_mtime(path, mtime).expect("Failed to set mtime");
+}
+
+#[test]
+fn test_older_than_marks_only_old_files() {
+    let env = TestEnvironment::new();
+    let old = env.create_file("old.bin", "old content");
+    let fresh = env.create_file("fresh.bin", "fresh content");
+    let nested_dir = env.create_dir("archive");
+    let nested = nested_dir.join("older.bin");
+    std::fs::write(&nested, "older content").unwrap();
+
+    set_age(&old, Duration::from_secs(40 * 24 * 60 * 60));
+    set_age(&nested, Duration::from_secs(90 * 24 * 60 * 60));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--older-than", "30d"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Age filter: 2 files"));
+
+    assert!(platform_utils::has_any_ignore_attribute(&old));
+    assert!(platform_utils::has_any_ignore_attribute(&nested));
+    assert!(!platform_utils::has_any_ignore_attribute(&fresh));
+}
+
+#[test]
+fn test_older_than_dry_run_filters_explicit_files() {
+    let env = TestEnvironment::new();
+    let old = env.create_file("old.txt", "old");
+    let fresh = env.create_file("fresh.txt", "fresh");
+    set_age(&old, Duration::from_secs(3 * 60 * 60));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--dry-run", "--older-than", "1h", "old.txt", "fresh.txt"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Age filter: 1 files"));
+    assert!(!platform_utils::has_any_ignore_attribute(&old));
+    assert!(!platform_utils::has_any_ignore_attribute(&fresh));
+}
+
+#[test]
+fn test_since_marks_only_recently_modified_ignored_files() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["*.log"]);
+    let stale = env.create_file("stale.log", "old build");
+    let recent = env.create_file("recent.log", "new build");
+    set_age(&stale, Duration::from_secs(3 * 60 * 60));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .env("NO_COLOR", "1")
+        .args(["--since", "1h"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Since filter: skipped 1 path(s)"));
+    assert!(platform_utils::has_any_ignore_attribute(&recent));
+    assert!(!platform_utils::has_any_ignore_attribute(&stale));
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("45").unwrap(), Duration::from_secs(45));
+    assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+    assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+    assert_eq!(
+        parse_duration("30d").unwrap(),
+        Duration::from_secs(2_592_000)
+    );
+    assert_eq!(parse_duration("1w").unwrap(), Duration::from_secs(604_800));
+    assert!(parse_duration("d").is_err());
+    assert!(parse_duration("3y").is_err());
+    let err = parse_duration("999999999999999999d").unwrap_err();
+    assert!(err.to_string().contains("out of range"));
+}
+
+#[test]
+fn test_format_size() {
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(1536), "1.5 KB");
+    assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+}
+
+#[test]
+fn test_parse_size() {
+    use dbx_ignore::utils::parse_utils::parse_size;
+
+    assert_eq!(parse_size("512").unwrap(), 512);
+    assert_eq!(parse_size("100KB").unwrap(), 100 * 1024);
+    assert_eq!(parse_size("1.5g").unwrap(), 3 * 512 * 1024 * 1024);
+    assert!(parse_size("MB").is_err());
+    assert!(parse_size("10XB").is_err());
+}
@@ -98,6 +98,7 @@ fn test_cli_with_existing_file() {
     let test_file = env.create_file("test.txt", "test content");
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args(["--dry-run", test_file.to_str().unwrap()])
         .output()
         .expect("Failed to execute binary");
@@ -114,6 +115,7 @@ fn test_cli_with_directory() {
     let test_dir = env.create_dir("test_directory");
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args(["--dry-run", test_dir.to_str().unwrap()])
         .output()
         .expect("Failed to execute binary");
@@ -131,6 +133,7 @@ fn test_cli_with_multiple_files() {
     let test_file2 = env.create_file("test2.txt", "content2");
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args([
             "--dry-run",
             test_file1.to_str().unwrap(),
@@ -217,3 +220,801 @@ fn test_cli_binary_exists() {
 
     assert!(output.is_ok(), "Binary should be executable");
 }
+
+#[cfg(unix)]
+#[test]
+fn test_cli_files0_from_handles_newlines_in_names() {
+    let env = TestEnvironment::new();
+
+    // A filename with an embedded newline and glob characters, taken literally
+    let weird = env.create_file("line1\nline2[*].txt", "content");
+    env.create_file("plain.txt", "content");
+    env.create_file("line1", "decoy");
+
+    let list = env.path().join("list0");
+    std::fs::write(&list, b"line1\nline2[*].txt\0plain.txt\0").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--files0-from", "list0"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 files processed"));
+
+    #[cfg(target_os = "linux")]
+    {
+        assert!(
+            xattr::get(&weird, "user.com.dropbox.ignored")
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            xattr::get(env.path().join("line1"), "user.com.dropbox.ignored")
+                .unwrap()
+                .is_none()
+        );
+    }
+    let _ = weird;
+}
+
+#[test]
+fn test_cli_reads_paths_from_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let env = TestEnvironment::new();
+    env.create_dir("logs");
+    let relative = env.create_file("logs/app.log", "log");
+    let absolute = env.create_file("build.log", "log");
+    let untouched = env.create_file("other.log", "log");
+
+    let run = |input: String| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .arg("-")
+            .current_dir(env.path())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute binary");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    };
+
+    // Relative and absolute paths, padded with whitespace and blank lines
+    let output = run(format!("  logs/app.log \n\n{}\r\n", absolute.display()));
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 files processed"), "{}", stdout);
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+        assert!(has_any_ignore_attribute(&relative));
+        assert!(has_any_ignore_attribute(&absolute));
+        assert!(!has_any_ignore_attribute(&untouched));
+    }
+    let _ = (relative, untouched);
+
+    // Paths are taken literally, and a missing one fails as on the command line
+    let output = run("*.log\n".to_string());
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Path not found: *.log"), "{}", stderr);
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+fn test_cli_null_separates_stdin_and_list_output() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let env = TestEnvironment::new();
+    env.create_file("line\nbreak.log", "log");
+    env.create_file("plain.log", "log");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["-0", "-"])
+        .current_dir(env.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute binary");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"line\nbreak.log\0plain.log\0")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--list", "-0"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"line\nbreak.log\0plain.log\0");
+}
+
+#[test]
+fn test_cli_files0_from_conflicts_with_file_args() {
+    let env = TestEnvironment::new();
+    env.create_file("a.txt", "content");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--files0-from", "-", "a.txt"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(!output.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_dry_run_warns_on_read_only_state_dir() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let env = TestEnvironment::new();
+    env.create_file("app.log", "log");
+    let state_dir = env.create_dir(".dbx-ignore");
+    std::fs::set_permissions(&state_dir, std::fs::Permissions::from_mode(0o555)).unwrap();
+
+    // Privileged users bypass directory permissions, so there's nothing to detect
+    if tempfile::NamedTempFile::new_in(&state_dir).is_ok() {
+        std::fs::set_permissions(&state_dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        return;
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--dry-run", "app.log"])
+        .output()
+        .expect("Failed to execute binary");
+
+    std::fs::set_permissions(&state_dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("State directory is not writable"));
+}
+
+#[test]
+fn test_cli_dry_run_warns_when_state_path_is_blocked() {
+    let env = TestEnvironment::new();
+    env.create_file("app.log", "log");
+    // A regular file where the state directory should be
+    env.create_file(".dbx-ignore", "not a directory");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--dry-run", "app.log"])
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("State directory is not writable"));
+    assert!(stderr.contains("not a directory"));
+
+    // A healthy tree produces no warning
+    let clean = TestEnvironment::new();
+    clean.create_file("app.log", "log");
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(clean.path())
+        .args(["--dry-run", "app.log"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("State directory"));
+}
+
+#[test]
+fn test_cli_silent_produces_no_output() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["*.log"]);
+    env.create_file("app.log", "log");
+
+    // Git mode from the no-arguments path, which normally announces itself
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--silent", "--dry-run"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+
+    // No-op: nothing to unwatch
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--silent", "--unwatch"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_cli_silent_conflicts_with_verbose() {
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--silent", "--verbose", "file.txt"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_silent_checks_answer_through_exit_code() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["*.log"]);
+    env.create_file("app.log", "log");
+
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(env.path())
+            .arg("--silent")
+            .args(args)
+            .output()
+            .expect("Failed to execute binary")
+    };
+
+    // app.log is git-ignored but unmarked, so both checks answer "no"
+    for args in [&["--check"][..], &["--is-marked", "app.log"]] {
+        let output = run(args);
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(output.stdout.is_empty(), "{:?}", args);
+        assert!(output.stderr.is_empty(), "{:?}", args);
+    }
+
+    // Nothing tracked yet, so there is no drift to report
+    for args in [&["--drift"][..], &["--verify"], &["--detect-conflicts"]] {
+        let output = run(args);
+        assert!(output.status.success(), "{:?}", args);
+        assert!(output.stdout.is_empty(), "{:?}", args);
+        assert!(output.stderr.is_empty(), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_cli_silent_conflicts_with_reports() {
+    for args in [
+        &["--list"][..],
+        &["--status"],
+        &["--log"],
+        &["--doctor"],
+        &["--format", "{path}", "file.txt"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .arg("--silent")
+            .args(args)
+            .output()
+            .expect("Failed to execute binary");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_cli_refuses_paths_outside_repo_unless_allowed() {
+    let env = TestEnvironment::new();
+    env.create_dir("repo");
+    env.create_file("outside.txt", "outside");
+    let repo = env.path().join("repo");
+    git2::Repository::init(&repo).unwrap();
+
+    let run = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .args(["--dry-run", "../outside.txt"])
+            .args(extra)
+            .current_dir(&repo)
+            .output()
+            .expect("Failed to execute binary")
+    };
+
+    let output = run(&[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--allow-outside"), "{}", stderr);
+
+    let output = run(&["--allow-outside"]);
+    assert!(output.status.success());
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+fn test_cli_list_prints_marked_paths() {
+    let env = TestEnvironment::new();
+    env.create_dir("build");
+    env.create_file("build/out.o", "object");
+    env.create_file("app.log", "log");
+    env.create_file("notes.txt", "notes");
+
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .args(args)
+            .env("NO_COLOR", "1")
+            .current_dir(env.path())
+            .output()
+            .expect("Failed to execute binary")
+    };
+    assert!(
+        run(&["--quiet", "--tag", "logs", "app.log"])
+            .status
+            .success()
+    );
+    assert!(run(&["--quiet", "build"]).status.success());
+
+    let output = run(&["--list"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "app.log\nbuild\n"
+    );
+
+    let output = run(&["--list", "--verbose"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "app.log [logs]\nbuild\n"
+    );
+}
+
+#[test]
+fn test_current_dir_error_mapping() {
+    use dbx_ignore::utils::path_utils::explain_current_dir_error;
+    use std::io;
+
+    let removed = io::Error::new(io::ErrorKind::NotFound, "No such file or directory");
+    let error = explain_current_dir_error(Err(removed)).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("current directory no longer exists"),
+        "{}",
+        error
+    );
+
+    let denied = io::Error::new(io::ErrorKind::PermissionDenied, "Permission denied");
+    let error = explain_current_dir_error(Err(denied)).unwrap_err();
+    assert!(format!("{:#}", error).contains("Failed to get current directory"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_cli_reports_removed_current_dir() {
+    let env = TestEnvironment::new();
+    let doomed = env.create_dir("doomed");
+
+    // Remove the directory from under the shell before running the binary in it
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg("cd \"$1\" && rmdir \"$1\" && exec \"$2\" --dry-run file.txt")
+        .arg("sh")
+        .arg(&doomed)
+        .arg(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .output()
+        .expect("Failed to execute shell");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("current directory no longer exists"),
+        "{}",
+        stderr
+    );
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+fn test_cli_skips_own_binary_inside_tree() {
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let env = TestEnvironment::new();
+    let tools = env.create_dir("tools");
+    let binary = tools.join("dbx-ignore");
+    std::fs::copy(env!("CARGO_BIN_EXE_dbx-ignore"), &binary).unwrap();
+    let log = env.create_file("app.log", "log");
+
+    let output = Command::new(&binary)
+        .args(["--verbose", "tools/dbx-ignore", "tools", "app.log"])
+        .env("NO_COLOR", "1")
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Skipped 2 path(s) holding the running dbx-ignore binary"),
+        "{}",
+        stdout
+    );
+
+    assert!(has_any_ignore_attribute(&log));
+    assert!(!has_any_ignore_attribute(&binary));
+    assert!(!has_any_ignore_attribute(&tools));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_skips_symlinks_unless_following() {
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let env = TestEnvironment::new();
+    let project = env.create_dir("project");
+    let outside = env.create_file("outside.bin", "data");
+    std::os::unix::fs::symlink(&outside, project.join("link.bin")).unwrap();
+    let local = project.join("local.log");
+    std::fs::write(&local, "log").unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .args(args)
+            .env("NO_COLOR", "1")
+            .current_dir(&project)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let stdout = run(&["link.bin", "local.log"]);
+    assert!(
+        stdout.contains("Symlinks: skipped 1 symlink(s)"),
+        "{}",
+        stdout
+    );
+    assert!(has_any_ignore_attribute(&local));
+    assert!(!has_any_ignore_attribute(&outside));
+
+    run(&["--follow-symlinks", "link.bin"]);
+    assert!(has_any_ignore_attribute(&outside));
+}
+
+#[test]
+fn test_cli_format_template_renders_each_path() {
+    use dbx_ignore::platforms::CurrentPlatform;
+    use dbx_ignore::traits::PlatformHandler;
+
+    let env = TestEnvironment::new();
+    env.create_file("a.log", "log");
+    let marked = env.create_file("b.log", "log");
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&marked, false, b"1").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args([
+            "--format",
+            "{action} {path} changed={changed} attrs={attrs} {{{error}}}",
+        ])
+        .args(["a.log", "b.log"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines: Vec<&str> = stdout.lines().collect();
+    lines.sort();
+    let attrs = CurrentPlatform::get_target_attributes().len();
+    assert_eq!(
+        lines,
+        vec![
+            format!("ignore a.log changed=true attrs={} {{}}", attrs),
+            "ignore b.log changed=false attrs=0 {}".to_string(),
+        ]
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--format", "{path} {size}", "a.log"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown placeholder '{size}'"));
+}
+
+#[test]
+fn test_cli_doctor_reports_setup() {
+    use dbx_ignore::platforms::CurrentPlatform;
+    use dbx_ignore::traits::PlatformHandler;
+
+    let env = TestEnvironment::new();
+    let project = env.create_dir("project");
+
+    let doctor = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .arg("--doctor")
+            .env("NO_COLOR", "1")
+            .current_dir(&project)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let report = doctor();
+    assert!(
+        report.contains(&format!("Platform: {}", CurrentPlatform::platform_name())),
+        "{}",
+        report
+    );
+    assert!(report.contains("Ignore mechanism:"), "{}", report);
+    assert!(report.contains("Attribute written:"), "{}", report);
+    assert!(
+        report.contains("Not inside a detected Dropbox folder"),
+        "{}",
+        report
+    );
+
+    // The desktop client keeps its cache at the root of the Dropbox folder
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    {
+        env.create_dir(".dropbox.cache");
+        let report = doctor();
+        assert!(
+            report.contains(&format!(
+                "Inside Dropbox folder: {}",
+                project.parent().unwrap().display()
+            )),
+            "{}",
+            report
+        );
+    }
+}
+
+#[test]
+fn test_cli_preset_selects_ecosystem_directories() {
+    use dbx_ignore::tracked_files::TrackedFiles;
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let env = TestEnvironment::new();
+    env.create_dir("node_modules/left-pad");
+    let dependency = env.create_file("node_modules/left-pad/index.js", "module");
+    env.create_dir("src");
+    let source = env.create_file("src/index.js", "app");
+    let manifest = env.create_file("package.json", "{}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--preset", "node", "--quiet"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    assert!(has_any_ignore_attribute(&dependency));
+    assert!(!has_any_ignore_attribute(&source));
+    assert!(!has_any_ignore_attribute(&manifest));
+    let tracked = TrackedFiles::load(env.path()).unwrap();
+    assert!(tracked.patterns.contains(&"**/node_modules/**".to_string()));
+
+    // Unknown presets are rejected
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--preset", "cobol"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+    assert!(!output.status.success());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_exit_codes_tell_partial_from_hard_failures() {
+    let env = TestEnvironment::new();
+    env.create_file("ok.log", "log");
+    // Linux refuses user.* attributes on FIFOs, so marking this one always fails
+    let status = Command::new("mkfifo")
+        .arg(env.path().join("pipe"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(env.path())
+            .args(args)
+            .output()
+            .expect("Failed to execute binary")
+            .status
+            .code()
+    };
+
+    assert_eq!(run(&["--quiet", "ok.log"]), Some(0));
+    assert_eq!(run(&["--quiet", "ok.log", "pipe"]), Some(2));
+    assert_eq!(run(&["--quiet", "--strict", "ok.log", "pipe"]), Some(1));
+    // Not a git repository
+    assert_eq!(run(&["--quiet", "--git"]), Some(1));
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+fn test_git_mode_runs_against_repo_path() {
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let repo = TestEnvironment::new();
+    repo.init_git_repo().unwrap();
+    repo.create_gitignore(&["*.log"]);
+    let log_file = repo.create_file("app.log", "log");
+    let elsewhere = TestEnvironment::new();
+
+    let run = |path: &std::path::Path| {
+        Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(elsewhere.path())
+            .args(["--quiet", "--git", "--repo"])
+            .arg(path)
+            .output()
+            .expect("Failed to execute binary")
+    };
+
+    let output = run(repo.path());
+    assert!(output.status.success(), "{:?}", output);
+    assert!(has_any_ignore_attribute(&log_file));
+
+    let output = run(&repo.path().join("app.log"));
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--repo path is not a directory"));
+
+    let output = run(elsewhere.path());
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("--repo path is not inside a git repository")
+    );
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+fn test_summary_reports_pattern_changes() {
+    let env = TestEnvironment::new();
+    env.create_file("app.log", "log");
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(env.path())
+            .args(args)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(run(&["*.log"]).contains("Patterns: 1 added, 0 already tracked"));
+    assert!(run(&["*.log"]).contains("Patterns: 0 added, 1 already tracked"));
+    assert!(run(&["--reset", "*.log", "*.tmp"]).contains("Patterns: 1 removed, 1 not tracked"));
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+fn test_count_only_prints_a_single_line() {
+    let env = TestEnvironment::new();
+    env.create_file("a.log", "log");
+    env.create_file("b.log", "log");
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(env.path())
+            .args(args)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(
+        run(&["--count-only", "a.log", "b.log"]),
+        "processed=2 operations=2 errors=0\n"
+    );
+
+    let stdout = run(&["--count-only", "--dry-run", "--reset", "a.log"]);
+    assert_eq!(stdout.lines().count(), 1, "{}", stdout);
+    assert!(stdout.starts_with("processed="));
+    assert!(stdout.contains("errors=0"));
+}
+
+#[test]
+fn test_min_size_keeps_only_large_ignored_files() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    env.create_gitignore(&["*.bin"]);
+    env.create_file("model.bin", &"x".repeat(4096));
+    env.create_file("tiny.bin", "x");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--git", "--min-size", "2K", "--dry-run", "--verbose"])
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("model.bin"), "{}", stdout);
+    assert!(!stdout.contains("tiny.bin"), "{}", stdout);
+    assert!(
+        stdout.contains("skipped 1 path(s) smaller than 2"),
+        "{}",
+        stdout
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--min-size", "lots", "model.bin"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(!output.status.success());
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+fn test_dropbox_roots_come_from_info_json() {
+    use dbx_ignore::utils::platform_utils::dropbox_roots_from_info_json;
+
+    let env = TestEnvironment::new();
+    let personal = env.create_dir("Dropbox");
+    let business = env.create_dir("Dropbox (Acme)");
+    let project = env.create_dir("Dropbox/project");
+    env.create_dir("home/.dropbox");
+    let info = env.create_file(
+        "home/.dropbox/info.json",
+        &serde_json::json!({
+            "personal": { "path": personal, "host": 1 },
+            "business": { "path": business, "is_team": true },
+        })
+        .to_string(),
+    );
+
+    let mut roots = dropbox_roots_from_info_json(&info);
+    roots.sort();
+    assert_eq!(roots, vec![personal.clone(), business.clone()]);
+    assert!(dropbox_roots_from_info_json(&env.path().join("missing.json")).is_empty());
+
+    // --doctor lists the folders and finds the one holding the current directory
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .arg("--doctor")
+        .env("HOME", env.path().join("home"))
+        .env("NO_COLOR", "1")
+        .current_dir(&project)
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        report.contains(&business.display().to_string()),
+        "{}",
+        report
+    );
+    assert!(
+        report.contains(&format!("Inside Dropbox folder: {}", personal.display())),
+        "{}",
+        report
+    );
+
+    // --status reports it too
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .arg("--status")
+        .env("HOME", env.path().join("home"))
+        .env("NO_COLOR", "1")
+        .current_dir(&project)
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        report.contains(&format!("Dropbox folder: ✓ {}", personal.display())),
+        "{}",
+        report
+    );
+}
@@ -0,0 +1,57 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::conflicts;
+use dbx_ignore::platforms::linux::LinuxHandler;
+use dbx_ignore::traits::PlatformHandler;
+use std::process::Command;
+
+const DROPBOX: &str = "user.com.dropbox.ignored";
+const FILE_PROVIDER: &str = "user.com.apple.fileprovider.ignore#P";
+
+#[test]
+fn test_file_with_two_cloud_ignore_attributes_is_flagged() {
+    let env = TestEnvironment::new();
+    let both = env.create_file("both.bin", "both");
+    let single = env.create_file("single.bin", "single");
+    let unrelated = env.create_file("unrelated.bin", "unrelated");
+    LinuxHandler::add_attribute(&both, DROPBOX, b"1").unwrap();
+    LinuxHandler::add_attribute(&both, FILE_PROVIDER, b"1").unwrap();
+    LinuxHandler::add_attribute(&single, DROPBOX, b"1").unwrap();
+    LinuxHandler::add_attribute(&unrelated, DROPBOX, b"1").unwrap();
+    LinuxHandler::add_attribute(&unrelated, "user.mime_type", b"1").unwrap();
+
+    let found = conflicts::find_conflicts(env.path());
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, both);
+    assert_eq!(found[0].attributes, vec![FILE_PROVIDER, DROPBOX]);
+
+    assert_eq!(conflicts::cloud_ignore_client(DROPBOX), Some("Dropbox"));
+    assert_eq!(conflicts::cloud_ignore_client("user.mime_type"), None);
+}
+
+#[test]
+fn test_detect_conflicts_exit_status() {
+    let env = TestEnvironment::new();
+    let file = env.create_file("data.bin", "data");
+    LinuxHandler::add_attribute(&file, DROPBOX, b"1").unwrap();
+
+    let detect = || {
+        Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(env.path())
+            .arg("--detect-conflicts")
+            .output()
+            .expect("Failed to execute binary")
+    };
+
+    assert!(detect().status.success());
+
+    LinuxHandler::add_attribute(&file, FILE_PROVIDER, b"1").unwrap();
+    let output = detect();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("data.bin"), "{}", stdout);
+    assert!(stdout.contains(FILE_PROVIDER), "{}", stdout);
+}
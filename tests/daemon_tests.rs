@@ -0,0 +1,68 @@
+use dbx_ignore::core::daemon::DaemonStatus;
+use tempfile::TempDir;
+
+fn write_status(repo: &std::path::Path, heartbeat_age_secs: Option<i64>) {
+    let status = DaemonStatus {
+        // Use our own PID so the liveness check passes
+        pid: std::process::id(),
+        repo_path: repo.to_path_buf(),
+        started_at: chrono::Utc::now() - chrono::Duration::hours(1),
+        last_heartbeat: heartbeat_age_secs
+            .map(|age| chrono::Utc::now() - chrono::Duration::seconds(age)),
+        pidfile: None,
+        log_file: None,
+    };
+    status.write(repo).unwrap();
+}
+
+#[test]
+fn test_stale_heartbeat_reports_unhealthy() {
+    let temp_dir = TempDir::new().unwrap();
+    write_status(temp_dir.path(), Some(600));
+
+    let status = DaemonStatus::read(temp_dir.path()).unwrap().unwrap();
+    assert!(status.is_stalled());
+}
+
+#[test]
+fn test_fresh_heartbeat_reports_healthy() {
+    let temp_dir = TempDir::new().unwrap();
+    write_status(temp_dir.path(), Some(600));
+
+    // Refreshing the heartbeat brings the daemon back to healthy
+    DaemonStatus::refresh_heartbeat(temp_dir.path()).unwrap();
+    let status = DaemonStatus::read(temp_dir.path()).unwrap().unwrap();
+    assert!(!status.is_stalled());
+}
+
+#[test]
+fn test_legacy_status_without_heartbeat_is_healthy() {
+    let temp_dir = TempDir::new().unwrap();
+    write_status(temp_dir.path(), None);
+
+    let status = DaemonStatus::read(temp_dir.path()).unwrap().unwrap();
+    assert!(!status.is_stalled());
+}
+
+#[test]
+fn test_daemon_log_trimmed_to_newest_lines() {
+    use dbx_ignore::core::daemon::{log_file_path, trim_log};
+
+    let temp_dir = TempDir::new().unwrap();
+    let log = log_file_path(temp_dir.path());
+    std::fs::create_dir_all(log.parent().unwrap()).unwrap();
+    let lines: String = (0..100).map(|i| format!("scan {:03}\n", i)).collect();
+    std::fs::write(&log, &lines).unwrap();
+
+    // Under the cap: left alone
+    assert!(!trim_log(&log, 10_000).unwrap());
+    assert_eq!(std::fs::read_to_string(&log).unwrap(), lines);
+
+    // Over the cap: the oldest lines go, whole lines are kept
+    assert!(trim_log(&log, 400).unwrap());
+    let trimmed = std::fs::read_to_string(&log).unwrap();
+    assert!(trimmed.len() <= 200);
+    assert!(trimmed.ends_with("scan 099\n"));
+    assert!(!trimmed.contains("scan 000"));
+    assert!(trimmed.starts_with("scan "));
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::utils::pattern_matcher::PatternMatcher;
+use dbx_ignore::utils::{git_utils, path_utils, platform_utils};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Levels in the pathological tree; deep enough to overflow a recursive walker
+const DEEP_LEVELS: usize = 1000;
+
+/// Create `levels` nested directories under the test root with `leaf_name` at the bottom
+fn create_deep_tree(env: &TestEnvironment, levels: usize, leaf_name: &str) -> PathBuf {
+    let mut dir = env.path().to_path_buf();
+    for _ in 0..levels {
+        dir.push("d");
+    }
+    std::fs::create_dir_all(&dir).expect("Failed to create deep tree");
+
+    let leaf = dir.join(leaf_name);
+    std::fs::write(&leaf, "deep").expect("Failed to create leaf file");
+    leaf
+}
+
+#[test]
+fn test_walk_files_handles_deep_tree() {
+    let env = TestEnvironment::new();
+    let leaf = create_deep_tree(&env, DEEP_LEVELS, "leaf.bin");
+    let top = env.create_file("top.bin", "top");
+
+    let files = path_utils::walk_files(
+        env.path(),
+        None,
+        path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
+    );
+    assert!(files.contains(&leaf));
+    assert!(files.contains(&top));
+
+    let files = path_utils::walk_files(
+        env.path(),
+        Some(3),
+        path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
+    );
+    assert_eq!(files, vec![top]);
+}
+
+#[test]
+fn test_pattern_matcher_respects_max_depth() {
+    let env = TestEnvironment::new();
+    let leaf = create_deep_tree(&env, DEEP_LEVELS, "leaf.log");
+    let top = env.create_file("top.log", "top");
+    let patterns = vec!["*.log".to_string()];
+
+    let matcher = PatternMatcher::new(env.path(), &patterns, false).unwrap();
+    let matched = matcher.find_matching_files(env.path()).unwrap();
+    assert!(matched.contains(&leaf));
+    assert!(matched.contains(&top));
+
+    let matcher = PatternMatcher::new(env.path(), &patterns, false)
+        .unwrap()
+        .with_max_depth(Some(1));
+    let matched = matcher.find_matching_files(env.path()).unwrap();
+    assert_eq!(matched, vec![top.clone()]);
+
+    // A per-call limit overrides the matcher's own, in both directions
+    let matched = matcher
+        .find_matching_files_to_depth(env.path(), None)
+        .unwrap();
+    assert!(matched.contains(&leaf));
+    let matched = matcher
+        .find_matching_files_to_depth(env.path(), Some(1))
+        .unwrap();
+    assert_eq!(matched, vec![top]);
+}
+
+#[test]
+fn test_git_ignored_files_in_deep_tree() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    env.create_gitignore(&["*.log"]);
+    let leaf = create_deep_tree(&env, DEEP_LEVELS, "leaf.log");
+    let top = env.create_file("top.log", "top");
+
+    let ignored = git_utils::get_git_ignored_files_scoped(env.path(), false, None).unwrap();
+    assert_eq!(ignored, vec![leaf.clone(), top.clone()]);
+
+    let ignored = git_utils::get_git_ignored_files_scoped(env.path(), false, Some(2)).unwrap();
+    assert_eq!(ignored, vec![top]);
+}
+
+#[test]
+fn test_max_depth_flag_limits_git_mode() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    env.create_gitignore(&["*.log"]);
+    let leaf = create_deep_tree(&env, DEEP_LEVELS, "leaf.log");
+    let top = env.create_file("top.log", "top");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--max-depth", "1"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    assert!(platform_utils::has_any_ignore_attribute(&top));
+    assert!(!platform_utils::has_any_ignore_attribute(&leaf));
+
+    // Without a bound the whole tree is processed, however deep
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    assert!(platform_utils::has_any_ignore_attribute(&leaf));
+}
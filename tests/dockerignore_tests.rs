@@ -0,0 +1,92 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::utils::dockerignore::get_files_from_dockerignore;
+use dbx_ignore::utils::path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD;
+use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+use std::process::Command;
+
+/// Build context exercising the ways Docker reads ignore files differently from git
+fn setup_context(env: &TestEnvironment) {
+    env.create_file(
+        ".dockerignore",
+        "# Docker patterns are relative to the context root\n\
+         *.log\n\
+         /tmp/\n\
+         **/*.bak\n\
+         node_modules\n\
+         !node_modules/keep.js\n  \
+         ./docs/../build  \n",
+    );
+    env.create_file("main.rs", "fn main() {}");
+    env.create_file("a.log", "log");
+    env.create_dir("sub");
+    env.create_file("sub/b.log", "log");
+    env.create_file("sub/c.bak", "bak");
+    env.create_file("d.bak", "bak");
+    env.create_dir("tmp");
+    env.create_file("tmp/x.txt", "tmp");
+    env.create_dir("node_modules/pkg");
+    env.create_file("node_modules/pkg/index.js", "js");
+    env.create_file("node_modules/keep.js", "js");
+    env.create_dir("build");
+    env.create_file("build/out.o", "obj");
+}
+
+#[test]
+fn test_dockerignore_selection_follows_docker_semantics() {
+    let env = TestEnvironment::new();
+    setup_context(&env);
+
+    let files = get_files_from_dockerignore(
+        &env.path().join(".dockerignore"),
+        None,
+        DEFAULT_PARALLEL_WALK_THRESHOLD,
+    )
+    .unwrap();
+    let mut selected: Vec<String> = files
+        .iter()
+        .map(|path| {
+            path.strip_prefix(env.path())
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    selected.sort();
+
+    assert_eq!(
+        selected,
+        vec![
+            "a.log",
+            "build/out.o",
+            "d.bak",
+            "node_modules/pkg/index.js",
+            "sub/c.bak",
+            "tmp/x.txt",
+        ]
+    );
+}
+
+#[test]
+fn test_dockerignore_flag_defaults_to_local_file() {
+    let env = TestEnvironment::new();
+    setup_context(&env);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .arg("--dockerignore")
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    assert!(has_any_ignore_attribute(&env.path().join("a.log")));
+    assert!(has_any_ignore_attribute(
+        &env.path().join("node_modules/pkg/index.js")
+    ));
+    assert!(!has_any_ignore_attribute(&env.path().join("sub/b.log")));
+    assert!(!has_any_ignore_attribute(
+        &env.path().join("node_modules/keep.js")
+    ));
+    assert!(!has_any_ignore_attribute(&env.path().join("main.rs")));
+}
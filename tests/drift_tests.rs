@@ -0,0 +1,192 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::drift::DriftReport;
+use dbx_ignore::tracked_files::TrackedFiles;
+use dbx_ignore::utils::platform_utils;
+use std::process::Command;
+
+#[test]
+fn test_drift_categorizes_each_kind() {
+    let env = TestEnvironment::new();
+    let consistent = env.create_file("consistent.log", "log");
+    let fell_off = env.create_file("fell_off.log", "log");
+    let outside = env.create_file("outside.log", "log");
+    env.create_file("plain.txt", "text");
+
+    // Mark and track two files through the tool
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--quiet", "consistent.log", "fell_off.log"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    // Marker falls off one, another file gets marked behind the tool's back
+    platform_utils::remove_ignore_attributes(&fell_off).unwrap();
+    platform_utils::add_ignore_attributes(&outside, true, platform_utils::DEFAULT_MARKER_VALUE)
+        .unwrap();
+
+    // A tracked file that was deleted is stale, not drift
+    let mut tracked = TrackedFiles::load(env.path()).unwrap();
+    tracked.add_files(&[env.path().join("deleted.log")]);
+    tracked.save(env.path()).unwrap();
+
+    let report = DriftReport::gather(env.path()).unwrap();
+    assert_eq!(report.missing_markers, vec![fell_off]);
+    assert_eq!(report.untracked_markers, vec![outside]);
+    assert!(report.consistent.contains(&consistent));
+    assert_eq!(report.stale, vec![env.path().join("deleted.log")]);
+    assert!(report.has_drift());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .arg("--drift")
+        .output()
+        .expect("Failed to execute binary");
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("fell_off.log"));
+    assert!(stdout.contains("outside.log"));
+}
+
+#[test]
+fn test_drift_exits_zero_when_consistent() {
+    let env = TestEnvironment::new();
+    env.create_file("app.log", "log");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--quiet", "app.log"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .arg("--drift")
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No drift"));
+}
+
+#[test]
+fn test_verify_repair_reapplies_missing_markers() {
+    let env = TestEnvironment::new();
+    env.create_file("kept.log", "log");
+    let restored = env.create_file("restored.log", "log");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--quiet", "kept.log", "restored.log"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    // A restore from backup drops the marker but the file stays tracked
+    platform_utils::remove_ignore_attributes(&restored).unwrap();
+    let report = TrackedFiles::load(env.path())
+        .unwrap()
+        .reconcile(env.path())
+        .unwrap();
+    assert_eq!(report.missing_markers, vec![restored.clone()]);
+    assert!(report.untracked_markers.is_empty());
+
+    let verify = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(env.path())
+            .arg("--verify")
+            .args(args)
+            .output()
+            .expect("Failed to execute binary")
+    };
+    assert_eq!(verify(&[]).status.code(), Some(1));
+    assert!(!platform_utils::has_any_ignore_attribute(&restored));
+
+    assert!(verify(&["--repair"]).status.success());
+    assert!(platform_utils::has_any_ignore_attribute(&restored));
+    assert!(verify(&[]).status.success());
+}
+
+#[test]
+fn test_import_markers_tracks_manual_markers() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    env.create_gitignore(&["*.log", "build/"]);
+    let log = env.create_file("app.log", "log");
+    let build = env.create_dir("build");
+    let source = env.create_file("main.rs", "fn main() {}");
+
+    // Markers set by hand, as with `xattr -w com.dropbox.ignored 1`
+    for path in [&log, &build, &source] {
+        platform_utils::add_ignore_attributes(path, true, platform_utils::DEFAULT_MARKER_VALUE)
+            .unwrap();
+    }
+
+    let import = dbx_ignore::drift::import_markers(env.path(), true, true).unwrap();
+    assert_eq!(import.imported, vec![log.clone(), build.clone()]);
+    assert_eq!(import.skipped, vec![source.clone()]);
+    assert!(
+        TrackedFiles::load(env.path())
+            .unwrap()
+            .marked_files
+            .is_empty()
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--import-markers", "--git"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    let tracked = TrackedFiles::load(env.path()).unwrap();
+    assert!(tracked.is_tracked(&log));
+    assert!(tracked.is_tracked(&build));
+    assert!(!tracked.is_tracked(&source));
+
+    // Without --git every untracked marker is imported, and nothing is left to drift
+    let import = dbx_ignore::drift::import_markers(env.path(), false, false).unwrap();
+    assert_eq!(import.imported, vec![source]);
+    assert!(!DriftReport::gather(env.path()).unwrap().has_drift());
+}
+
+#[test]
+fn test_check_lists_unmarked_gitignored_files_without_changes() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["*.log", "build/"]);
+    let marked = env.create_file("marked.log", "log");
+    let unmarked = env.create_file("unmarked.log", "log");
+    let build = env.create_dir("build");
+    std::fs::write(build.join("output.bin"), "bin").unwrap();
+    env.create_file("source.txt", "text");
+    platform_utils::add_ignore_attributes(&marked, false, platform_utils::DEFAULT_MARKER_VALUE)
+        .unwrap();
+    platform_utils::add_ignore_attributes(&build, false, platform_utils::DEFAULT_MARKER_VALUE)
+        .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .arg("--check")
+        .output()
+        .expect("Failed to execute binary");
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 git-ignored file(s) are not marked"));
+    assert!(stdout.contains("unmarked.log"));
+    assert!(!stdout.contains("output.bin"));
+
+    // Nothing was marked and no state was written
+    assert!(!platform_utils::has_any_ignore_attribute(&unmarked));
+    assert!(!env.path().join(".dbx-ignore").exists());
+
+    platform_utils::add_ignore_attributes(&unmarked, false, platform_utils::DEFAULT_MARKER_VALUE)
+        .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .arg("--check")
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+}
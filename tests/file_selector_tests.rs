@@ -0,0 +1,340 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::file_selector::{EntryKind, FileSelector, Filter, Source};
+use dbx_ignore::utils::predicate::Predicate;
+use filetime::FileTime;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Sorted paths relative to the environment root
+fn relative(env: &TestEnvironment, paths: &[PathBuf]) -> Vec<String> {
+    let mut relative: Vec<String> = paths
+        .iter()
+        .map(|path| {
+            path.strip_prefix(env.path())
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    relative.sort();
+    relative
+}
+
+/// Tree with files of different sizes and types spread over a few folders
+fn setup_tree(env: &TestEnvironment) {
+    env.create_dir("media");
+    env.create_dir("logs/archive");
+    env.create_file("media/big.mp4", &"x".repeat(4096));
+    env.create_file("media/small.mp4", "x");
+    env.create_file("logs/app.log", &"x".repeat(2048));
+    env.create_file("logs/archive/old.log", &"x".repeat(2048));
+    env.create_file("notes.txt", "notes");
+}
+
+fn walk(env: &TestEnvironment) -> Source {
+    Source::Walk {
+        root: env.path().to_path_buf(),
+        max_depth: None,
+    }
+}
+
+#[test]
+fn test_size_and_kind_filters() {
+    let env = TestEnvironment::new();
+    setup_tree(&env);
+
+    let selection = FileSelector::new(env.path())
+        .source(walk(&env))
+        .filter(Filter::Kind(EntryKind::File))
+        .filter(Filter::MinSize(1024))
+        .filter(Filter::MaxSize(3000))
+        .select()
+        .unwrap();
+
+    assert_eq!(
+        relative(&env, &selection.files),
+        vec!["logs/app.log", "logs/archive/old.log"]
+    );
+    assert_eq!(selection.dropped_by("min-size"), 2);
+    assert_eq!(selection.dropped_by("max-size"), 1);
+}
+
+#[test]
+fn test_size_filter_counts_directory_contents() {
+    let env = TestEnvironment::new();
+    setup_tree(&env);
+
+    // logs/ holds 4 KB across its files, though no single file reaches 3 KB
+    let selection = FileSelector::new(env.path())
+        .source(Source::Paths {
+            paths: vec![
+                env.path().join("logs"),
+                env.path().join("media/small.mp4"),
+                env.path().join("notes.txt"),
+            ],
+            literal: true,
+            local_only: false,
+            confine_to: None,
+        })
+        .filter(Filter::MinSize(3000))
+        .select()
+        .unwrap();
+
+    assert_eq!(relative(&env, &selection.files), vec!["logs"]);
+    assert_eq!(selection.dropped_by("min-size"), 2);
+}
+
+#[test]
+fn test_exclude_and_allow_filters() {
+    let env = TestEnvironment::new();
+    setup_tree(&env);
+
+    let selection = FileSelector::new(env.path())
+        .source(walk(&env))
+        .filter(Filter::Allow(vec![
+            PathBuf::from("logs"),
+            PathBuf::from("notes.txt"),
+        ]))
+        .filter(Filter::Exclude(vec![env.path().join("logs/archive")]))
+        .select()
+        .unwrap();
+
+    assert_eq!(
+        relative(&env, &selection.files),
+        vec!["logs/app.log", "notes.txt"]
+    );
+    assert_eq!(selection.dropped_by("allow"), 2);
+    assert_eq!(selection.dropped_by("exclude"), 1);
+}
+
+#[test]
+fn test_pattern_source_with_where_and_custom_filters() {
+    let env = TestEnvironment::new();
+    setup_tree(&env);
+
+    let selection = FileSelector::new(env.path())
+        .source(Source::Patterns {
+            root: env.path().to_path_buf(),
+            patterns: vec!["*.mp4".to_string(), "*.log".to_string()],
+            max_depth: None,
+        })
+        .filter(Filter::Where(
+            Predicate::parse("ext==mp4 || size>1KB").unwrap(),
+        ))
+        .filter(Filter::Custom {
+            name: "not-archived",
+            keep: Box::new(|path: &Path| !path.components().any(|c| c.as_os_str() == "archive")),
+        })
+        .select()
+        .unwrap();
+
+    assert_eq!(
+        relative(&env, &selection.files),
+        vec!["logs/app.log", "media/big.mp4", "media/small.mp4"]
+    );
+    assert_eq!(selection.dropped_by("where"), 0);
+    assert_eq!(selection.dropped_by("not-archived"), 1);
+}
+
+#[test]
+fn test_age_filter_over_combined_sources() {
+    let env = TestEnvironment::new();
+    setup_tree(&env);
+    let month_ago = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+    for file in ["logs/app.log", "media/big.mp4"] {
+        filetime::set_file_mtime(env.path().join(file), FileTime::from_system_time(month_ago))
+            .unwrap();
+    }
+
+    // Overlapping sources contribute each path once
+    let cutoff = SystemTime::now() - Duration::from_secs(7 * 24 * 60 * 60);
+    let selection = FileSelector::new(env.path())
+        .source(Source::Paths {
+            paths: vec![
+                env.path().join("media/big.mp4"),
+                env.path().join("notes.txt"),
+            ],
+            literal: true,
+            local_only: false,
+            confine_to: None,
+        })
+        .source(walk(&env))
+        .filter(Filter::ModifiedBefore(cutoff))
+        .select()
+        .unwrap();
+
+    assert_eq!(
+        relative(&env, &selection.files),
+        vec!["logs/app.log", "media/big.mp4"]
+    );
+    assert_eq!(selection.dropped_by("modified-before"), 3);
+}
+
+#[test]
+fn test_paths_source_reports_unmatched_patterns() {
+    let env = TestEnvironment::new();
+    setup_tree(&env);
+
+    let result = FileSelector::new(env.path())
+        .source(Source::Paths {
+            paths: vec![env.path().join("*.missing")],
+            literal: false,
+            local_only: false,
+            confine_to: None,
+        })
+        .select();
+
+    let error = result.unwrap_err().to_string();
+    assert!(
+        error.contains("No files found matching patterns"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_paths_escaping_root_through_parent_dir() {
+    let env = TestEnvironment::new();
+    env.create_dir("repo/src");
+    env.create_file("repo/src/main.rs", "fn main() {}");
+    env.create_file("secret.txt", "outside");
+    env.create_file("outside.log", "outside");
+    let root = env.path().join("repo");
+
+    let select = |path: PathBuf, confine_to: Option<PathBuf>| {
+        FileSelector::new(&root)
+            .source(Source::Paths {
+                paths: vec![path],
+                literal: false,
+                local_only: false,
+                confine_to,
+            })
+            .select()
+    };
+
+    // Literal paths and glob patterns climbing out of the root are refused
+    for path in [root.join("../secret.txt"), root.join("src/../../*.log")] {
+        let error = select(path.clone(), Some(root.clone()))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("--allow-outside"), "{}", error);
+    }
+
+    // Climbing back down inside the root is fine
+    let selection = select(root.join("src/../src/main.rs"), Some(root.clone())).unwrap();
+    assert_eq!(selection.files.len(), 1);
+
+    // Without a boundary the same paths are selected
+    let selection = select(root.join("../secret.txt"), None).unwrap();
+    assert_eq!(selection.files, vec![root.join("../secret.txt")]);
+    let selection = select(root.join("src/../../*.log"), None).unwrap();
+    assert_eq!(selection.files.len(), 1);
+}
+
+#[test]
+fn test_absolute_and_symlinked_paths_outside_root() {
+    let env = TestEnvironment::new();
+    env.create_dir("repo/src");
+    env.create_file("repo/src/main.rs", "fn main() {}");
+    env.create_dir("shared");
+    env.create_file("shared/cache.bin", "outside");
+    let root = env.path().join("repo");
+    let outside = env.path().join("shared/cache.bin");
+
+    let select = |path: PathBuf, confine_to: Option<PathBuf>| {
+        FileSelector::new(&root)
+            .source(Source::Paths {
+                paths: vec![path],
+                literal: false,
+                local_only: false,
+                confine_to,
+            })
+            .select()
+    };
+
+    // An absolute path outside the root is refused, with no `..` involved
+    let error = select(outside.clone(), Some(root.clone()))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("--allow-outside"), "{}", error);
+    let selection = select(outside.clone(), None).unwrap();
+    assert_eq!(selection.files, vec![outside]);
+
+    // An absolute path inside the root is fine
+    let selection = select(root.join("src/main.rs"), Some(root.clone())).unwrap();
+    assert_eq!(selection.files.len(), 1);
+
+    // So is a directory symlinked out of the root, but not what lies behind it
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(env.path().join("shared"), root.join("link")).unwrap();
+        let error = select(root.join("link/cache.bin"), Some(root.clone()))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("--allow-outside"), "{}", error);
+        let error = select(root.join("link/*.bin"), Some(root.clone()))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("--allow-outside"), "{}", error);
+    }
+}
+
+#[test]
+fn test_exclude_patterns_split_directories() {
+    let env = TestEnvironment::new();
+    setup_tree(&env);
+
+    let selection = FileSelector::new(env.path())
+        .source(Source::Paths {
+            paths: vec![env.path().join("logs"), env.path().join("media")],
+            literal: true,
+            local_only: false,
+            confine_to: None,
+        })
+        .exclude(&["archive/".to_string(), "small.mp4".to_string()])
+        .unwrap()
+        .select()
+        .unwrap();
+
+    // Both directories hold excluded paths, so their other contents are selected instead
+    assert_eq!(
+        relative(&env, &selection.files),
+        vec!["logs/app.log", "media/big.mp4"]
+    );
+    assert_eq!(
+        relative(&env, &selection.excluded),
+        vec!["logs/archive", "media/small.mp4"]
+    );
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+fn test_attribute_filter_selects_tagged_files() {
+    use dbx_ignore::utils::platform_utils::AttributeCondition;
+
+    let env = TestEnvironment::new();
+    let archived = env.create_file("old.tar", "archive");
+    let draft = env.create_file("draft.tar", "draft");
+    env.create_file("plain.tar", "plain");
+    xattr::set(&archived, "user.archive", b"true").unwrap();
+    xattr::set(&draft, "user.archive", b"false").unwrap();
+
+    let select = |condition: &str| {
+        let selection = FileSelector::new(env.path())
+            .source(Source::Walk {
+                root: env.path().to_path_buf(),
+                max_depth: None,
+            })
+            .filter(Filter::Attribute(condition.parse().unwrap()))
+            .select()
+            .unwrap();
+        relative(&env, &selection.files)
+    };
+
+    assert_eq!(select("user.archive"), vec!["draft.tar", "old.tar"]);
+    assert_eq!(select("user.archive=true"), vec!["old.tar"]);
+    assert!(select("user.other").is_empty());
+    assert!("=true".parse::<AttributeCondition>().is_err());
+}
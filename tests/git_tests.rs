@@ -30,6 +30,7 @@ fn test_git_mode_in_valid_repository() {
         patterns: vec![],
         git_mode: true,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Change to temp directory for the test
@@ -60,6 +61,7 @@ fn test_git_mode_outside_repository() {
         patterns: vec![],
         git_mode: true,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Change to temp directory for the test
@@ -97,6 +99,7 @@ fn test_git_mode_with_empty_gitignore() {
         patterns: vec![],
         git_mode: true,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Change to temp directory for the test
@@ -131,6 +134,7 @@ fn test_git_mode_with_no_gitignore() {
         patterns: vec![],
         git_mode: true,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Change to temp directory for the test
@@ -181,6 +185,7 @@ fn test_git_mode_with_complex_gitignore() {
         patterns: vec![],
         git_mode: true,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Change to temp directory for the test
@@ -221,6 +226,7 @@ fn test_git_mode_with_negated_patterns() {
         patterns: vec![],
         git_mode: true,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Change to temp directory for the test
@@ -256,6 +262,7 @@ fn test_mixed_mode_vs_git_mode() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Test git mode
@@ -268,6 +275,7 @@ fn test_mixed_mode_vs_git_mode() {
         patterns: vec![],
         git_mode: true,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Change to temp directory for the tests
@@ -307,6 +315,7 @@ fn test_git_repository_discovery() {
         patterns: vec![],
         git_mode: true,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Change to subdirectory and test git discovery
@@ -320,3 +329,388 @@ fn test_git_repository_discovery() {
     // Should succeed - git repository should be discovered from parent
     assert!(result.is_ok());
 }
+
+#[test]
+#[serial]
+fn test_skip_worktree_selection() {
+    let env = TestEnvironment::new();
+    let _repo = env.init_git_repo().expect("Failed to init git repo");
+
+    env.create_file("local.conf", "local edits");
+    env.create_file("normal.txt", "content");
+
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .current_dir(env.path())
+            .args(args)
+            .output()
+            .expect("Failed to run git")
+    };
+    git(&["add", "local.conf", "normal.txt"]);
+    git(&["update-index", "--skip-worktree", "local.conf"]);
+
+    let selected =
+        dbx_ignore::utils::git_utils::get_skip_worktree_files_in_path(env.path()).unwrap();
+    let names: Vec<_> = selected
+        .iter()
+        .filter_map(|p| p.file_name().and_then(|n| n.to_str()))
+        .collect();
+    assert_eq!(names, vec!["local.conf"]);
+
+    // The selector should also drive a run
+    let config = Config {
+        action: Action::Ignore,
+        dry_run: true,
+        quiet: true,
+        skip_worktree: true,
+        ..Default::default()
+    };
+    std::env::set_current_dir(&env.temp_path).unwrap();
+    assert!(run(config).is_ok());
+}
+
+#[test]
+#[serial]
+fn test_skip_worktree_outside_repository() {
+    let env = TestEnvironment::new();
+
+    let result = dbx_ignore::utils::git_utils::get_skip_worktree_files_in_path(env.path());
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("Not in a git repository"));
+}
+
+/// Create a parent repo with a `vendor/lib` submodule that has its own `.gitignore`
+fn setup_repo_with_submodule(env: &TestEnvironment) {
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .current_dir(dir)
+            .args([
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "-c",
+                "protocol.file.allow=always",
+            ])
+            .args(args)
+            .output()
+            .expect("Failed to run git");
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    };
+
+    // Upstream repository for the submodule
+    let upstream = env.create_dir("upstream");
+    git(&upstream, &["init", "-q"]);
+    std::fs::write(upstream.join(".gitignore"), "*.tmp\n").unwrap();
+    std::fs::write(upstream.join("lib.rs"), "// lib").unwrap();
+    git(&upstream, &["add", "."]);
+    git(&upstream, &["commit", "-q", "-m", "init"]);
+
+    let parent = env.create_dir("parent");
+    git(&parent, &["init", "-q"]);
+    std::fs::write(parent.join(".gitignore"), "*.log\n").unwrap();
+    git(
+        &parent,
+        &[
+            "submodule",
+            "add",
+            "-q",
+            upstream.to_str().unwrap(),
+            "vendor/lib",
+        ],
+    );
+
+    std::fs::write(parent.join("app.log"), "log").unwrap();
+    std::fs::write(parent.join("vendor/lib/cache.tmp"), "tmp").unwrap();
+    std::fs::write(parent.join("vendor/lib/debug.log"), "log").unwrap();
+}
+
+#[test]
+fn test_git_ignored_files_respect_submodule_boundaries() {
+    use dbx_ignore::utils::git_utils::get_git_ignored_files_scoped;
+
+    let env = TestEnvironment::new();
+    setup_repo_with_submodule(&env);
+    let parent = env.path().join("parent");
+
+    let names = |files: Vec<std::path::PathBuf>| -> Vec<String> {
+        files
+            .iter()
+            .map(|p| p.strip_prefix(&parent).unwrap().display().to_string())
+            .collect()
+    };
+
+    // Default: stay out of the submodule entirely
+    let ignored = get_git_ignored_files_scoped(&parent, false, None).unwrap();
+    assert_eq!(names(ignored), vec!["app.log"]);
+
+    // Opt-in: descend, applying the submodule's own rules rather than the parent's
+    let ignored = get_git_ignored_files_scoped(&parent, true, None).unwrap();
+    assert_eq!(names(ignored), vec!["app.log", "vendor/lib/cache.tmp"]);
+}
+
+#[test]
+fn test_git_ignored_files_follow_nested_rules_and_ignored_dirs() {
+    use dbx_ignore::utils::git_utils::get_git_ignored_files_in_path;
+
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    env.create_gitignore(&["build/", "*.log", "!keep.log", "!build/keep.o"]);
+    env.create_dir("build/nested");
+    env.create_file("build/out.o", "object");
+    env.create_file("build/keep.o", "object");
+    env.create_file("build/nested/deep.o", "object");
+    env.create_file("app.log", "log");
+    env.create_file("keep.log", "log");
+    env.create_dir("src");
+    env.create_file("src/.gitignore", "*.tmp\n");
+    env.create_file("src/cache.tmp", "tmp");
+    env.create_file("src/main.rs", "fn main() {}");
+    env.create_file("top.tmp", "tmp");
+
+    let ignored: Vec<String> = get_git_ignored_files_in_path(env.path())
+        .unwrap()
+        .iter()
+        .map(|p| p.strip_prefix(env.path()).unwrap().display().to_string())
+        .collect();
+    // Git can't re-include a file inside an excluded directory, so build/keep.o stays ignored
+    assert_eq!(
+        ignored,
+        vec![
+            "app.log",
+            "build/keep.o",
+            "build/nested/deep.o",
+            "build/out.o",
+            "src/cache.tmp"
+        ]
+    );
+}
+
+#[test]
+#[serial]
+fn test_recurse_submodules_flag_marks_submodule_files() {
+    let env = TestEnvironment::new();
+    setup_repo_with_submodule(&env);
+    let parent = env.path().join("parent");
+
+    std::env::set_current_dir(&parent).unwrap();
+    let config = Config {
+        quiet: true,
+        git_mode: true,
+        ..Default::default()
+    };
+    run(config).unwrap();
+
+    let has_marker = dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+    assert!(has_marker(&parent.join("app.log")));
+    assert!(!has_marker(&parent.join("vendor/lib/cache.tmp")));
+
+    let config = Config {
+        quiet: true,
+        git_mode: true,
+        recurse_submodules: true,
+        ..Default::default()
+    };
+    run(config).unwrap();
+    assert!(has_marker(&parent.join("vendor/lib/cache.tmp")));
+    assert!(!has_marker(&parent.join("vendor/lib/debug.log")));
+}
+
+#[test]
+#[serial]
+fn test_git_mode_from_subdirectory_spans_repository() {
+    use dbx_ignore::utils::platform_utils::{has_any_ignore_attribute, remove_ignore_attributes};
+
+    let env = TestEnvironment::new();
+    let _repo = env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["*.log"]);
+    let subdir = env.create_dir("subdir");
+    env.create_dir("other");
+    let root_log = env.create_file("root.log", "log");
+    let sub_log = env.create_file("subdir/sub.log", "log");
+    let other_log = env.create_file("other/other.log", "log");
+
+    std::env::set_current_dir(&subdir).unwrap();
+    let config = Config {
+        quiet: true,
+        git_mode: true,
+        ..Default::default()
+    };
+    run(config).unwrap();
+
+    assert!(has_any_ignore_attribute(&root_log));
+    assert!(has_any_ignore_attribute(&sub_log));
+    assert!(has_any_ignore_attribute(&other_log));
+
+    for file in [&root_log, &sub_log, &other_log] {
+        remove_ignore_attributes(file).unwrap();
+    }
+
+    // Opting out keeps selection under the current directory
+    let config = Config {
+        quiet: true,
+        git_mode: true,
+        cwd_scope: true,
+        ..Default::default()
+    };
+    run(config).unwrap();
+
+    assert!(!has_any_ignore_attribute(&root_log));
+    assert!(has_any_ignore_attribute(&sub_log));
+    assert!(!has_any_ignore_attribute(&other_log));
+}
+
+#[test]
+#[serial]
+fn test_local_only_ignores_inherited_gitignore_rules() {
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let env = TestEnvironment::new();
+    let _repo = env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["*.tmp"]);
+    env.create_dir("subdir");
+    env.create_file("subdir/.gitignore", "*.log\n");
+    let sub_log = env.create_file("subdir/app.log", "log");
+    let sub_tmp = env.create_file("subdir/cache.tmp", "tmp");
+    let root_log = env.create_file("root.log", "log");
+
+    std::env::set_current_dir(env.path()).unwrap();
+    let config = Config {
+        quiet: true,
+        files: vec![env.path().join("subdir/.gitignore")],
+        local_only: true,
+        ..Default::default()
+    };
+    run(config).unwrap();
+
+    assert!(has_any_ignore_attribute(&sub_log));
+    assert!(!has_any_ignore_attribute(&sub_tmp));
+    assert!(!has_any_ignore_attribute(&root_log));
+}
+
+#[test]
+#[serial]
+fn test_exclude_keeps_paths_syncing_in_git_mode() {
+    use dbx_ignore::utils::platform_utils::{
+        DEFAULT_MARKER_VALUE, add_ignore_attributes, has_any_ignore_attribute,
+    };
+
+    let env = TestEnvironment::new();
+    let _repo = env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["build/", "*.log"]);
+    let build = env.create_dir("build");
+    let object = env.create_file("build/main.o", "object");
+    let artifacts = env.create_file("build/artifacts.zip", "zip");
+    let debug_log = env.create_file("debug.log", "log");
+    let keep_log = env.create_file("keep.log", "log");
+
+    // An earlier run marked the whole build directory and a log now excluded
+    add_ignore_attributes(&build, false, DEFAULT_MARKER_VALUE).unwrap();
+    add_ignore_attributes(&keep_log, false, DEFAULT_MARKER_VALUE).unwrap();
+
+    std::env::set_current_dir(env.path()).unwrap();
+    let config = Config {
+        quiet: true,
+        git_mode: true,
+        exclude: vec!["build/artifacts.zip".to_string(), "keep.log".to_string()],
+        reset_excluded: true,
+        ..Default::default()
+    };
+    run(config).unwrap();
+
+    // The build directory's marker would still cover the excluded archive, so it goes
+    assert!(!has_any_ignore_attribute(&build));
+    assert!(has_any_ignore_attribute(&object));
+    assert!(!has_any_ignore_attribute(&artifacts));
+    assert!(has_any_ignore_attribute(&debug_log));
+    assert!(!has_any_ignore_attribute(&keep_log));
+}
+
+#[test]
+#[serial]
+fn test_git_status_selection_matches_porcelain() {
+    let env = TestEnvironment::new();
+    let _repo = env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["*.log", "target/"]);
+    env.create_file("tracked.txt", "tracked");
+    env.create_file("new.txt", "untracked");
+    env.create_dir("generated");
+    env.create_file("generated/a.rs", "untracked dir");
+    env.create_file("debug.log", "ignored");
+    env.create_dir("target/release");
+    env.create_file("target/release/app", "ignored dir");
+
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .current_dir(env.path())
+            .args(args)
+            .output()
+            .expect("Failed to run git")
+    };
+    git(&["add", ".gitignore", "tracked.txt"]);
+
+    // Untracked (??) and ignored (!!) entries from `git status --porcelain -z --ignored`
+    let porcelain = git(&["status", "--porcelain", "-z", "--ignored"]).stdout;
+    let mut expected: Vec<String> = porcelain
+        .split(|b| *b == 0)
+        .filter_map(|entry| std::str::from_utf8(entry).ok())
+        .filter(|entry| entry.starts_with("?? ") || entry.starts_with("!! "))
+        .map(|entry| entry[3..].trim_end_matches('/').to_string())
+        .collect();
+    expected.sort();
+    assert_eq!(
+        expected,
+        vec!["debug.log", "generated", "new.txt", "target"]
+    );
+
+    let selected =
+        dbx_ignore::utils::git_utils::get_untracked_and_ignored_in_path(env.path()).unwrap();
+    let root = env.path().canonicalize().unwrap();
+    let relative: Vec<String> = selected
+        .iter()
+        .map(|p| {
+            p.strip_prefix(&root)
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    assert_eq!(relative, expected);
+
+    // A dry run marks nothing
+    std::env::set_current_dir(&env.temp_path).unwrap();
+    let config = Config {
+        dry_run: true,
+        quiet: true,
+        git_status: true,
+        ..Default::default()
+    };
+    run(config).unwrap();
+    assert!(
+        selected
+            .iter()
+            .all(|p| !dbx_ignore::utils::platform_utils::has_any_ignore_attribute(p))
+    );
+
+    let config = Config {
+        quiet: true,
+        git_status: true,
+        ..Default::default()
+    };
+    run(config).unwrap();
+    assert!(
+        selected
+            .iter()
+            .all(|p| dbx_ignore::utils::platform_utils::has_any_ignore_attribute(p))
+    );
+    assert!(
+        !dbx_ignore::utils::platform_utils::has_any_ignore_attribute(
+            &env.path().join("tracked.txt")
+        )
+    );
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::gitignore_diff::GitignoreDiff;
+
+#[test]
+fn test_diff_reports_newly_ignored_and_unignored_files() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    env.create_gitignore(&["*.log", "build/"]);
+    env.create_dir("build");
+    env.create_dir("dist");
+    env.create_dir("nested");
+    let app_log = env.create_file("app.log", "log");
+    let build_output = env.create_file("build/output.o", "object");
+    let dist_bundle = env.create_file("dist/bundle.js", "bundle");
+    let keep_log = env.create_file("keep.log", "log");
+    env.create_file("src.rs", "fn main() {}");
+    env.create_file("nested/.gitignore", "*.tmp\n");
+    env.create_file("nested/scratch.tmp", "tmp");
+
+    // Keep *.log, stop ignoring build/, start ignoring dist/ but re-include keep.log
+    let proposed = env.create_file("proposed.gitignore", "*.log\n!keep.log\ndist/\n");
+
+    let diff = GitignoreDiff::compute(env.path(), &proposed).unwrap();
+    let to_mark: Vec<_> = diff.to_mark.iter().map(|(path, _)| path.clone()).collect();
+    assert_eq!(to_mark, vec![dist_bundle]);
+    assert!(diff.to_mark[0].1.contains("dist/"));
+    assert_eq!(diff.to_unmark, vec![build_output, keep_log]);
+
+    // Files ignored either way, including by nested rules, don't show up
+    assert!(!to_mark.contains(&app_log));
+    assert!(!diff.is_empty());
+}
+
+#[test]
+fn test_diff_of_identical_rules_is_empty() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    env.create_gitignore(&["*.log"]);
+    env.create_file("app.log", "log");
+    let proposed = env.create_file("same.gitignore", "*.log\n");
+
+    let diff = GitignoreDiff::compute(env.path(), &proposed).unwrap();
+    assert!(diff.is_empty());
+}
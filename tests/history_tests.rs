@@ -0,0 +1,96 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::Action;
+use dbx_ignore::history::OperationRecord;
+use std::process::Command;
+
+#[test]
+fn test_log_shows_operations_in_order() {
+    let env = TestEnvironment::new();
+    env.create_file("app.log", "log content");
+    env.create_file("debug.log", "log content");
+
+    // First operation: mark both files
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--quiet", "app.log", "debug.log"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    // Second operation: reset one of them
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--quiet", "--reset", "app.log"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    // The history should contain both operations, oldest first
+    let records = OperationRecord::load_all(env.path()).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].action, Action::Ignore);
+    assert_eq!(records[1].action, Action::Reset);
+    assert!(!records[0].dry_run);
+
+    // The log command should list both in the same order
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--log", "--verbose"])
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let record_lines: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with(|c: char| c.is_ascii_digit()))
+        .collect();
+    let expected: Vec<String> = records
+        .iter()
+        .map(|record| {
+            format!(
+                "{}  {:<6}  {} files, {} operations",
+                record
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S"),
+                record.action.to_string(),
+                record.file_count,
+                record.operations
+            )
+        })
+        .collect();
+    assert_eq!(record_lines, expected, "{}", stdout);
+
+    // With --verbose, each record is followed by the files it changed
+    let reset_line = stdout.lines().position(|line| line == expected[1]).unwrap();
+    let reset_files: Vec<&str> = stdout
+        .lines()
+        .skip(reset_line + 1)
+        .map_while(|line| line.strip_prefix("    "))
+        .collect();
+    let recorded: Vec<String> = records[1]
+        .files
+        .iter()
+        .map(|file| file.display().to_string())
+        .collect();
+    assert_eq!(reset_files, recorded, "{}", stdout);
+}
+
+#[test]
+fn test_log_without_history() {
+    let env = TestEnvironment::new();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .arg("--log")
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No operations recorded yet"));
+}
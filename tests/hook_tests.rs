@@ -0,0 +1,145 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::core::hooks::{HookOutcome, OnChangeHook};
+use dbx_ignore::core::watch::ScanSummary;
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_on_change_hook_runs_with_counts_after_change() {
+    let env = TestEnvironment::new();
+    let hook = OnChangeHook::new(
+        "echo \"$DBX_MARKED $DBX_UNMARKED $DBX_ERRORS\" > hook.out".to_string(),
+        env.path().to_path_buf(),
+    );
+
+    let summary = ScanSummary {
+        marked: 3,
+        unmarked: 1,
+        errors: 2,
+    };
+    let handle = hook
+        .notify(&summary)
+        .expect("hook should run after a change");
+    let outcome = handle.join().unwrap().unwrap();
+    assert!(matches!(outcome, HookOutcome::Completed(status) if status.success()));
+
+    let output = fs::read_to_string(env.path().join("hook.out")).unwrap();
+    assert_eq!(output.trim(), "3 1 2");
+}
+
+#[test]
+fn test_on_change_hook_skipped_without_changes() {
+    let env = TestEnvironment::new();
+    let hook = OnChangeHook::new("touch hook.out".to_string(), env.path().to_path_buf());
+
+    // Errors alone don't count as a change
+    let summary = ScanSummary {
+        errors: 1,
+        ..Default::default()
+    };
+    assert!(hook.notify(&summary).is_none());
+    assert!(hook.notify(&ScanSummary::default()).is_none());
+    assert!(!env.path().join("hook.out").exists());
+}
+
+#[test]
+fn test_on_change_hook_is_killed_after_timeout() {
+    let env = TestEnvironment::new();
+    let hook = OnChangeHook::new("sleep 10".to_string(), env.path().to_path_buf())
+        .with_timeout(Duration::from_millis(200));
+
+    let start = Instant::now();
+    let outcome = hook
+        .run(&ScanSummary {
+            marked: 1,
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(matches!(outcome, HookOutcome::TimedOut));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_on_change_requires_watch() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--on-change", "true", "file.txt"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_pre_scan_hook_excludes_listed_file() {
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let env = TestEnvironment::new();
+    let open_file = env.create_file("open.log", "being edited");
+    let closed_file = env.create_file("closed.log", "done");
+    env.create_dir("editor");
+    let nested = env.create_file("editor/swap.log", "swap");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args([
+            "--quiet",
+            "--pre-scan-hook",
+            "printf 'open.log\\0editor\\0'",
+            "open.log",
+            "closed.log",
+            "editor/swap.log",
+        ])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    assert!(!has_any_ignore_attribute(&open_file));
+    assert!(!has_any_ignore_attribute(&nested));
+    assert!(has_any_ignore_attribute(&closed_file));
+}
+
+#[test]
+fn test_pre_scan_hook_failure_aborts_unless_keep_going() {
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let env = TestEnvironment::new();
+    let file = env.create_file("app.log", "log");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--quiet", "--pre-scan-hook", "exit 3", "app.log"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Pre-scan hook"));
+    assert!(!has_any_ignore_attribute(&file));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args([
+            "--quiet",
+            "--keep-going",
+            "--pre-scan-hook",
+            "exit 3",
+            "app.log",
+        ])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    assert!(has_any_ignore_attribute(&file));
+}
+
+#[test]
+fn test_pre_scan_hook_times_out() {
+    use dbx_ignore::core::hooks::PreScanHook;
+
+    let env = TestEnvironment::new();
+    let hook = PreScanHook::new("sleep 10".to_string(), env.path().to_path_buf())
+        .with_timeout(Duration::from_millis(200));
+
+    let error = hook.run().unwrap_err().to_string();
+    assert!(error.contains("timed out"));
+}
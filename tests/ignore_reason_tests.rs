@@ -0,0 +1,110 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::utils::ignore_reason::{GitignoreExplainer, IgnoreReason};
+use std::process::Command;
+
+/// Repo with a root `.gitignore` and a nested one adding its own rule
+fn setup_nested_gitignores(env: &TestEnvironment) {
+    env.init_git_repo().unwrap();
+    env.create_gitignore(&["*.tmp", "build/"]);
+    env.create_dir("logs");
+    env.create_file("logs/.gitignore", "# logs\n*.log\n!keep.log\n");
+    env.create_file("logs/app.log", "log");
+    env.create_file("logs/keep.log", "log");
+    env.create_file("logs/cache.tmp", "tmp");
+    env.create_dir("build/out");
+    env.create_file("build/out/main.o", "obj");
+}
+
+#[test]
+fn test_explains_rule_from_nested_gitignore() {
+    let env = TestEnvironment::new();
+    setup_nested_gitignores(&env);
+    let mut explainer = GitignoreExplainer::new(env.path());
+
+    let reason = explainer.explain(&env.path().join("logs/app.log")).unwrap();
+    assert_eq!(
+        reason,
+        IgnoreReason::GitIgnore {
+            pattern: "*.log".to_string(),
+            source: Some(env.path().join("logs/.gitignore")),
+        }
+    );
+    assert_eq!(
+        reason.describe(env.path()),
+        "matched `*.log` in ./logs/.gitignore"
+    );
+
+    // Rules from parent directories still apply below them
+    let reason = explainer
+        .explain(&env.path().join("logs/cache.tmp"))
+        .unwrap();
+    assert_eq!(
+        reason.describe(env.path()),
+        "matched `*.tmp` in ./.gitignore"
+    );
+
+    // A directory rule explains the files inside it
+    let reason = explainer
+        .explain(&env.path().join("build/out/main.o"))
+        .unwrap();
+    assert_eq!(
+        reason.describe(env.path()),
+        "matched `build/` in ./.gitignore"
+    );
+}
+
+#[test]
+fn test_negated_and_unmatched_paths_have_no_reason() {
+    let env = TestEnvironment::new();
+    setup_nested_gitignores(&env);
+    let readme = env.create_file("README.md", "readme");
+    let mut explainer = GitignoreExplainer::new(env.path());
+
+    assert_eq!(explainer.explain(&env.path().join("logs/keep.log")), None);
+    assert_eq!(explainer.explain(&readme), None);
+}
+
+#[test]
+fn test_verbose_git_mode_reports_deciding_rule() {
+    let env = TestEnvironment::new();
+    setup_nested_gitignores(&env);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--dry-run", "--verbose", "--git"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let app_line = stdout
+        .lines()
+        .find(|line| line.contains("app.log"))
+        .expect("app.log not reported");
+    assert!(
+        app_line.contains("matched `*.log` in ./logs/.gitignore"),
+        "unexpected output: {}",
+        app_line
+    );
+    assert!(!stdout.contains("keep.log"));
+}
+
+#[test]
+fn test_verbose_reports_cli_pattern() {
+    let env = TestEnvironment::new();
+    env.create_file("notes.tmp", "tmp");
+    env.create_file("explicit.txt", "txt");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--dry-run", "--verbose", "*.tmp", "explicit.txt"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("matched pattern `*.tmp`"), "{}", stdout);
+    assert!(stdout.contains("explicitly selected"), "{}", stdout);
+}
@@ -1,6 +1,7 @@
 mod common;
 
 use common::TestEnvironment;
+use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
 use dbx_ignore::{Action, Config, run};
 use serial_test::serial;
 use std::path::PathBuf;
@@ -16,6 +17,7 @@ fn test_config_creation() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     assert!(config.dry_run);
@@ -27,7 +29,12 @@ fn test_config_creation() {
 }
 
 #[test]
+#[serial]
 fn test_run_with_empty_file_list() {
+    // Other serial tests leave the working directory pointing at a removed temp dir
+    let env = TestEnvironment::new();
+    std::env::set_current_dir(&env.temp_path).unwrap();
+
     let config = Config {
         action: Action::Ignore,
         dry_run: true,
@@ -37,6 +44,7 @@ fn test_run_with_empty_file_list() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Should succeed even with empty file list when not in git mode
@@ -55,6 +63,7 @@ fn test_run_with_nonexistent_file() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Should fail with nonexistent file
@@ -78,6 +87,7 @@ fn test_run_with_existing_files() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Should succeed with existing files
@@ -101,6 +111,7 @@ fn test_run_with_directory() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Should succeed with directories
@@ -125,6 +136,7 @@ fn test_run_with_mixed_files_and_directories() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Should succeed with mixed files and directories
@@ -149,6 +161,7 @@ fn test_dry_run_vs_actual_run() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     std::env::set_current_dir(&env.temp_path).unwrap();
@@ -165,6 +178,7 @@ fn test_dry_run_vs_actual_run() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     let result = run(actual_config);
@@ -186,6 +200,7 @@ fn test_verbose_mode() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Should succeed in verbose mode
@@ -209,6 +224,7 @@ fn test_quiet_mode() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     // Should succeed in quiet mode
@@ -239,6 +255,7 @@ fn test_run_on_supported_platform() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     std::env::set_current_dir(&env.temp_path).unwrap();
@@ -262,3 +279,262 @@ fn test_platform_detection_consistency() {
         assert_eq!(CurrentPlatform::get_target_attributes().len(), 0);
     }
 }
+
+#[test]
+#[serial]
+fn test_run_with_summary_reports_counts() {
+    use dbx_ignore::{RunSummary, run_with_summary};
+
+    let env = TestEnvironment::new();
+    let marked = env.create_file("marked.log", "log");
+    env.create_file("fresh.log", "log");
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&marked, false, b"1").unwrap();
+
+    std::env::set_current_dir(env.path()).unwrap();
+    let summary = run_with_summary(Config {
+        quiet: true,
+        files: vec![PathBuf::from("marked.log"), PathBuf::from("fresh.log")],
+        ..Default::default()
+    })
+    .unwrap();
+
+    // The .dbx-ignore folder doesn't exist before the first real run, so only the two
+    // files are processed; the already-marked one needs no change
+    assert_eq!(
+        summary,
+        RunSummary {
+            files_processed: 2,
+            operations_performed: 1,
+            errors: vec![],
+            skipped: 1,
+        }
+    );
+}
+
+#[test]
+#[serial]
+fn test_jobs_limits_worker_threads() {
+    let env = TestEnvironment::new();
+    let files: Vec<PathBuf> = (0..8)
+        .map(|i| env.create_file(&format!("file{i}.log"), "log"))
+        .collect();
+
+    std::env::set_current_dir(env.path()).unwrap();
+    for jobs in [1, 3] {
+        let summary = dbx_ignore::run_with_summary(Config {
+            action: Action::Reset,
+            quiet: true,
+            jobs: Some(jobs),
+            files: files.clone(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(summary.errors.is_empty());
+
+        let summary = dbx_ignore::run_with_summary(Config {
+            quiet: true,
+            jobs: Some(jobs),
+            files: files.clone(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(summary.errors.is_empty());
+        assert!(summary.operations_performed >= files.len());
+        assert!(files.iter().all(|file| has_any_ignore_attribute(file)));
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[serial]
+fn test_strict_fails_on_unprocessable_files() {
+    let env = TestEnvironment::new();
+    env.create_file("ok.log", "log");
+    // Linux refuses user.* attributes on FIFOs, so marking this one always fails
+    let status = std::process::Command::new("mkfifo")
+        .arg(env.path().join("pipe"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    std::env::set_current_dir(env.path()).unwrap();
+    let config = || Config {
+        quiet: true,
+        files: vec![PathBuf::from("ok.log"), PathBuf::from("pipe")],
+        ..Default::default()
+    };
+
+    // Strict first: the .dbx-ignore folder doesn't exist yet, so only the two files count
+    let error = dbx_ignore::run(Config {
+        strict: true,
+        ..config()
+    })
+    .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("1 of 2 file(s) could not be processed")
+    );
+    assert!(error.to_string().contains("pipe"));
+
+    let summary = dbx_ignore::run_with_summary(config()).unwrap();
+    assert_eq!(summary.errors.len(), 1);
+    assert_eq!(summary.errors[0].0.file_name().unwrap(), "pipe");
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+#[serial]
+fn test_preserve_times_keeps_mtime() {
+    use filetime::FileTime;
+
+    let env = TestEnvironment::new();
+    let file = env.create_file("build.log", "log");
+    let old = FileTime::from_unix_time(1_600_000_000, 0);
+    filetime::set_file_times(&file, old, old).unwrap();
+
+    std::env::set_current_dir(env.path()).unwrap();
+    for action in [Action::Ignore, Action::Reset] {
+        let summary = dbx_ignore::run_with_summary(Config {
+            action,
+            quiet: true,
+            preserve_times: true,
+            files: vec![PathBuf::from("build.log")],
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(summary.errors.is_empty());
+        assert_eq!(has_any_ignore_attribute(&file), action == Action::Ignore);
+
+        let metadata = std::fs::metadata(&file).unwrap();
+        assert_eq!(FileTime::from_last_modification_time(&metadata), old);
+        assert_eq!(FileTime::from_last_access_time(&metadata), old);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+#[serial]
+fn test_hard_links_processed_once() {
+    let env = TestEnvironment::new();
+    let original = env.create_file("data.bin", "data");
+    let link = env.path().join("data-link.bin");
+    std::fs::hard_link(&original, &link).unwrap();
+    let other = env.create_file("other.bin", "other");
+
+    std::env::set_current_dir(env.path()).unwrap();
+    let summary = dbx_ignore::run_with_summary(Config {
+        quiet: true,
+        files: vec![original.clone(), link.clone(), other.clone()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    // The shared inode is marked once, through the first path
+    assert_eq!(summary.files_processed, 2);
+    assert_eq!(summary.skipped, 0);
+    assert!(has_any_ignore_attribute(&original));
+    assert!(has_any_ignore_attribute(&link));
+    assert!(has_any_ignore_attribute(&other));
+
+    let tracked = dbx_ignore::tracked_files::TrackedFiles::load(env.path()).unwrap();
+    assert!(tracked.is_tracked(&link));
+}
+
+#[test]
+#[serial]
+fn test_progress_sink_receives_events() {
+    use dbx_ignore::ProgressEvent;
+
+    let env = TestEnvironment::new();
+    let first = env.create_file("first.log", "log");
+    let second = env.create_file("second.log", "log");
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::env::set_current_dir(env.path()).unwrap();
+    run(Config {
+        quiet: true,
+        files: vec![first.clone(), second.clone()],
+        progress_sink: Some(sender),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let events: Vec<ProgressEvent> = receiver.try_iter().collect();
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[0], ProgressEvent::Started { total: 2 });
+    let mut done: Vec<PathBuf> = events[1..3]
+        .iter()
+        .map(|event| match event {
+            ProgressEvent::FileDone { path, ops } => {
+                assert!(*ops > 0);
+                path.clone()
+            }
+            other => panic!("unexpected event: {other:?}"),
+        })
+        .collect();
+    done.sort();
+    assert_eq!(done, vec![first, second]);
+    assert!(matches!(
+        events[3],
+        ProgressEvent::Finished { processed: 2, .. }
+    ));
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+#[serial]
+fn test_undo_reverts_last_operation_skipping_deleted_files() {
+    let env = TestEnvironment::new();
+    let kept = env.create_file("kept.log", "log");
+    let deleted = env.create_file("deleted.log", "log");
+
+    std::env::set_current_dir(env.path()).unwrap();
+    let undo = || {
+        dbx_ignore::run_with_summary(Config {
+            quiet: true,
+            undo: true,
+            ..Default::default()
+        })
+        .unwrap()
+    };
+
+    // Nothing recorded yet
+    assert_eq!(undo(), dbx_ignore::RunSummary::default());
+
+    dbx_ignore::run(Config {
+        quiet: true,
+        files: vec![kept.clone(), deleted.clone()],
+        ..Default::default()
+    })
+    .unwrap();
+    std::fs::remove_file(&deleted).unwrap();
+
+    // Undoing the marking resets what is left of it
+    let summary = undo();
+    assert!(summary.errors.is_empty());
+    assert!(!has_any_ignore_attribute(&kept));
+    let tracked = dbx_ignore::tracked_files::TrackedFiles::load(env.path()).unwrap();
+    assert!(!tracked.is_tracked(&kept));
+
+    // Undoing the undo marks the file again, whatever selection options are given
+    let summary = dbx_ignore::run_with_summary(Config {
+        quiet: true,
+        undo: true,
+        exclude: vec!["*.log".to_string()],
+        min_size: Some(1 << 20),
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(summary.errors.is_empty());
+    assert!(has_any_ignore_attribute(&kept));
+
+    // An undo that changes nothing isn't recorded
+    let history = || dbx_ignore::history::OperationRecord::load_all(env.path()).unwrap();
+    let before = history();
+    for path in &before.last().unwrap().files {
+        dbx_ignore::utils::platform_utils::remove_ignore_attributes(path).unwrap();
+    }
+    undo();
+    assert_eq!(history().len(), before.len());
+}
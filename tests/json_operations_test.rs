@@ -18,6 +18,9 @@ fn test_daemon_status_write_and_read() -> Result<()> {
         pid: current_pid,
         repo_path: repo_path.to_path_buf(),
         started_at: chrono::Utc::now(),
+        last_heartbeat: None,
+        pidfile: None,
+        log_file: None,
     };
 
     // Write status
@@ -46,6 +49,9 @@ fn test_daemon_status_invalid_pid() -> Result<()> {
         pid: 0, // Invalid PID
         repo_path: repo_path.to_path_buf(),
         started_at: chrono::Utc::now(),
+        last_heartbeat: None,
+        pidfile: None,
+        log_file: None,
     };
 
     // Should fail to write
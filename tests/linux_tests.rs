@@ -13,9 +13,105 @@ mod linux_tests {
         assert!(LinuxHandler::is_supported());
 
         let attrs = LinuxHandler::get_target_attributes();
-        assert_eq!(attrs.len(), 2);
+        assert_eq!(attrs.len(), 1);
         assert!(attrs.contains(&"user.com.dropbox.ignored"));
-        assert!(attrs.contains(&"user.com.apple.fileprovider.ignore#P"));
+    }
+
+    #[test]
+    fn test_parse_attribute_names() {
+        assert_eq!(
+            LinuxHandler::parse_attribute_names(
+                " user.com.dropbox.ignored, ,com.example.ignored,trusted.sync.skip"
+            ),
+            [
+                "user.com.dropbox.ignored",
+                "user.com.example.ignored",
+                "trusted.sync.skip"
+            ]
+        );
+        assert!(LinuxHandler::parse_attribute_names(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_attrs_env_var_overrides_target_attributes() {
+        let env = TestEnvironment::new();
+        let file = env.create_file("app.log", "log");
+
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .arg("app.log")
+            .env("DBX_IGNORE_ATTRS", "com.example.ignored")
+            .current_dir(env.path())
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+
+        assert!(
+            xattr::get(&file, "user.com.example.ignored")
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            xattr::get(&file, "user.com.dropbox.ignored")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_marker_value_from_config_and_env() {
+        let env = TestEnvironment::new();
+        let configured = env.create_file("configured.log", "log");
+        let overridden = env.create_file("overridden.log", "log");
+        env.create_dir(".dbx-ignore");
+        env.create_file(".dbx-ignore/config.toml", "marker_value = \"ignored\"\n");
+
+        let mark = |name: &str, env_value: Option<&str>| {
+            let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"));
+            command.arg(name).current_dir(env.path());
+            if let Some(value) = env_value {
+                command.env("DBX_IGNORE_MARKER_VALUE", value);
+            }
+            let output = command.output().expect("Failed to execute binary");
+            assert!(output.status.success(), "{:?}", output);
+        };
+
+        mark("configured.log", None);
+        mark("overridden.log", Some("yes"));
+
+        let value = |path: &Path| xattr::get(path, "user.com.dropbox.ignored").unwrap();
+        assert_eq!(value(&configured).as_deref(), Some(&b"ignored"[..]));
+        assert_eq!(value(&overridden).as_deref(), Some(&b"yes"[..]));
+
+        // Any value counts as marked
+        assert!(LinuxHandler::has_attribute(&configured, "user.com.dropbox.ignored").unwrap());
+    }
+
+    #[test]
+    fn test_verify_repair_writes_configured_marker_value() {
+        let env = TestEnvironment::new();
+        let file = env.create_file("debug.log", "log");
+        env.create_dir(".dbx-ignore");
+        env.create_file(".dbx-ignore/config.toml", "marker_value = \"ignored\"\n");
+
+        let run = |args: &[&str]| {
+            std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+                .args(args)
+                .current_dir(env.path())
+                .output()
+                .expect("Failed to execute binary")
+        };
+
+        assert!(run(&["debug.log"]).status.success());
+        xattr::remove(&file, "user.com.dropbox.ignored").unwrap();
+
+        let output = run(&["--verify", "--repair"]);
+        assert!(output.status.success(), "{:?}", output);
+        assert_eq!(
+            xattr::get(&file, "user.com.dropbox.ignored")
+                .unwrap()
+                .as_deref(),
+            Some(&b"ignored"[..])
+        );
     }
 
     #[test]
@@ -0,0 +1,105 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::marker_query;
+use dbx_ignore::utils::platform_utils;
+use std::process::{Command, Output};
+
+fn setup(env: &TestEnvironment) {
+    env.create_file("marked.bin", "marked");
+    env.create_file("plain.txt", "plain");
+    env.create_file("other.txt", "other");
+    platform_utils::add_ignore_attributes(
+        &env.path().join("marked.bin"),
+        false,
+        platform_utils::DEFAULT_MARKER_VALUE,
+    )
+    .unwrap();
+}
+
+fn is_marked(env: &TestEnvironment, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .arg("--is-marked")
+        .args(args)
+        .output()
+        .expect("Failed to execute binary")
+}
+
+#[test]
+fn test_query_reports_each_path() {
+    let env = TestEnvironment::new();
+    setup(&env);
+
+    let paths = vec![
+        env.path().join("marked.bin"),
+        env.path().join("plain.txt"),
+        env.path().join("missing.txt"),
+    ];
+    let states = marker_query::query(&paths);
+
+    assert_eq!(states.len(), 3);
+    assert!(states[0].exists && states[0].marked);
+    assert!(states[1].exists && !states[1].marked);
+    assert!(!states[2].exists && !states[2].marked);
+}
+
+#[test]
+fn test_is_marked_text_output_and_exit_codes() {
+    let env = TestEnvironment::new();
+    setup(&env);
+
+    let output = is_marked(&env, &["marked.bin", "plain.txt", "other.txt"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("marked.bin: marked"));
+    assert!(stdout.contains("plain.txt: not marked"));
+    assert!(stdout.contains("other.txt: not marked"));
+
+    let output = is_marked(&env, &["--any", "marked.bin", "plain.txt"]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = is_marked(&env, &["marked.bin"]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = is_marked(&env, &["--any", "plain.txt", "other.txt"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_is_marked_json_output() {
+    let env = TestEnvironment::new();
+    setup(&env);
+
+    let output = is_marked(&env, &["--json", "marked.bin", "plain.txt", "gone.txt"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let results = results.as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["path"], "marked.bin");
+    assert_eq!(results[0]["marked"], true);
+    assert_eq!(results[1]["path"], "plain.txt");
+    assert_eq!(results[1]["marked"], false);
+    assert_eq!(results[2]["exists"], false);
+}
+
+#[test]
+fn test_is_marked_print0_lists_marked_paths() {
+    let env = TestEnvironment::new();
+    setup(&env);
+
+    let output = is_marked(&env, &["--print0", "plain.txt", "marked.bin", "other.txt"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"marked.bin\0");
+}
+
+#[test]
+fn test_is_marked_requires_paths() {
+    let env = TestEnvironment::new();
+
+    let output = is_marked(&env, &[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("requires at least one path"));
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::test_support::BEFORE_PROCESS_PATH;
+use dbx_ignore::utils::platform_utils;
+use dbx_ignore::{Action, Config, run_with_summary};
+use std::path::Path;
+
+fn panic_on_poison(path: &Path) {
+    if path.file_name().is_some_and(|name| name == "poison.log") {
+        panic!("injected failure");
+    }
+}
+
+#[test]
+fn test_panic_on_one_path_fails_only_that_path() {
+    let env = TestEnvironment::new();
+    let files: Vec<_> = ["a.log", "poison.log", "b.log", "c.log"]
+        .iter()
+        .map(|name| env.create_file(name, "log"))
+        .collect();
+    BEFORE_PROCESS_PATH.set(panic_on_poison).unwrap();
+
+    std::env::set_current_dir(env.path()).unwrap();
+    let summary = run_with_summary(Config {
+        action: Action::Ignore,
+        quiet: true,
+        files: files.clone(),
+        ..Default::default()
+    })
+    .expect("the batch should survive a panicking path");
+
+    assert_eq!(summary.errors.len(), 1);
+    let (failed, error) = &summary.errors[0];
+    assert!(failed.ends_with("poison.log"));
+    assert!(error.contains("injected failure"));
+
+    for file in files.iter().filter(|file| !file.ends_with("poison.log")) {
+        assert!(platform_utils::has_any_ignore_attribute(file));
+    }
+    assert!(!platform_utils::has_any_ignore_attribute(&files[1]));
+}
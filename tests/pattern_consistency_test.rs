@@ -44,12 +44,16 @@ fn test_pattern_matching_consistency() -> Result<()> {
         let git_results = dbx_ignore::utils::pattern_matcher::find_files_matching_patterns(
             git_path,
             &[pattern.to_string()],
+            false,
+            dbx_ignore::utils::path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
         )?;
 
         // Get files in non-git directory
         let non_git_results = dbx_ignore::utils::pattern_matcher::find_files_matching_patterns(
             non_git_path,
             &[pattern.to_string()],
+            false,
+            dbx_ignore::utils::path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
         )?;
 
         // Convert to relative paths for comparison
@@ -106,6 +110,8 @@ fn test_gitignore_vs_cli_patterns() -> Result<()> {
     let pattern_files = dbx_ignore::utils::pattern_matcher::find_files_matching_patterns(
         temp_path,
         &patterns.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+        false,
+        dbx_ignore::utils::path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
     )?;
     let pattern_set: HashSet<PathBuf> = pattern_files
         .into_iter()
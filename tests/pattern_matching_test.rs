@@ -56,6 +56,8 @@ src/**/*.bak
         let our_files = dbx_ignore::utils::git_utils::find_files_matching_patterns(
             temp_path,
             &[pattern.to_string()],
+            false,
+            dbx_ignore::utils::path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
         )?;
 
         // Convert to sets for comparison
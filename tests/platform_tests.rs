@@ -52,8 +52,7 @@ fn test_linux_platform_handler() {
 
     let target_attrs = LinuxHandler::get_target_attributes();
     assert!(target_attrs.contains(&"user.com.dropbox.ignored"));
-    assert!(target_attrs.contains(&"user.com.apple.fileprovider.ignore#P"));
-    assert_eq!(target_attrs.len(), 2);
+    assert_eq!(target_attrs.len(), 1);
 }
 
 #[cfg(target_os = "windows")]
@@ -64,8 +63,7 @@ fn test_windows_platform_handler() {
 
     let target_attrs = WindowsHandler::get_target_attributes();
     assert!(target_attrs.contains(&"com.dropbox.ignored"));
-    assert!(target_attrs.contains(&"com.apple.fileprovider.ignore#P"));
-    assert_eq!(target_attrs.len(), 2);
+    assert_eq!(target_attrs.len(), 1);
 }
 
 #[test]
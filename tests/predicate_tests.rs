@@ -0,0 +1,101 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::utils::platform_utils;
+use dbx_ignore::utils::predicate::Predicate;
+use filetime::FileTime;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+fn create_sized_file(env: &TestEnvironment, name: &str, size: usize) -> std::path::PathBuf {
+    env.create_file(name, &"x".repeat(size))
+}
+
+#[test]
+fn test_compound_predicate_against_mixed_files() {
+    let env = TestEnvironment::new();
+    let big_video = create_sized_file(&env, "big.mp4", 4096);
+    let small_video = create_sized_file(&env, "small.MP4", 10);
+    let big_text = create_sized_file(&env, "big.txt", 4096);
+    let old_log = create_sized_file(&env, "old.log", 10);
+    let fresh_log = create_sized_file(&env, "fresh.log", 10);
+    let dir = env.create_dir("cache.mp4");
+
+    let old = FileTime::from_system_time(SystemTime::now() - Duration::from_secs(10 * 86400));
+    filetime::set_file_mtime(&old_log, old).unwrap();
+
+    let predicate =
+        Predicate::parse("(size>1KB && ext==mp4 || name==\"*.log\" && age>=7d) && type==file")
+            .unwrap();
+
+    assert!(predicate.matches(&big_video));
+    assert!(!predicate.matches(&small_video));
+    assert!(!predicate.matches(&big_text));
+    assert!(predicate.matches(&old_log));
+    assert!(!predicate.matches(&fresh_log));
+    assert!(!predicate.matches(&dir));
+
+    let negated = Predicate::parse("!(ext==log) && ext != txt").unwrap();
+    assert!(negated.matches(&big_video));
+    assert!(negated.matches(&small_video));
+    assert!(!negated.matches(&big_text));
+    assert!(!negated.matches(&old_log));
+}
+
+#[test]
+fn test_predicate_parse_errors() {
+    let cases = [
+        ("", "expression is empty"),
+        ("sise>1MB", "unknown field 'sise'"),
+        ("size>", "expected a value"),
+        ("size 1MB", "expected an operator"),
+        ("ext>mp4", "only supports == and !="),
+        ("size>1XB", "Invalid size unit"),
+        ("age<3y", "Invalid duration unit"),
+        ("(ext==mp4", "missing ')'"),
+        ("ext==mp4 &&", "at end of input"),
+        ("ext==mp4 & size>1", "use &&, || or =="),
+        ("type==link", "type must be 'file' or 'dir'"),
+        ("name==\"*.log", "unterminated quote"),
+        ("ext==mp4 ext==txt", "unexpected 'ext'"),
+    ];
+
+    for (input, expected) in cases {
+        let error = Predicate::parse(input).unwrap_err().to_string();
+        assert!(
+            error.contains(expected),
+            "'{}' gave '{}', expected '{}'",
+            input,
+            error,
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_where_flag_marks_matching_files() {
+    let env = TestEnvironment::new();
+    let big_video = create_sized_file(&env, "big.mp4", 4096);
+    let small_video = create_sized_file(&env, "small.mp4", 10);
+    let big_text = create_sized_file(&env, "big.txt", 4096);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--quiet", "--where", "size>1KB && ext==mp4"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    assert!(platform_utils::has_any_ignore_attribute(&big_video));
+    assert!(!platform_utils::has_any_ignore_attribute(&small_video));
+    assert!(!platform_utils::has_any_ignore_attribute(&big_text));
+
+    // Invalid expressions are rejected before anything runs
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--where", "colour==red"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown field 'colour'"));
+}
@@ -0,0 +1,64 @@
+#![cfg(unix)]
+
+use dbx_ignore::utils::priority::{NICE_LEVEL, lower_priority, niceness};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+#[test]
+fn test_lower_priority_sets_niceness() {
+    // Runs on its own test thread, which is what Linux applies niceness to
+    lower_priority().unwrap();
+    assert!(niceness() >= NICE_LEVEL);
+
+    // Already nice enough: nothing to do
+    lower_priority().unwrap();
+    assert!(niceness() >= NICE_LEVEL);
+}
+
+#[test]
+fn test_nice_daemon_runs_at_lower_priority() {
+    let temp_dir = TempDir::new().unwrap();
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .output()
+        .expect("Failed to init git");
+
+    let pidfile = temp_dir.path().join("watcher.pid");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .args([
+            "--watch",
+            "--daemon-mode",
+            "--nice",
+            "--pidfile",
+            "watcher.pid",
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start daemon");
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while !pidfile.exists() && std::time::Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    // Best effort: `ps` may be missing from minimal systems
+    let output = Command::new("ps")
+        .args(["-o", "ni=", "-p", &child.id().to_string()])
+        .output();
+    let _ = child.kill();
+    let _ = child.wait();
+
+    let Ok(output) = output else {
+        return;
+    };
+    let nice: i32 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .expect("Unexpected ps output");
+    assert!(nice >= NICE_LEVEL, "daemon niceness is {}", nice);
+}
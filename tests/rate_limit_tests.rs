@@ -0,0 +1,48 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::utils::rate_limit::RateLimiter;
+use dbx_ignore::{Action, Config, run};
+use rayon::prelude::*;
+use serial_test::serial;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_rate_limiter_spreads_operations_across_threads() {
+    let limiter = RateLimiter::new(20);
+
+    let start = Instant::now();
+    (0..11).into_par_iter().for_each(|_| limiter.acquire());
+
+    // 11 operations at 20/s need at least 10 intervals of 50ms
+    assert!(start.elapsed() >= Duration::from_millis(500));
+}
+
+#[test]
+#[serial]
+fn test_rate_limited_run_takes_minimum_time() {
+    let env = TestEnvironment::new();
+    let files: Vec<_> = (0..6)
+        .map(|i| env.create_file(&format!("file{}.txt", i), "content"))
+        .collect();
+
+    std::env::set_current_dir(env.path()).unwrap();
+    let config = Config {
+        action: Action::Ignore,
+        quiet: true,
+        files: files.clone(),
+        rate_limit: Some(10),
+        ..Default::default()
+    };
+
+    let start = Instant::now();
+    run(config).unwrap();
+
+    // 6 marker operations at 10/s need at least 5 intervals of 100ms
+    assert!(start.elapsed() >= Duration::from_millis(500));
+    assert!(
+        files
+            .iter()
+            .all(|f| dbx_ignore::utils::platform_utils::has_any_ignore_attribute(f))
+    );
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::Action;
+use dbx_ignore::repo_config::RepoConfig;
+use dbx_ignore::utils::platform_utils;
+use std::process::Command;
+
+fn write_config(env: &TestEnvironment, contents: &str) {
+    env.create_dir(".dbx-ignore");
+    env.create_file(".dbx-ignore/config.toml", contents);
+}
+
+#[test]
+fn test_repo_default_action_reset() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["*.log"]);
+    let log = env.create_file("app.log", "log");
+    platform_utils::add_ignore_attributes(&log, true, platform_utils::DEFAULT_MARKER_VALUE)
+        .unwrap();
+
+    write_config(&env, "default_action = \"reset\"\n");
+    assert_eq!(
+        RepoConfig::load(env.path()).unwrap().default_action,
+        Some(Action::Reset)
+    );
+
+    // A bare invocation resets instead of marking
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .arg("--quiet")
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    assert!(!platform_utils::has_any_ignore_attribute(&log));
+
+    // Explicit flags still win
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--quiet", "--ignore", "app.log"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    assert!(platform_utils::has_any_ignore_attribute(&log));
+}
+
+#[test]
+fn test_repo_config_missing_and_invalid() {
+    let env = TestEnvironment::new();
+    assert_eq!(RepoConfig::load(env.path()).unwrap(), RepoConfig::default());
+
+    write_config(&env, "default_action = \"watch\"\n");
+    let error = format!("{:#}", RepoConfig::load(env.path()).unwrap_err());
+    assert!(error.contains("default_action must be"));
+
+    write_config(&env, "default_action = \"explode\"\n");
+    assert!(RepoConfig::load(env.path()).is_err());
+
+    write_config(&env, "defualt_action = \"reset\"\n");
+    assert!(RepoConfig::load(env.path()).is_err());
+
+    write_config(&env, "marker_value = \"\"\n");
+    let error = format!("{:#}", RepoConfig::load(env.path()).unwrap_err());
+    assert!(error.contains("marker_value must not be empty"));
+}
+
+#[test]
+fn test_repo_config_defaults_under_cli_flags() {
+    use dbx_ignore::Config;
+    use std::time::Duration;
+
+    let env = TestEnvironment::new();
+    write_config(&env, "quiet = true\ndebounce_ms = 2000\n");
+    let repo_config = RepoConfig::load(env.path()).unwrap();
+    assert_eq!(repo_config.quiet, Some(true));
+
+    let mut config = Config::default();
+    repo_config.apply_to(&mut config);
+    assert!(config.quiet);
+    assert_eq!(config.debounce, Some(Duration::from_secs(2)));
+
+    // Explicit flags win over the file
+    let mut config = Config {
+        verbose: true,
+        debounce: Some(Duration::from_millis(100)),
+        ..Default::default()
+    };
+    repo_config.apply_to(&mut config);
+    assert!(config.verbose && !config.quiet);
+    assert_eq!(config.debounce, Some(Duration::from_millis(100)));
+
+    // End to end: the configured quiet mode silences a run unless --verbose is given
+    env.create_file("notes.txt", "notes");
+    let run = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(env.path())
+            .args(["--dry-run", "notes.txt"])
+            .args(extra)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert!(run(&[]).trim().is_empty());
+    assert!(run(&["--verbose"]).contains("Platform:"));
+
+    write_config(&env, "debounce_ms = 0\n");
+    assert!(RepoConfig::load(env.path()).is_err());
+}
+
+#[test]
+fn test_scan_cache_skips_unchanged_git_runs() {
+    use filetime::FileTime;
+
+    let env = TestEnvironment::new();
+    env.init_git_repo().expect("Failed to init git repo");
+    let gitignore = env.create_gitignore(&["*.log", ".dbx-ignore/"]);
+    write_config(&env, "scan_cache = true\n");
+    let first = env.create_file("first.log", "log");
+
+    let run = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(env.path())
+            .env("NO_COLOR", "1")
+            .arg("--git")
+            .args(extra)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(!run(&[]).contains("Nothing to do"));
+    assert!(platform_utils::has_any_ignore_attribute(&first));
+
+    // Nothing the cache covers changed, so the new file isn't seen
+    let second = env.create_file("second.log", "log");
+    assert!(run(&[]).contains("Nothing to do"));
+    assert!(!platform_utils::has_any_ignore_attribute(&second));
+
+    // --force re-scans anyway
+    assert!(!run(&["--force"]).contains("Nothing to do"));
+    assert!(platform_utils::has_any_ignore_attribute(&second));
+
+    // A changed .gitignore invalidates the cache
+    let third = env.create_file("third.tmp", "tmp");
+    std::fs::write(&gitignore, "*.log\n*.tmp\n.dbx-ignore/\n").unwrap();
+    filetime::set_file_mtime(&gitignore, FileTime::from_unix_time(1_000_000_000, 0)).unwrap();
+    assert!(!run(&[]).contains("Nothing to do"));
+    assert!(platform_utils::has_any_ignore_attribute(&third));
+}
+
+#[test]
+fn test_scan_cache_tracks_marker_value_and_selection_options() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["*.log", ".dbx-ignore/"]);
+    write_config(&env, "scan_cache = true\n");
+    env.create_file("app.log", "log");
+
+    let run = |marker_value: &str, extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(env.path())
+            .env("NO_COLOR", "1")
+            .env(platform_utils::MARKER_VALUE_ENV_VAR, marker_value)
+            .arg("--git")
+            .args(extra)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(!run("1", &[]).contains("Nothing to do"));
+    assert!(run("1", &[]).contains("Nothing to do"));
+
+    // Options the cache doesn't record bypass it
+    for option in ["--follow-symlinks", "--allow-outside"] {
+        assert!(!run("1", &[option]).contains("Nothing to do"), "{}", option);
+    }
+
+    // A different marker value invalidates it
+    assert!(!run("2", &[]).contains("Nothing to do"));
+    assert!(run("2", &[]).contains("Nothing to do"));
+}
@@ -21,6 +21,7 @@ fn test_reset_removes_markers() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     std::env::set_current_dir(&env.temp_path).unwrap();
@@ -38,6 +39,7 @@ fn test_reset_removes_markers() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     std::env::set_current_dir(&env.temp_path).unwrap();
@@ -46,7 +48,7 @@ fn test_reset_removes_markers() {
     let _ = result;
 
     // Verify markers are removed (platform-specific check)
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     {
         use xattr;
         let attrs = xattr::list(&test_file).unwrap();
@@ -75,6 +77,7 @@ fn test_reset_on_unmarked_file() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     std::env::set_current_dir(&env.temp_path).unwrap();
@@ -101,6 +104,7 @@ fn test_reset_multiple_files() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     std::env::set_current_dir(&env.temp_path).unwrap();
@@ -116,6 +120,7 @@ fn test_reset_multiple_files() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     std::env::set_current_dir(&env.temp_path).unwrap();
@@ -149,6 +154,7 @@ fn test_reset_git_mode() {
         patterns: vec![],
         git_mode: true,
         daemon_mode: false,
+        ..Default::default()
     };
 
     run(ignore_config).unwrap();
@@ -163,6 +169,7 @@ fn test_reset_git_mode() {
         patterns: vec![],
         git_mode: true,
         daemon_mode: false,
+        ..Default::default()
     };
 
     let result = run(reset_config);
@@ -186,6 +193,7 @@ fn test_reset_dry_run() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     std::env::set_current_dir(&env.temp_path).unwrap();
@@ -201,6 +209,7 @@ fn test_reset_dry_run() {
         patterns: vec![],
         git_mode: false,
         daemon_mode: false,
+        ..Default::default()
     };
 
     std::env::set_current_dir(&env.temp_path).unwrap();
@@ -211,3 +220,138 @@ fn test_reset_dry_run() {
     // Verify marker is still there (dry run shouldn't remove it)
     // Note: This verification is platform-specific and may not work in all test environments
 }
+
+#[test]
+#[serial]
+fn test_reset_by_tag_leaves_other_tags_marked() {
+    use dbx_ignore::tracked_files::TrackedFiles;
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let env = TestEnvironment::new();
+    let build_output = env.create_file("app.o", "object");
+    let media = env.create_file("video.mp4", "video");
+
+    let mark = |file: &str, tag: &str| Config {
+        action: Action::Ignore,
+        quiet: true,
+        files: vec![PathBuf::from(file)],
+        tag: Some(tag.to_string()),
+        ..Default::default()
+    };
+
+    std::env::set_current_dir(&env.temp_path).unwrap();
+    run(mark("app.o", "build")).unwrap();
+    run(mark("video.mp4", "media")).unwrap();
+
+    let tracked = TrackedFiles::load(&env.temp_path).unwrap();
+    assert_eq!(tracked.files_tagged("build"), vec![PathBuf::from("app.o")]);
+    assert_eq!(tracked.tag_of(&PathBuf::from("video.mp4")), Some("media"));
+
+    run(Config {
+        action: Action::Reset,
+        quiet: true,
+        tag: Some("build".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(!has_any_ignore_attribute(&build_output));
+    assert!(has_any_ignore_attribute(&media));
+
+    let tracked = TrackedFiles::load(&env.temp_path).unwrap();
+    assert!(tracked.files_tagged("build").is_empty());
+    assert_eq!(
+        tracked.files_tagged("media"),
+        vec![PathBuf::from("video.mp4")]
+    );
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+#[serial]
+fn test_reset_inside_marked_directory_resets_directory() {
+    use dbx_ignore::tracked_files::TrackedFiles;
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let env = TestEnvironment::new();
+    env.create_dir("build");
+    let object = env.create_file("build/main.o", "object");
+    env.create_file("build/util.o", "object");
+    std::env::set_current_dir(env.path()).unwrap();
+
+    // Mark the directory as a unit, then reset a single file inside it
+    run(Config {
+        quiet: true,
+        files: vec![PathBuf::from("build")],
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(has_any_ignore_attribute(&env.path().join("build")));
+    let tracked = TrackedFiles::load(env.path()).unwrap();
+    assert!(tracked.marked_files.contains(&PathBuf::from("build")));
+
+    run(Config {
+        action: Action::Reset,
+        quiet: true,
+        files: vec![PathBuf::from("build/main.o")],
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(!has_any_ignore_attribute(&env.path().join("build")));
+    assert!(!has_any_ignore_attribute(&object));
+    let tracked = TrackedFiles::load(env.path()).unwrap();
+    assert!(!tracked.marked_files.contains(&PathBuf::from("build")));
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+#[serial]
+fn test_reset_pattern_unmarks_every_match_in_tree() {
+    use dbx_ignore::run_with_summary;
+    use dbx_ignore::tracked_files::TrackedFiles;
+    use dbx_ignore::utils::platform_utils::{
+        DEFAULT_MARKER_VALUE, add_ignore_attributes, has_any_ignore_attribute,
+    };
+
+    let env = TestEnvironment::new();
+    let top = env.create_file("top.log", "log");
+    env.create_dir("logs/old");
+    let nested = env.create_file("logs/old/nested.log", "log");
+    let never_marked = env.create_file("logs/fresh.log", "log");
+    let kept = env.create_file("notes.txt", "text");
+    std::env::set_current_dir(env.path()).unwrap();
+
+    // One match marked through the tool, one by hand, one never
+    run(Config {
+        quiet: true,
+        files: vec![PathBuf::from("*.log")],
+        patterns: vec!["*.log".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    add_ignore_attributes(&nested, false, DEFAULT_MARKER_VALUE).unwrap();
+    add_ignore_attributes(&kept, false, DEFAULT_MARKER_VALUE).unwrap();
+    assert!(
+        TrackedFiles::load(env.path())
+            .unwrap()
+            .patterns
+            .contains(&"*.log".to_string())
+    );
+
+    let summary = run_with_summary(Config {
+        action: Action::Reset,
+        quiet: true,
+        files: vec![PathBuf::from("*.log")],
+        patterns: vec!["*.log".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(summary.errors.is_empty());
+    assert!(!has_any_ignore_attribute(&top));
+    assert!(!has_any_ignore_attribute(&nested));
+    assert!(!has_any_ignore_attribute(&never_marked));
+    assert!(has_any_ignore_attribute(&kept));
+    assert!(TrackedFiles::load(env.path()).unwrap().patterns.is_empty());
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::TestEnvironment;
+use serde_json::Value;
+use std::process::Command;
+
+fn run_json(dir: &std::path::Path, args: &[&str]) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute binary");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn type_matches(value: &Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_u64() || value.is_i64(),
+        "null" => value.is_null(),
+        _ => panic!("unsupported type {}", ty),
+    }
+}
+
+/// Check `value` against the subset of JSON Schema the published schemas use
+fn validate(value: &Value, schema: &Value, at: &str) {
+    if let Some(expected) = schema.get("const") {
+        assert_eq!(value, expected, "{}", at);
+    }
+    if let Some(ty) = schema.get("type") {
+        let types: Vec<&str> = match ty {
+            Value::String(ty) => vec![ty.as_str()],
+            Value::Array(tys) => tys.iter().map(|t| t.as_str().unwrap()).collect(),
+            _ => panic!("bad type at {}", at),
+        };
+        assert!(
+            types.iter().any(|ty| type_matches(value, ty)),
+            "{}: {} is not {:?}",
+            at,
+            value,
+            types
+        );
+    }
+    if let (Some(minimum), Some(n)) = (schema.get("minimum"), value.as_i64()) {
+        assert!(n >= minimum.as_i64().unwrap(), "{}", at);
+    }
+    if let Some(object) = value.as_object() {
+        for required in schema["required"].as_array().into_iter().flatten() {
+            let key = required.as_str().unwrap();
+            assert!(object.contains_key(key), "{}: missing {}", at, key);
+        }
+        for (key, field) in object {
+            let at = format!("{}.{}", at, key);
+            match schema["properties"].get(key) {
+                Some(property) => validate(field, property, &at),
+                None => match &schema["additionalProperties"] {
+                    Value::Bool(false) => panic!("{}: not in schema", at),
+                    Value::Object(_) => validate(field, &schema["additionalProperties"], &at),
+                    _ => {}
+                },
+            }
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            validate(item, items, &format!("{}[{}]", at, i));
+        }
+    }
+}
+
+#[test]
+fn test_status_json_validates_against_schema() {
+    let env = TestEnvironment::new();
+    let marked = env.create_file("marked.log", "log");
+    env.create_file("plain.txt", "plain");
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&marked, false, b"1").unwrap();
+
+    let schema = run_json(env.path(), &["--schema", "status"]);
+    assert_eq!(schema["$id"], dbx_ignore::schema::STATUS_SCHEMA_ID);
+
+    let report = run_json(env.path(), &["--status", "--json"]);
+    assert_eq!(report["$schema"], schema["$id"]);
+    validate(&report, &schema, "$");
+
+    let report = run_json(env.path(), &["--status", "--json", "--recursive"]);
+    validate(&report, &schema, "$");
+}
+
+#[test]
+fn test_is_marked_json_validates_against_schema() {
+    let env = TestEnvironment::new();
+    let marked = env.create_file("marked.bin", "data");
+    env.create_file("plain.txt", "plain");
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&marked, false, b"1").unwrap();
+
+    let schema = run_json(env.path(), &["--schema", "is-marked"]);
+    let results = run_json(
+        env.path(),
+        &[
+            "--is-marked",
+            "--json",
+            "marked.bin",
+            "plain.txt",
+            "gone.txt",
+        ],
+    );
+    assert_eq!(results.as_array().unwrap().len(), 3);
+    validate(&results, &schema, "$");
+}
+
+#[test]
+fn test_schema_rejects_unknown_output() {
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--schema", "plan"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(!output.status.success());
+}
@@ -0,0 +1,55 @@
+use dbx_ignore::selective_sync::SelectiveSyncReport;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+#[test]
+fn test_selective_sync_folder_aggregation() {
+    let root = PathBuf::from("/repo");
+    let all_files: Vec<PathBuf> = [
+        "build/out.o",
+        "build/nested/lib.a",
+        "media/video.mp4",
+        "media/notes.txt",
+        "src/main.rs",
+        "README.md",
+    ]
+    .iter()
+    .map(|f| root.join(f))
+    .collect();
+
+    // build/ is fully ignored via its files, media/ only partially, cache/ is an empty marked folder
+    let marked: HashSet<PathBuf> = [
+        "build/out.o",
+        "build/nested/lib.a",
+        "media/video.mp4",
+        "cache",
+    ]
+    .iter()
+    .map(|f| root.join(f))
+    .collect();
+
+    let report = SelectiveSyncReport::from_marked(&root, &all_files, &marked);
+
+    assert_eq!(
+        report.fully_ignored,
+        vec![PathBuf::from("build"), PathBuf::from("cache")]
+    );
+    assert_eq!(report.partially_ignored, vec![PathBuf::from("media")]);
+}
+
+#[test]
+fn test_selective_sync_marked_directory_covers_contents() {
+    let root = PathBuf::from("/repo");
+    let all_files = vec![
+        root.join("node_modules/a/index.js"),
+        root.join("node_modules/b/index.js"),
+        root.join("src/app.js"),
+    ];
+    let marked: HashSet<PathBuf> = [root.join("node_modules")].into_iter().collect();
+
+    let report = SelectiveSyncReport::from_marked(&root, &all_files, &marked);
+
+    // Nested folders of a fully ignored folder are not listed separately
+    assert_eq!(report.fully_ignored, vec![PathBuf::from("node_modules")]);
+    assert!(report.partially_ignored.is_empty());
+}
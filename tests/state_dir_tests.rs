@@ -0,0 +1,150 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::tracked_files::TrackedFiles;
+use dbx_ignore::utils::{git_utils, path_utils};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+#[test]
+fn test_scans_never_see_state_files_during_writes() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().expect("Failed to init git repo");
+    env.create_gitignore(&["*.log", ".dbx-ignore/"]);
+    env.create_file("app.log", "log");
+    env.create_file("main.rs", "fn main() {}");
+
+    let root = env.path().to_path_buf();
+    let stop = Arc::new(AtomicBool::new(false));
+
+    // Continuously rewrite the state file, leaving temp files around mid-write
+    let writer = {
+        let root = root.clone();
+        let stop = stop.clone();
+        thread::spawn(move || {
+            let mut i = 0;
+            while !stop.load(Ordering::Relaxed) {
+                let mut tracked = TrackedFiles::default();
+                tracked.add_files(&[PathBuf::from(format!("file{}.log", i))]);
+                tracked.save(&root).unwrap();
+                i += 1;
+            }
+        })
+    };
+
+    for _ in 0..50 {
+        let ignored = git_utils::get_git_ignored_files_in_path(&root).unwrap();
+        assert!(
+            ignored
+                .iter()
+                .all(|p| !path_utils::is_in_state_dir(&root, p))
+        );
+        assert!(ignored.iter().any(|p| p.ends_with("app.log")));
+
+        let matched = git_utils::find_files_matching_patterns(
+            &root,
+            &["*".to_string()],
+            false,
+            path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD,
+        )
+        .unwrap();
+        assert!(
+            matched
+                .iter()
+                .all(|p| !path_utils::is_in_state_dir(&root, p))
+        );
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    writer.join().unwrap();
+}
+
+#[test]
+fn test_is_in_state_dir() {
+    let root = PathBuf::from("/repo");
+    assert!(path_utils::is_in_state_dir(
+        &root,
+        &root.join(".dbx-ignore/tracked_files.json")
+    ));
+    assert!(path_utils::is_in_state_dir(
+        &root,
+        &root.join(".dbx-ignore/.tmpAbC123")
+    ));
+    assert!(!path_utils::is_in_state_dir(
+        &root,
+        &root.join("src/main.rs")
+    ));
+}
+
+#[test]
+fn test_global_state_location_migrates_local_state_once() {
+    use dbx_ignore::state_location::global_state_dir;
+    use std::process::Command;
+
+    let env = TestEnvironment::new();
+    let repo = env.create_dir("repo");
+    std::fs::write(repo.join("app.log"), "log").unwrap();
+    std::fs::write(repo.join("old.log"), "log").unwrap();
+    std::fs::write(repo.join("later.log"), "log").unwrap();
+    let config_home = env.create_dir("config");
+    let config_dir = config_home.join("dbx-ignore");
+
+    let run = |global: bool, file: &str| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"));
+        command
+            .args(["--quiet", file])
+            .env("XDG_CONFIG_HOME", &config_home)
+            .current_dir(&repo);
+        let config = if global {
+            "state_location = \"global\"\n"
+        } else {
+            ""
+        };
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(config_dir.join("config.toml"), config).unwrap();
+        let output = command.output().expect("Failed to execute binary");
+        assert!(output.status.success(), "{:?}", output);
+    };
+    let local_state = repo.join(".dbx-ignore").join("tracked_files.json");
+    let global_state = global_state_dir(&config_dir, &repo).join("tracked_files.json");
+    let tracked = |file: &std::path::Path| -> Vec<String> {
+        let state: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(file).unwrap()).unwrap();
+        let mut files: Vec<String> = state["marked_files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|path| path.as_str().unwrap().to_string())
+            .collect();
+        files.sort();
+        files
+    };
+
+    // Repo-local state from before the switch is carried over to the global location
+    run(false, "old.log");
+    assert!(local_state.is_file());
+    run(true, "app.log");
+    assert!(global_state.is_file());
+    assert!(
+        tracked(&global_state)
+            .iter()
+            .any(|f| f.ends_with("old.log"))
+    );
+    assert!(
+        tracked(&global_state)
+            .iter()
+            .any(|f| f.ends_with("app.log"))
+    );
+    assert!(!tracked(&local_state).iter().any(|f| f.ends_with("app.log")));
+
+    // With both present, nothing is migrated again
+    run(false, "later.log");
+    run(true, "app.log");
+    assert!(
+        !tracked(&global_state)
+            .iter()
+            .any(|f| f.ends_with("later.log"))
+    );
+}
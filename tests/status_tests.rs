@@ -145,3 +145,238 @@ fn test_status_hidden_files_excluded() {
     assert_eq!(status.total_files, 1);
     assert!(status.has_gitignore); // .gitignore is detected even though hidden
 }
+
+#[test]
+#[serial]
+fn test_status_and_marking_warn_when_root_is_ignored() {
+    let env = TestEnvironment::new();
+    let root = env.create_dir("project");
+    fs::write(root.join("file.txt"), "content").unwrap();
+
+    // Mark the directory we're about to operate from
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&root, true, b"1").unwrap();
+
+    let original_dir = std::env::current_dir().unwrap();
+    std::env::set_current_dir(&root).unwrap();
+    let status = StatusInfo::gather().unwrap();
+    std::env::set_current_dir(original_dir).unwrap();
+    assert_eq!(status.ignored_ancestor.as_deref(), Some(root.as_path()));
+
+    // A subsequent marking operation should warn and suggest a reset
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(&root)
+        .args(["--dry-run", "file.txt"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("is already marked as ignored"));
+    assert!(stdout.contains("--reset"));
+
+    // An unmarked root produces no warning
+    let other = env.create_dir("other");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(&other)
+        .args(["--dry-run", "--status"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("is marked as ignored"));
+}
+
+/// Handler with a current and a legacy attribute, reading "presence" from file contents
+struct MockDualAttributeHandler;
+
+impl dbx_ignore::traits::PlatformHandler for MockDualAttributeHandler {
+    fn get_target_attributes() -> &'static [&'static str] {
+        &["mock.current", "mock.legacy"]
+    }
+
+    fn has_attribute(path: &std::path::Path, attr: &str) -> anyhow::Result<bool> {
+        Ok(Self::is_expected_attribute(attr) && Self::attribute_present(path, attr)?)
+    }
+
+    fn attribute_present(path: &std::path::Path, attr: &str) -> anyhow::Result<bool> {
+        Ok(fs::read_to_string(path)?.contains(attr))
+    }
+
+    fn is_expected_attribute(attr: &str) -> bool {
+        attr == "mock.current"
+    }
+
+    fn add_attribute(_path: &std::path::Path, _attr: &str, _value: &[u8]) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn remove_attribute(_path: &std::path::Path, _attr: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn platform_name() -> &'static str {
+        "mock"
+    }
+}
+
+#[test]
+fn test_attribute_breakdown_flags_unexpected_variants() {
+    use dbx_ignore::utils::platform_utils::attribute_breakdown_with;
+
+    let env = TestEnvironment::new();
+    let current = env.create_file("current.txt", "mock.current");
+    let legacy = env.create_file("legacy.txt", "mock.legacy");
+    let both = env.create_file("both.txt", "mock.current mock.legacy");
+    let none = env.create_file("none.txt", "");
+
+    let breakdown = attribute_breakdown_with::<MockDualAttributeHandler>(&current);
+    assert_eq!(breakdown.present, vec!["mock.current"]);
+    assert!(!breakdown.has_unexpected());
+
+    let breakdown = attribute_breakdown_with::<MockDualAttributeHandler>(&legacy);
+    assert_eq!(breakdown.present, vec!["mock.legacy"]);
+    assert_eq!(breakdown.unexpected, vec!["mock.legacy"]);
+
+    let breakdown = attribute_breakdown_with::<MockDualAttributeHandler>(&both);
+    assert_eq!(breakdown.present, vec!["mock.current", "mock.legacy"]);
+    assert_eq!(breakdown.unexpected, vec!["mock.legacy"]);
+
+    let breakdown = attribute_breakdown_with::<MockDualAttributeHandler>(&none);
+    assert!(breakdown.present.is_empty());
+}
+
+#[test]
+#[serial]
+fn test_status_records_attributes_per_file() {
+    use dbx_ignore::platforms::CurrentPlatform;
+    use dbx_ignore::traits::PlatformHandler;
+
+    let env = TestEnvironment::new();
+    let marked = env.create_file("marked.txt", "content");
+    let plain = env.create_file("plain.txt", "content");
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&marked, true, b"1").unwrap();
+
+    let original_dir = std::env::current_dir().unwrap();
+    std::env::set_current_dir(env.path()).unwrap();
+    let status = StatusInfo::gather().unwrap();
+    std::env::set_current_dir(original_dir).unwrap();
+
+    let marked_attrs = &status.attributes[&status.ignored_files[0]];
+    assert!(!marked_attrs.present.is_empty());
+    assert!(
+        marked_attrs
+            .present
+            .iter()
+            .all(|attr| CurrentPlatform::is_expected_attribute(attr))
+    );
+    assert!(
+        status
+            .attributes
+            .iter()
+            .any(|(path, a)| path.ends_with(plain.file_name().unwrap()) && a.present.is_empty())
+    );
+    assert!(status.inconsistent_files.is_empty());
+}
+
+#[test]
+fn test_status_json_output() {
+    let env = TestEnvironment::new();
+    let marked = env.create_file("marked.log", "log");
+    env.create_file("plain.txt", "plain");
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&marked, false, b"1").unwrap();
+
+    let status_json = |dir: &std::path::Path| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(dir)
+            .args(["--status", "--json"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let report = status_json(env.path());
+    assert_eq!(report["total_files"], 2);
+    assert_eq!(report["ignored_files"], serde_json::json!(["marked.log"]));
+    assert_eq!(
+        report["non_ignored_files"],
+        serde_json::json!(["plain.txt"])
+    );
+    assert_eq!(report["has_gitignore"], false);
+    assert!(report["daemon_status"].is_null());
+
+    // An empty directory still produces the full object
+    let empty = env.create_dir("empty");
+    let report = status_json(&empty);
+    assert_eq!(report["total_files"], 0);
+    assert_eq!(report["ignored_files"], serde_json::json!([]));
+    assert_eq!(report["non_ignored_files"], serde_json::json!([]));
+}
+
+#[test]
+fn test_status_short_output() {
+    let env = TestEnvironment::new();
+    let marked = env.create_file("marked.log", "log");
+    let marked_dir = env.create_dir("build");
+    env.create_file("plain.txt", "plain");
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&marked, false, b"1").unwrap();
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&marked_dir, false, b"1").unwrap();
+
+    let status_short = |color: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(env.path())
+            .args(["--status", "--short", "--color", color])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(status_short("never"), "dbx: 2 ignored\n");
+    assert_eq!(
+        dbx_ignore::status::short_line(env.path()).unwrap(),
+        "dbx: 2 ignored"
+    );
+}
+
+#[test]
+#[serial]
+fn test_status_recursive_counts() {
+    let env = TestEnvironment::new();
+    env.create_file("top.txt", "top");
+    env.create_dir("src/nested");
+    env.create_file("src/main.rs", "fn main() {}");
+    let marked_log = env.create_file("src/nested/debug.log", "log");
+    let build = env.create_dir("build");
+    env.create_file("build/out.bin", "bin");
+    env.create_dir(".git");
+    env.create_file(".git/HEAD", "ref: refs/heads/main");
+    env.create_dir(".dbx-ignore");
+    env.create_file(".dbx-ignore/tracked_files.json", "{}");
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&marked_log, false, b"1").unwrap();
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&build, false, b"1").unwrap();
+
+    let original_dir = std::env::current_dir().unwrap();
+    std::env::set_current_dir(env.path()).unwrap();
+    let flat = StatusInfo::gather().unwrap();
+    let recursive = StatusInfo::gather_recursive().unwrap();
+    std::env::set_current_dir(original_dir).unwrap();
+
+    assert_eq!(flat.total_files, 3);
+    assert!(!flat.recursive);
+
+    // top.txt, build, src, src/main.rs, src/nested, src/nested/debug.log; the marked
+    // build directory covers its contents, and .git/.dbx-ignore are skipped
+    assert!(recursive.recursive);
+    assert_eq!(recursive.total_files, 6);
+    let ignored: Vec<_> = recursive
+        .ignored_files
+        .iter()
+        .map(|f| f.strip_prefix(env.path()).unwrap().to_path_buf())
+        .collect();
+    assert_eq!(
+        ignored,
+        vec![
+            std::path::PathBuf::from("build"),
+            std::path::PathBuf::from("src/nested/debug.log")
+        ]
+    );
+    assert_eq!(recursive.non_ignored_files.len(), 4);
+}
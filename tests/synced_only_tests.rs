@@ -0,0 +1,63 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::utils::platform_utils::{self, DEFAULT_MARKER_VALUE, add_ignore_attributes};
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_retain_synced_keeps_only_synced_files() {
+    let env = TestEnvironment::new();
+    let synced = env.create_file("synced.log", "log");
+    let online_only = env.create_file("online.log", "log");
+    let unmanaged = env.create_file("elsewhere.log", "log");
+
+    // Stand-in for Dropbox's metadata: only one file is synced
+    let is_synced = |path: &Path| path.ends_with("synced.log");
+
+    let mut candidates = vec![synced.clone(), online_only, unmanaged];
+    let skipped = platform_utils::retain_synced(&mut candidates, is_synced);
+
+    assert_eq!(candidates, vec![synced]);
+    assert_eq!(skipped, 2);
+}
+
+#[test]
+fn test_already_excluded_files_are_not_synced() {
+    let env = TestEnvironment::new();
+    let plain = env.create_file("plain.log", "log");
+    let marked = env.create_file("marked.log", "log");
+    let excluded_dir = env.create_dir("excluded");
+    let inside = env.create_file("excluded/inner.log", "log");
+    add_ignore_attributes(&marked, false, DEFAULT_MARKER_VALUE).unwrap();
+    add_ignore_attributes(&excluded_dir, false, DEFAULT_MARKER_VALUE).unwrap();
+
+    assert!(platform_utils::is_synced(&plain));
+    assert!(!platform_utils::is_synced(&marked));
+    assert!(!platform_utils::is_synced(&inside));
+}
+
+#[test]
+fn test_synced_only_reports_skipped_candidates() {
+    let env = TestEnvironment::new();
+    env.create_file("plain.log", "log");
+    let marked = env.create_file("marked.log", "log");
+    add_ignore_attributes(&marked, false, DEFAULT_MARKER_VALUE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
+        .args(["--synced-only", "*.log"])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("skipped 1 candidate(s) Dropbox isn't syncing"),
+        "{}",
+        stdout
+    );
+    assert!(platform_utils::has_any_ignore_attribute(
+        &env.path().join("plain.log")
+    ));
+}
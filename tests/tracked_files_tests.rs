@@ -1,5 +1,5 @@
 use dbx_ignore::tracked_files::TrackedFiles;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
 #[test]
@@ -65,12 +65,17 @@ fn test_tracked_files_patterns() {
     assert_eq!(tracked.patterns.len(), 3);
     assert!(tracked.patterns.contains(&"*.log".to_string()));
 
+    // Only patterns not tracked yet are reported as added
+    let added = tracked.add_patterns(&["*.log".to_string(), "*.bak".to_string()]);
+    assert_eq!(added, vec!["*.bak".to_string()]);
+    assert_eq!(tracked.patterns.len(), 4);
+
     // Save and reload
     tracked.save(test_path).unwrap();
     let loaded = TrackedFiles::load(test_path).unwrap();
 
     // Verify patterns persist
-    assert_eq!(loaded.patterns.len(), 3);
+    assert_eq!(loaded.patterns.len(), 4);
     assert!(loaded.patterns.contains(&"*.log".to_string()));
     assert!(loaded.patterns.contains(&"build/**".to_string()));
     assert!(loaded.patterns.contains(&"*.tmp".to_string()));
@@ -88,8 +93,13 @@ fn test_tracked_files_remove_patterns() {
     ]);
     assert_eq!(tracked.patterns.len(), 3);
 
-    // Remove some patterns
-    tracked.remove_patterns(&["*.tmp".to_string(), "*.cache".to_string()]);
+    // Remove some patterns, one of them not tracked
+    let removed = tracked.remove_patterns(&[
+        "*.tmp".to_string(),
+        "*.cache".to_string(),
+        "*.bak".to_string(),
+    ]);
+    assert_eq!(removed, vec!["*.tmp".to_string(), "*.cache".to_string()]);
 
     // Verify only *.log remains
     assert_eq!(tracked.patterns.len(), 1);
@@ -155,4 +165,118 @@ fn test_tracked_files_duplicate_handling() {
 
     // Should only be tracked once
     assert_eq!(tracked.marked_files.len(), 1);
+
+    // Different spellings of the same file are one entry, relative to the state's root
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::create_dir(root.join("logs")).unwrap();
+    std::fs::write(root.join("logs/foo.log"), "log").unwrap();
+    let mut tracked = TrackedFiles::load(root).unwrap();
+    tracked.add_files(&[
+        PathBuf::from("logs/foo.log"),
+        PathBuf::from("./logs/foo.log"),
+        PathBuf::from("logs/../logs/foo.log"),
+        root.join("logs/foo.log"),
+    ]);
+    assert_eq!(tracked.marked_files.len(), 1);
+    assert!(tracked.marked_files.contains(Path::new("logs/foo.log")));
+    assert!(tracked.is_tracked(&root.join("logs/./foo.log")));
+    assert!(tracked.is_tracked(Path::new("./logs/foo.log")));
+
+    tracked.remove_files(&[root.join("logs/foo.log")]);
+    assert!(tracked.marked_files.is_empty());
+}
+
+#[test]
+fn test_load_merges_differently_spelled_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join("foo.log"), "log").unwrap();
+
+    // State written before paths were normalized
+    let mut tracked = TrackedFiles::default();
+    tracked.add_files(&[PathBuf::from("foo.log"), root.join("foo.log")]);
+    tracked.tag_files(&[root.join("foo.log")], "logs");
+    tracked.save(root).unwrap();
+    assert_eq!(tracked.marked_files.len(), 2);
+
+    let loaded = TrackedFiles::load(root).unwrap();
+    assert_eq!(loaded.marked_files.len(), 1);
+    assert!(loaded.is_tracked(Path::new("foo.log")));
+    assert_eq!(loaded.tag_of(Path::new("./foo.log")), Some("logs"));
+}
+
+#[test]
+fn test_load_removes_orphaned_temp_files() {
+    use filetime::FileTime;
+    use std::time::{Duration, SystemTime};
+
+    let temp_dir = TempDir::new().unwrap();
+    let test_path = temp_dir.path();
+
+    let mut tracked = TrackedFiles::default();
+    tracked.add_files(&[PathBuf::from("file1.txt")]);
+    tracked.save(test_path).unwrap();
+
+    // A write killed between create and persist leaves its temp file behind
+    let state_dir = test_path.join(".dbx-ignore");
+    let orphan = state_dir.join(".tmpAbC123");
+    std::fs::write(&orphan, "{\"partial\":").unwrap();
+    let an_hour_ago = FileTime::from_system_time(SystemTime::now() - Duration::from_secs(3600));
+    filetime::set_file_mtime(&orphan, an_hour_ago).unwrap();
+
+    // A fresh temp file may belong to a write in progress elsewhere
+    let in_progress = state_dir.join(".tmpXyZ789");
+    std::fs::write(&in_progress, "{").unwrap();
+
+    let loaded = TrackedFiles::load(test_path).unwrap();
+    assert!(loaded.is_tracked(&PathBuf::from("file1.txt")));
+    assert!(!orphan.exists());
+    assert!(in_progress.exists());
+
+    // Saving afterwards leaves no temp files of its own
+    loaded.save(test_path).unwrap();
+    let leftovers: Vec<_> = std::fs::read_dir(&state_dir)
+        .unwrap()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(".tmp"))
+        .map(|entry| entry.file_name())
+        .collect();
+    assert_eq!(leftovers, vec![in_progress.file_name().unwrap().to_owned()]);
+}
+
+#[test]
+fn test_runs_report_delta_against_previous_state() {
+    let temp_dir = TempDir::new().unwrap();
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(temp_dir.path())
+            .args(args)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    for name in ["a.log", "b.log", "keep.txt"] {
+        std::fs::write(temp_dir.path().join(name), name).unwrap();
+    }
+
+    let stdout = run(&["*.log"]);
+    assert!(stdout.contains("+2 / -0 vs last run"), "{}", stdout);
+
+    // Only the new match counts, not the files marked last time
+    std::fs::write(temp_dir.path().join("c.log"), "c").unwrap();
+    let stdout = run(&["*.log"]);
+    assert!(stdout.contains("+1 / -0 vs last run"), "{}", stdout);
+
+    let stdout = run(&["--reset", "a.log"]);
+    assert!(stdout.contains("+0 / -1 vs last run"), "{}", stdout);
+
+    let mut tracked = TrackedFiles::default();
+    tracked.add_files(&[PathBuf::from("x"), PathBuf::from("y")]);
+    let previous = [PathBuf::from("y"), PathBuf::from("z")]
+        .into_iter()
+        .collect();
+    let delta = tracked.delta_since(&previous);
+    assert_eq!((delta.added, delta.removed), (1, 1));
 }
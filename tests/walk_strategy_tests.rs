@@ -0,0 +1,62 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::utils::path_utils::{self, WalkStrategy};
+use dbx_ignore::utils::pattern_matcher::PatternMatcher;
+
+/// Create `count` top-level files plus a `logs/` directory holding one log
+fn create_entries(env: &TestEnvironment, count: usize) {
+    for i in 0..count {
+        env.create_file(&format!("file{:03}.txt", i), "text");
+    }
+    env.create_dir("logs");
+    env.create_file("logs/app.log", "log");
+}
+
+#[test]
+fn test_walk_strategy_follows_threshold() {
+    let env = TestEnvironment::new();
+    create_entries(&env, 9);
+
+    // 10 top-level entries: sequential below the threshold, parallel at it
+    assert_eq!(
+        path_utils::walk_strategy(env.path(), 11),
+        WalkStrategy::Sequential
+    );
+    assert_eq!(
+        path_utils::walk_strategy(env.path(), 10),
+        WalkStrategy::Parallel
+    );
+
+    // 0 always walks in parallel, even an empty directory
+    let empty = env.create_dir("empty");
+    assert_eq!(path_utils::walk_strategy(&empty, 0), WalkStrategy::Parallel);
+
+    assert_eq!(
+        path_utils::walk_strategy(env.path(), path_utils::DEFAULT_PARALLEL_WALK_THRESHOLD),
+        WalkStrategy::Sequential
+    );
+}
+
+#[test]
+fn test_parallel_and_sequential_walks_agree() {
+    let env = TestEnvironment::new();
+    create_entries(&env, 20);
+    let patterns = ["*.log".to_string(), "file01*".to_string()];
+    let walk = |threshold: usize| {
+        let matcher = PatternMatcher::new(env.path(), &patterns, false)
+            .unwrap()
+            .with_parallel_walk_threshold(threshold);
+        (
+            path_utils::walk_files(env.path(), None, threshold),
+            matcher.find_matching_files(env.path()).unwrap(),
+        )
+    };
+
+    let sequential = walk(usize::MAX);
+    let parallel = walk(0);
+
+    assert_eq!(sequential.0.len(), 21);
+    assert_eq!(sequential.1.len(), 11);
+    assert_eq!(sequential, parallel);
+}
@@ -0,0 +1,436 @@
+mod common;
+
+use common::TestEnvironment;
+use dbx_ignore::core::watch::WatchConfig;
+use dbx_ignore::test_support::WatchHarness;
+use dbx_ignore::tracked_files::TrackedFiles;
+use dbx_ignore::utils::platform_utils::{self, has_any_ignore_attribute};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+#[tokio::test(start_paused = true)]
+async fn test_created_file_gets_marked() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    let mut tracked = TrackedFiles::default();
+    tracked.add_patterns(&["*.log".to_string()]);
+    tracked.save(env.path()).unwrap();
+
+    let mut harness = WatchHarness::start(WatchConfig::new(env.path().to_path_buf()));
+    let initial = harness.next_scan().await.unwrap();
+    assert_eq!(initial.marked, 0);
+
+    let log_file = env.create_file("app.log", "log");
+    let other_file = env.create_file("notes.txt", "notes");
+    harness.file_created(&log_file);
+    harness.file_created(&other_file);
+
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!(summary.marked, 1);
+    assert!(has_any_ignore_attribute(&log_file));
+    assert!(!has_any_ignore_attribute(&other_file));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_scans_deferred_while_syncing() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    let mut tracked = TrackedFiles::default();
+    tracked.add_patterns(&["*.log".to_string()]);
+    tracked.save(env.path()).unwrap();
+
+    let syncing = Arc::new(AtomicBool::new(false));
+    let detector_flag = syncing.clone();
+    let mut config = WatchConfig::new(env.path().to_path_buf());
+    config.defer_during_sync = true;
+    config.sync_detector = Arc::new(move |_| detector_flag.load(Ordering::SeqCst));
+
+    let mut harness = WatchHarness::start(config);
+    harness.next_scan().await.unwrap();
+
+    syncing.store(true, Ordering::SeqCst);
+    let log_file = env.create_file("app.log", "log");
+    harness.file_created(&log_file);
+
+    let deferred = tokio::time::timeout(Duration::from_secs(60), harness.next_scan()).await;
+    assert!(deferred.is_err(), "scan ran while syncing");
+    assert!(!has_any_ignore_attribute(&log_file));
+
+    // The pending change is picked up once syncing settles
+    syncing.store(false, Ordering::SeqCst);
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!(summary.marked, 1);
+    assert!(has_any_ignore_attribute(&log_file));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_periodic_rescan_without_events() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    let mut tracked = TrackedFiles::default();
+    tracked.add_patterns(&["*.log".to_string()]);
+    tracked.save(env.path()).unwrap();
+
+    let interval = Duration::from_secs(600);
+    let mut config = WatchConfig::new(env.path().to_path_buf());
+    config.rescan_interval = Some(interval);
+
+    let mut harness = WatchHarness::start(config);
+    harness.next_scan().await.unwrap();
+    let started = tokio::time::Instant::now();
+
+    // The backend misses this file entirely: no event is delivered
+    let log_file = env.create_file("app.log", "log");
+
+    let summary = harness.next_scan().await.unwrap();
+    assert!(started.elapsed() >= interval);
+    assert_eq!(summary.marked, 1);
+    assert!(has_any_ignore_attribute(&log_file));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_scans_held_while_paused() {
+    use dbx_ignore::core::daemon;
+
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    let mut tracked = TrackedFiles::default();
+    tracked.add_patterns(&["*.log".to_string()]);
+    tracked.save(env.path()).unwrap();
+
+    let mut harness = WatchHarness::start(WatchConfig::new(env.path().to_path_buf()));
+    harness.next_scan().await.unwrap();
+
+    daemon::set_paused(env.path(), true).unwrap();
+    let log_file = env.create_file("app.log", "log");
+    harness.file_created(&log_file);
+
+    let held = tokio::time::timeout(Duration::from_secs(60), harness.next_scan()).await;
+    assert!(held.is_err(), "scan ran while paused");
+    assert!(!has_any_ignore_attribute(&log_file));
+
+    // Changes seen while paused are scanned once the watcher resumes
+    daemon::set_paused(env.path(), false).unwrap();
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!(summary.marked, 1);
+    assert!(has_any_ignore_attribute(&log_file));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_metrics_file_counts_scans() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    let mut tracked = TrackedFiles::default();
+    tracked.add_patterns(&["*.log".to_string()]);
+    tracked.save(env.path()).unwrap();
+
+    let metrics_file = env.path().join("metrics").join("dbx-ignore.prom");
+    let mut config = WatchConfig::new(env.path().to_path_buf());
+    config.metrics_file = Some(metrics_file.clone());
+
+    let mut harness = WatchHarness::start(config);
+    harness.next_scan().await.unwrap();
+
+    let log_file = env.create_file("app.log", "log");
+    harness.file_created(&log_file);
+    harness.next_scan().await.unwrap();
+
+    let metrics = std::fs::read_to_string(&metrics_file).unwrap();
+    assert!(metrics.contains("# TYPE dbx_ignore_scans_total counter"));
+    assert!(metrics.contains("\ndbx_ignore_scans_total 2\n"));
+    assert!(metrics.contains("\ndbx_ignore_files_marked_total 1\n"));
+    assert!(metrics.contains("\ndbx_ignore_files_unmarked_total 0\n"));
+    assert!(metrics.contains("\ndbx_ignore_errors_total 0\n"));
+    assert!(!metrics.contains("\ndbx_ignore_last_scan_timestamp_seconds 0\n"));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_error_notifier_rate_limits() {
+    use dbx_ignore::core::notifications::{ErrorNotifier, MIN_NOTIFY_INTERVAL};
+    use std::sync::Mutex;
+
+    let notifications = Arc::new(Mutex::new(Vec::<String>::new()));
+    let sent = notifications.clone();
+    let mut notifier = ErrorNotifier::new(
+        Arc::new(move |_, body| sent.lock().unwrap().push(body.to_string())),
+        "/repo".into(),
+    );
+
+    notifier.report(0, "clean");
+    assert!(notifications.lock().unwrap().is_empty());
+
+    notifier.report(1, "1 path(s) could not be updated");
+    // Held back within the interval, then mentioned in the next notification
+    notifier.report(2, "2 path(s) could not be updated");
+    assert_eq!(notifications.lock().unwrap().len(), 1);
+
+    tokio::time::advance(MIN_NOTIFY_INTERVAL).await;
+    notifier.report(1, "scan failed");
+    let notifications = notifications.lock().unwrap();
+    assert_eq!(notifications.len(), 2);
+    assert_eq!(notifications[0], "/repo: 1 path(s) could not be updated");
+    assert_eq!(
+        notifications[1],
+        "/repo: scan failed (2 more error(s) since the last notification)"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test(start_paused = true)]
+async fn test_notify_on_error_only_for_failed_scans() {
+    use std::process::Command;
+    use std::sync::Mutex;
+
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    let mut tracked = TrackedFiles::default();
+    tracked.add_patterns(&["*.log".to_string()]);
+    tracked.save(env.path()).unwrap();
+
+    let notifications = Arc::new(Mutex::new(Vec::<String>::new()));
+    let sent = notifications.clone();
+    let mut config = WatchConfig::new(env.path().to_path_buf());
+    config.notify_on_error = true;
+    config.notifier = Arc::new(move |_, body| sent.lock().unwrap().push(body.to_string()));
+
+    env.create_file("app.log", "log");
+    let mut harness = WatchHarness::start(config);
+    let clean = harness.next_scan().await.unwrap();
+    assert_eq!((clean.marked, clean.errors), (1, 0));
+    assert!(notifications.lock().unwrap().is_empty());
+
+    // An immutable file can't take the marker; setting the flag needs root and ext4/xfs/btrfs
+    let locked = env.create_file("locked.log", "log");
+    let immutable = Command::new("chattr").arg("+i").arg(&locked).status();
+    if !immutable.is_ok_and(|status| status.success()) {
+        eprintln!("skipping the error half: chattr +i is unavailable here");
+        harness.stop().await.unwrap();
+        return;
+    }
+    harness.file_created(&locked);
+    let failed = harness.next_scan().await.unwrap();
+    let _ = Command::new("chattr").arg("-i").arg(&locked).status();
+
+    assert_eq!(failed.errors, 1);
+    let sent = notifications.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].contains("1 path(s) could not be updated"));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_observe_mode_reports_without_changing_markers() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    let mut tracked = TrackedFiles::default();
+    tracked.add_patterns(&["*.log".to_string()]);
+    tracked.save(env.path()).unwrap();
+    let stray = env.create_file("notes.txt", "notes");
+    dbx_ignore::utils::platform_utils::add_ignore_attributes(&stray, false, b"1").unwrap();
+
+    let mut config = WatchConfig::new(env.path().to_path_buf());
+    config.observe = true;
+    let mut harness = WatchHarness::start(config);
+    let initial = harness.next_scan().await.unwrap();
+    assert_eq!(initial.unmarked, 1);
+
+    // Re-scans only look at the changed paths, so the stray marker isn't reported again
+    let first = env.create_file("first.log", "log");
+    harness.file_created(&first);
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!((summary.marked, summary.unmarked), (1, 0));
+
+    let second = env.create_file("second.log", "log");
+    harness.file_created(&second);
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!((summary.marked, summary.unmarked), (1, 0));
+
+    assert!(!has_any_ignore_attribute(&first));
+    assert!(!has_any_ignore_attribute(&second));
+    assert!(has_any_ignore_attribute(&stray));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_change_in_subdirectory_rescans_only_that_subtree() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    let mut tracked = TrackedFiles::default();
+    tracked.add_patterns(&["*.log".to_string()]);
+    tracked.save(env.path()).unwrap();
+    env.create_dir("a");
+    env.create_dir("b");
+    let untouched = env.create_file("b/old.log", "log");
+
+    let mut harness = WatchHarness::start(WatchConfig::new(env.path().to_path_buf()));
+    assert_eq!(harness.next_scan().await.unwrap().marked, 1);
+
+    // Lose a marker without an event: only a scan reaching b/ would restore it
+    dbx_ignore::utils::platform_utils::remove_ignore_attributes(&untouched).unwrap();
+
+    let created = env.create_file("a/new.log", "log");
+    harness.file_created(&created);
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!(summary.marked, 1);
+    assert!(has_any_ignore_attribute(&created));
+    assert!(!has_any_ignore_attribute(&untouched));
+
+    // An ignore-source change falls back to the whole tree
+    let gitignore = env.path().join(".gitignore");
+    std::fs::write(&gitignore, "*.tmp\n").unwrap();
+    harness.file_created(&gitignore);
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!(summary.marked, 1);
+    assert!(has_any_ignore_attribute(&untouched));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_gitignore_mode_marks_only_changed_paths() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    let gitignore = env.create_gitignore(&["*.log"]);
+    env.create_dir("a");
+    env.create_dir("b");
+    let untouched = env.create_file("b/old.log", "log");
+
+    let mut harness = WatchHarness::start(WatchConfig::new(env.path().to_path_buf()));
+    assert_eq!(harness.next_scan().await.unwrap().marked, 1);
+
+    // Lose a marker without an event: only a full scan would restore it
+    platform_utils::remove_ignore_attributes(&untouched).unwrap();
+
+    let created = env.create_file("a/new.log", "log");
+    let kept = env.create_file("a/notes.txt", "notes");
+    harness.file_created(&created);
+    harness.file_created(&kept);
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!((summary.marked, summary.unmarked), (1, 0));
+    assert!(has_any_ignore_attribute(&created));
+    assert!(!has_any_ignore_attribute(&kept));
+    assert!(!has_any_ignore_attribute(&untouched));
+
+    // A .gitignore change falls back to the whole tree
+    std::fs::write(&gitignore, "*.log\n*.tmp\n").unwrap();
+    harness.file_created(&gitignore);
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!(summary.marked, 1);
+    assert!(has_any_ignore_attribute(&untouched));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_collapsed_directory_follows_gitignore_edits() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    let gitignore = env.create_gitignore(&["build/"]);
+    let build = env.create_dir("build");
+    let output = env.create_file("build/out.bin", "bin");
+    platform_utils::add_ignore_attributes(&build, false, platform_utils::DEFAULT_MARKER_VALUE)
+        .unwrap();
+    let mut tracked = TrackedFiles::default();
+    tracked.add_files(std::slice::from_ref(&build));
+    tracked.save(env.path()).unwrap();
+
+    // Still ignored: the directory keeps its marker and its contents aren't marked
+    let mut harness = WatchHarness::start(WatchConfig::new(env.path().to_path_buf()));
+    let initial = harness.next_scan().await.unwrap();
+    assert_eq!((initial.marked, initial.unmarked), (0, 0));
+    assert!(has_any_ignore_attribute(&build));
+    assert!(!has_any_ignore_attribute(&output));
+
+    // Un-ignoring the directory removes its marker
+    std::fs::write(&gitignore, "*.log\n").unwrap();
+    harness.file_created(&gitignore);
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!(summary.unmarked, 1);
+    assert!(!has_any_ignore_attribute(&build));
+    assert!(!has_any_ignore_attribute(&output));
+
+    // Ignoring it again collapses it back into a single marker
+    std::fs::write(&gitignore, "build/\n").unwrap();
+    harness.file_created(&gitignore);
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!(summary.marked, 1);
+    assert!(has_any_ignore_attribute(&build));
+    assert!(!has_any_ignore_attribute(&output));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_combined_mode_keeps_tracked_and_gitignored_files_marked() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    env.create_gitignore(&["*.log"]);
+    let notes = env.create_file("notes.txt", "notes");
+    let debug_log = env.create_file("debug.log", "log");
+    let stray = env.create_file("stray.txt", "stray");
+    platform_utils::add_ignore_attributes(&notes, false, platform_utils::DEFAULT_MARKER_VALUE)
+        .unwrap();
+    platform_utils::add_ignore_attributes(&stray, false, platform_utils::DEFAULT_MARKER_VALUE)
+        .unwrap();
+    let mut tracked = TrackedFiles::default();
+    tracked.add_files(std::slice::from_ref(&notes));
+    tracked.save(env.path()).unwrap();
+
+    let mut config = WatchConfig::new(env.path().to_path_buf());
+    config.combined = true;
+    let mut harness = WatchHarness::start(config);
+
+    // The git-ignored file is marked, the tracked one kept, and only the stray one unmarked
+    let initial = harness.next_scan().await.unwrap();
+    assert_eq!((initial.marked, initial.unmarked), (1, 1));
+    assert!(has_any_ignore_attribute(&notes));
+    assert!(has_any_ignore_attribute(&debug_log));
+    assert!(!has_any_ignore_attribute(&stray));
+
+    // New git-ignored files are picked up without dropping the tracked one
+    let trace_log = env.create_file("trace.log", "log");
+    harness.file_created(&trace_log);
+    let summary = harness.next_scan().await.unwrap();
+    assert_eq!((summary.marked, summary.unmarked), (1, 0));
+    assert!(has_any_ignore_attribute(&trace_log));
+    assert!(has_any_ignore_attribute(&notes));
+    assert!(has_any_ignore_attribute(&debug_log));
+
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pattern_mode_can_respect_gitignore() {
+    let env = TestEnvironment::new();
+    env.init_git_repo().unwrap();
+    env.create_gitignore(&["vendor.bin"]);
+    let model = env.create_file("model.bin", "model");
+    let vendor = env.create_file("vendor.bin", "vendor");
+    let mut tracked = TrackedFiles::default();
+    tracked.add_patterns(&["*.bin".to_string()]);
+    tracked.save(env.path()).unwrap();
+
+    let mut config = WatchConfig::new(env.path().to_path_buf());
+    config.respect_gitignore = true;
+    let mut harness = WatchHarness::start(config);
+
+    let initial = harness.next_scan().await.unwrap();
+    assert_eq!(initial.marked, 1);
+    assert!(has_any_ignore_attribute(&model));
+    assert!(!has_any_ignore_attribute(&vendor));
+
+    harness.stop().await.unwrap();
+}
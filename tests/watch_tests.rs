@@ -256,3 +256,428 @@ fn test_watch_with_multiple_patterns() {
         .arg("--unwatch")
         .output();
 }
+
+#[test]
+fn test_watch_follows_changing_patterns_file() {
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let temp_dir = TempDir::new().unwrap();
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .output()
+        .expect("Failed to init git");
+
+    let policy = temp_dir.path().join("policy.txt");
+    std::fs::write(&policy, "# central policy\n*.log\n").unwrap();
+    let log_file = temp_dir.path().join("app.log");
+    let tmp_file = temp_dir.path().join("cache.tmp");
+    std::fs::write(&log_file, "log").unwrap();
+    std::fs::write(&tmp_file, "tmp").unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .args([
+            "--watch",
+            "--daemon-mode",
+            "--patterns-file",
+            "policy.txt",
+            "--patterns-refresh",
+            "1s",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start daemon");
+
+    let wait_for = |condition: &dyn Fn() -> bool| {
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while !condition() && std::time::Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(100));
+        }
+        condition()
+    };
+
+    let initial = wait_for(&|| has_any_ignore_attribute(&log_file));
+
+    // An invalid list is rejected and the current patterns stay active
+    std::fs::write(&policy, "").unwrap();
+    thread::sleep(Duration::from_millis(1500));
+    let kept = has_any_ignore_attribute(&log_file);
+
+    // The central policy changes: the daemon re-reconciles on its next refresh
+    std::fs::write(&policy, "*.tmp\n").unwrap();
+    let switched =
+        wait_for(&|| has_any_ignore_attribute(&tmp_file) && !has_any_ignore_attribute(&log_file));
+
+    let _ = child.kill();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(initial, "initial patterns not applied: {}", stdout);
+    assert!(kept, "empty pattern list should be rejected");
+    assert!(stderr.contains("pattern list is empty"));
+    assert!(switched, "changed patterns not applied: {}", stdout);
+    assert!(stdout.contains("Pattern list changed"));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_watch_close_write_waits_for_writer() {
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .output()
+        .expect("Failed to init git");
+
+    std::fs::write(temp_dir.path().join("policy.txt"), "*.bin\n").unwrap();
+    let seed = temp_dir.path().join("seed.bin");
+    std::fs::write(&seed, "seed").unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .args([
+            "--watch",
+            "--daemon-mode",
+            "--close-write",
+            "--patterns-file",
+            "policy.txt",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start daemon");
+
+    let wait_for = |condition: &dyn Fn() -> bool| {
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while !condition() && std::time::Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(100));
+        }
+        condition()
+    };
+
+    let started = wait_for(&|| has_any_ignore_attribute(&seed));
+    // Let the watcher get registered after the initial scan
+    thread::sleep(Duration::from_millis(500));
+
+    // A large file is still being written...
+    let big = temp_dir.path().join("big.bin");
+    let mut writer = std::fs::File::create(&big).unwrap();
+    writer.write_all(&[0u8; 4096]).unwrap();
+    writer.flush().unwrap();
+
+    // ...while another file finishes and triggers a re-scan
+    let other = temp_dir.path().join("other.bin");
+    std::fs::write(&other, "done").unwrap();
+    let other_marked = wait_for(&|| has_any_ignore_attribute(&other));
+    let marked_while_writing = has_any_ignore_attribute(&big);
+
+    writer.write_all(&[1u8; 4096]).unwrap();
+    drop(writer);
+    let marked_after_close = wait_for(&|| has_any_ignore_attribute(&big));
+
+    let _ = child.kill();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(started, "initial scan did not run: {}", stdout);
+    assert!(other_marked, "closed file not marked: {}", stdout);
+    assert!(!marked_while_writing, "file marked while still open");
+    assert!(
+        marked_after_close,
+        "file not marked after close: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_watch_pidfile_created_and_removed_on_unwatch() {
+    let temp_dir = TempDir::new().unwrap();
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .output()
+        .expect("Failed to init git");
+
+    let pidfile = temp_dir.path().join("run").join("dbx-ignore.pid");
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .args(["--watch", "--pidfile", "run/dbx-ignore.pid"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while !pidfile.exists() && std::time::Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+    }
+    let contents = std::fs::read_to_string(&pidfile).unwrap_or_default();
+
+    let _ = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .arg("--unwatch")
+        .output();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!("PID: {}", contents.trim())),
+        "pidfile {:?} doesn't match daemon: {}",
+        contents,
+        stdout
+    );
+    assert!(!pidfile.exists(), "pidfile left behind after --unwatch");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_watch_pidfile_removed_on_clean_shutdown() {
+    let temp_dir = TempDir::new().unwrap();
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .output()
+        .expect("Failed to init git");
+
+    let pidfile = temp_dir.path().join("watcher.pid");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .args(["--watch", "--daemon-mode", "--pidfile", "watcher.pid"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start daemon");
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while !pidfile.exists() && std::time::Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+    }
+    let contents = std::fs::read_to_string(&pidfile).unwrap_or_default();
+
+    // Ctrl+C shuts the watcher down cleanly
+    Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while child.try_wait().unwrap().is_none() && std::time::Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+
+    assert_eq!(contents.trim(), child.id().to_string());
+    assert!(!pidfile.exists(), "pidfile left behind after shutdown");
+}
+
+#[test]
+fn test_watch_exits_after_max_runtime() {
+    let temp_dir = TempDir::new().unwrap();
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .output()
+        .expect("Failed to init git");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .args(["--watch", "--max-runtime", "2s"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    let status_file = dbx_ignore::core::daemon::DaemonStatus::status_file_path(temp_dir.path());
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while !status_file.exists() && std::time::Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+    }
+    assert!(status_file.exists(), "daemon never started");
+
+    // No --unwatch: the daemon stops on its own and cleans up after itself
+    let deadline = std::time::Instant::now() + Duration::from_secs(20);
+    while status_file.exists() && std::time::Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+    }
+    assert!(
+        !status_file.exists(),
+        "status file left behind after --max-runtime"
+    );
+}
+
+#[test]
+fn test_unwatch_all_stops_every_registered_daemon() {
+    let config_home = TempDir::new().unwrap();
+    let repos: Vec<TempDir> = (0..2).map(|_| TempDir::new().unwrap()).collect();
+    let dbx_ignore = |dir: &std::path::Path, arg: &str| {
+        Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+            .current_dir(dir)
+            .env("XDG_CONFIG_HOME", config_home.path())
+            .env("NO_COLOR", "1")
+            .arg(arg)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    for repo in &repos {
+        Command::new("git")
+            .current_dir(repo.path())
+            .args(["init"])
+            .output()
+            .expect("Failed to init git");
+        assert!(dbx_ignore(repo.path(), "--watch").status.success());
+    }
+    thread::sleep(Duration::from_millis(500));
+
+    // A daemon that already exited leaves a stale entry behind
+    let registry_file = config_home.path().join("dbx-ignore").join("daemons.json");
+    let mut exited = Command::new("true").spawn().unwrap();
+    exited.wait().unwrap();
+    let mut registry: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&registry_file).unwrap()).unwrap();
+    assert_eq!(registry["daemons"].as_array().unwrap().len(), 2);
+    registry["daemons"]
+        .as_array_mut()
+        .unwrap()
+        .push(serde_json::json!({
+            "pid": exited.id(),
+            "repo_path": config_home.path(),
+        }));
+    std::fs::write(&registry_file, registry.to_string()).unwrap();
+
+    let output = dbx_ignore(config_home.path(), "--unwatch-all");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("Stopped daemon watcher for").count(), 2);
+    assert!(stdout.contains("Removed 1 stale registry entry(ies)"));
+
+    for repo in &repos {
+        assert!(!repo.path().join(".dbx-ignore").join("daemon.json").exists());
+    }
+    let registry: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&registry_file).unwrap()).unwrap();
+    assert!(registry["daemons"].as_array().unwrap().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_daemon_cleans_up_on_sigterm() {
+    let temp_dir = TempDir::new().unwrap();
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .output()
+        .expect("Failed to init git");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .arg("--watch")
+        .output()
+        .expect("Failed to start daemon");
+    assert!(output.status.success());
+
+    let state_dir = temp_dir.path().join(".dbx-ignore");
+    let status_file = state_dir.join("daemon.json");
+    let mut status = None;
+    for _ in 0..50 {
+        // The daemon may still be writing the file
+        if let Ok(contents) = std::fs::read_to_string(&status_file)
+            && let Ok(value) = serde_json::from_str::<serde_json::Value>(&contents)
+        {
+            status = Some(value);
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    let status = status.expect("daemon never wrote its status file");
+    let pid = status["pid"].as_u64().unwrap().to_string();
+
+    // A plain TERM, as a supervisor would send, rather than --unwatch
+    Command::new("kill").args(["-TERM", &pid]).status().unwrap();
+    let is_running = || {
+        Command::new("kill")
+            .args(["-0", &pid])
+            .stderr(Stdio::null())
+            .status()
+            .unwrap()
+            .success()
+    };
+    for _ in 0..50 {
+        if !is_running() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    assert!(!is_running(), "daemon did not exit on SIGTERM");
+    assert!(!status_file.exists(), "daemon left its status file behind");
+
+    let log = std::fs::read_to_string(state_dir.join("daemon.log")).unwrap();
+    assert!(log.contains("Watcher stopped after"));
+    assert!(log.contains("Daemon watcher stopped"));
+}
+
+#[test]
+fn test_debounce_flag_is_validated_and_clamped() {
+    let temp_dir = TempDir::new().unwrap();
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .output()
+        .expect("Failed to init git");
+
+    let rejected = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .args(["--watch", "--debounce", "0"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!rejected.status.success());
+
+    // Too short a window is raised to the minimum
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .args(["--watch", "--daemon-mode", "--debounce", "10"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start daemon");
+    thread::sleep(Duration::from_millis(500));
+    let _ = child.kill();
+    let output = child.wait_with_output().unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Debounce: 50ms"));
+}
+
+#[test]
+fn test_watch_dry_run_only_logs_marker_changes() {
+    use dbx_ignore::utils::platform_utils::has_any_ignore_attribute;
+
+    let temp_dir = TempDir::new().unwrap();
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .output()
+        .expect("Failed to init git");
+    let log_file = temp_dir.path().join("app.log");
+    std::fs::write(temp_dir.path().join(".gitignore"), "*.log").unwrap();
+    std::fs::write(&log_file, "log").unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(temp_dir.path())
+        .args(["--watch", "--daemon-mode", "--dry-run"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start daemon");
+    thread::sleep(Duration::from_millis(500));
+    let _ = child.kill();
+    let output = child.wait_with_output().unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Observe mode"));
+    assert!(stdout.contains("Would add ignore marker to"));
+    assert!(!has_any_ignore_attribute(&log_file));
+}
@@ -11,6 +11,7 @@ fn test_wildcard_single_pattern() {
     let _test3 = env.create_file("test3.md", "content");
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args(["--dry-run"]) // Remove --quiet to see more output
         .arg(env.path().join("*.txt").to_str().unwrap())
         .output()
@@ -41,6 +42,7 @@ fn test_wildcard_multiple_patterns() {
     let _test4 = env.create_file("test4.md", "content");
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args(["--dry-run", "--quiet"])
         .arg(env.path().join("*.txt").to_str().unwrap())
         .arg(env.path().join("*.md").to_str().unwrap())
@@ -58,6 +60,7 @@ fn test_wildcard_all_files() {
     let _dir = env.create_dir("testdir");
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args(["--dry-run", "--quiet"])
         .arg(env.path().join("*").to_str().unwrap())
         .output()
@@ -93,6 +96,7 @@ fn test_wildcard_with_subdirectory() {
     std::fs::write(subdir.join("file2.rs"), "content").unwrap();
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args(["--dry-run", "--verbose"])
         .arg(env.path().join("subdir/*.rs").to_str().unwrap())
         .output()
@@ -113,6 +117,7 @@ fn test_mixed_wildcard_and_literal() {
     let _test3 = env.create_file("test3.md", "content");
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args(["--dry-run", "--verbose"])
         .arg(test1.to_str().unwrap()) // literal path
         .arg(env.path().join("*.md").to_str().unwrap()) // wildcard
@@ -135,6 +140,7 @@ fn test_wildcard_question_mark() {
     let _test = env.create_file("test.txt", "content");
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args(["--dry-run", "--verbose"])
         .arg(env.path().join("test?.txt").to_str().unwrap())
         .output()
@@ -156,6 +162,7 @@ fn test_wildcard_brackets() {
     let _test3 = env.create_file("test3.txt", "content");
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args(["--dry-run", "--verbose"])
         .arg(env.path().join("test[12].txt").to_str().unwrap())
         .output()
@@ -178,6 +185,7 @@ fn test_wildcard_folders() {
     let _src = env.create_dir("src");
 
     let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .current_dir(env.path())
         .args(["--dry-run", "--verbose"])
         .arg(env.path().join("build*").to_str().unwrap())
         .output()
@@ -192,3 +200,75 @@ fn test_wildcard_folders() {
     assert!(!stdout.contains("src")); // shouldn't match
     assert!(stdout.contains("files would be processed"));
 }
+
+#[test]
+fn test_wildcard_brace_expansion() {
+    let env = TestEnvironment::new();
+    env.create_file("app.log", "content");
+    env.create_file("cache.tmp", "content");
+    env.create_file("notes.md", "content");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--dry-run", "--verbose", "*.{log,tmp}"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("app.log"));
+    assert!(stdout.contains("cache.tmp"));
+    assert!(!stdout.contains("notes.md"));
+}
+
+#[test]
+fn test_wildcard_star_stays_at_top_level() {
+    let env = TestEnvironment::new();
+    env.create_file("top.log", "content");
+    let sub = env.create_dir("sub");
+    std::fs::write(sub.join("a.log"), "content").unwrap();
+
+    // Shell rules, unlike watch-mode patterns where `*.log` matches at any depth
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--dry-run", "--verbose", "*.log"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("top.log"));
+    assert!(!stdout.contains("a.log"));
+}
+
+#[test]
+fn test_wildcard_double_star_matches_at_any_depth() {
+    let env = TestEnvironment::new();
+    env.create_file("top.log", "content");
+    let nested = env.create_dir("a/b");
+    std::fs::write(nested.join("deep.log"), "content").unwrap();
+    std::fs::write(nested.join("deep.txt"), "content").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--dry-run", "--verbose", "**/*.log"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("top.log"));
+    assert!(stdout.contains("deep.log"));
+    assert!(!stdout.contains("deep.txt"));
+
+    // A single `*` stays within one directory level
+    let output = Command::new(env!("CARGO_BIN_EXE_dbx-ignore"))
+        .args(["--dry-run", "--verbose", "a/*.log"])
+        .current_dir(env.path())
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("No files found matching pattern"));
+}
@@ -13,9 +13,8 @@ mod windows_tests {
         assert!(WindowsHandler::is_supported());
 
         let attrs = WindowsHandler::get_target_attributes();
-        assert_eq!(attrs.len(), 2);
+        assert_eq!(attrs.len(), 1);
         assert!(attrs.contains(&"com.dropbox.ignored"));
-        assert!(attrs.contains(&"com.apple.fileprovider.ignore#P"));
     }
 
     #[test]